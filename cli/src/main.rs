@@ -3,15 +3,61 @@ use {
     clap::{ App, Arg, ArgMatches, SubCommand },
     p2p_swap::{
         SwapSPLOrder,
+        StartAt,
+        OrderCounter,
+        SellerEpoch,
+        Market,
+        OrderBook,
+        Config,
+        Proposal,
+        Deal,
+        PayoutSplit,
+        Rfq,
+        Quote,
+        Auction,
+        SealedAuction,
+        SealedBid,
+        Flip,
+        FiatTrade,
+        ArbitrationConfig,
+        Reputation,
+        TradeReceipt,
+        Delegate,
+        MintListMode,
+        MintStatus,
+        get_config_address,
+        get_mint_list_entry_address,
+        get_proposal_address,
+        get_deal_address,
+        get_payout_split_address,
+        get_rfq_address,
+        get_quote_address,
+        get_auction_address,
+        get_sealed_auction_address,
+        get_sealed_bid_address,
+        get_bid_commitment,
+        get_flip_address,
+        get_fiat_trade_address,
+        get_arbitration_config_address,
+        get_reputation_address,
+        get_trade_receipt_address,
+        get_delegate_address,
+        get_seller_epoch_address,
+        get_order_counter_address,
+        get_market_address,
+        get_order_book_address,
         get_order_wallet_address,
         get_order_wallet_authority,
         get_order_address,
         P2PSwapInstructions,
+        PERMISSION_AMEND,
+        PERMISSION_PAUSE,
+        PERMISSION_REVOKE,
     },
     solana_client::{
         rpc_client::{ RpcClient },
         rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
-        rpc_filter::RpcFilterType,
+        rpc_filter::{Memcmp, RpcFilterType},
     },
     solana_clap_utils::{
         input_validators::{is_valid_pubkey, is_url_or_moniker, normalize_to_url_if_moniker},
@@ -31,7 +77,15 @@ use {
         transaction::Transaction,
     },
     spl_token::state::Account as SPLAccount,
-    std::{ process::exit, str::FromStr },
+    std::{
+        fs,
+        io::Write,
+        path::Path,
+        process::exit,
+        str::FromStr,
+        thread::sleep,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
     log::{warn},
 };
 
@@ -46,6 +100,39 @@ fn is_valid_u64<T>(value: T) -> Result<(), String>
     Ok(())
 }
 
+fn is_valid_bps<T>(value: T) -> Result<(), String>
+    where
+        T: AsRef<str>,
+{
+    let str_ref = value.as_ref();
+    match str_ref.parse::<u16>() {
+        Ok(bps) if bps < 10_000 => Ok(()),
+        Ok(_) => Err(format!("Basis points {:?} should be less than 10000", str_ref)),
+        Err(err) => Err(format!("Failed to parse basis points {:?}: {:?}", str_ref, err)),
+    }
+}
+
+fn parse_currency<T>(value: T) -> Result<[u8; 3], String>
+    where
+        T: AsRef<str>,
+{
+    let str_ref = value.as_ref().to_uppercase();
+    if str_ref.len() != 3 || !str_ref.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(format!("Unexpected currency {:?}. Expected ISO 4217 code (e.g. USD)", str_ref));
+    }
+
+    let mut currency = [0u8; 3];
+    currency.copy_from_slice(str_ref.as_bytes());
+    Ok(currency)
+}
+
+fn is_valid_currency<T>(value: T) -> Result<(), String>
+    where
+        T: AsRef<str>,
+{
+    parse_currency(value).map(|_| ())
+}
+
 fn is_valid_bool<T>(value: T) -> Result<(), String>
     where
         T: AsRef<str>,
@@ -58,6 +145,53 @@ fn is_valid_bool<T>(value: T) -> Result<(), String>
     }
 }
 
+fn parse_start_at<T>(value: T) -> Result<StartAt, String>
+    where
+        T: AsRef<str>,
+{
+    let str_ref = value.as_ref();
+    match str_ref.split_once(':') {
+        Some(("slot", slot)) => slot.parse::<u64>()
+            .map(StartAt::Slot)
+            .map_err(|err| format!("Failed to parse slot {:?}: {:?}", slot, err)),
+        Some(("time", unix_timestamp)) => unix_timestamp.parse::<i64>()
+            .map(StartAt::UnixTimestamp)
+            .map_err(|err| format!("Failed to parse unix timestamp {:?}: {:?}", unix_timestamp, err)),
+        _ => Err(format!("Unexpected value {:?}. Expected slot:<SLOT> or time:<UNIX_TIMESTAMP>", str_ref))
+    }
+}
+
+fn is_valid_start_at<T>(value: T) -> Result<(), String>
+    where
+        T: AsRef<str>,
+{
+    parse_start_at(value).map(|_| ())
+}
+
+fn parse_payout<T>(value: T) -> Result<(Pubkey, u16), String>
+    where
+        T: AsRef<str>,
+{
+    let str_ref = value.as_ref();
+    match str_ref.rsplit_once(':') {
+        Some((address, bps)) => {
+            let address = Pubkey::from_str(address)
+                .map_err(|err| format!("Failed to parse address {:?}: {:?}", address, err))?;
+            let bps = bps.parse::<u16>()
+                .map_err(|err| format!("Failed to parse basis points {:?}: {:?}", bps, err))?;
+            Ok((address, bps))
+        },
+        None => Err(format!("Unexpected value {:?}. Expected <ADDRESS>:<BPS>", str_ref))
+    }
+}
+
+fn is_valid_payout<T>(value: T) -> Result<(), String>
+    where
+        T: AsRef<str>,
+{
+    parse_payout(value).map(|_| ())
+}
+
 fn parse_bool<T>(value: T) -> Result<bool, String>
     where
         T: AsRef<str>,
@@ -124,15 +258,15 @@ impl AppContext {
         )
     }
 
-    pub fn send_transaction(&self, instructions: &Vec<Instruction>) -> Result<Signature, String> {
+    pub fn send_transaction(&self, instructions: &[Instruction]) -> Result<Signature, String> {
         let mut transaction =
             Transaction::new_with_payer(instructions, Some(&self.signer.pubkey()));
 
         let blockhash = self.client.get_latest_blockhash()
             .map_err(|err| format!("Failed to get latest blockhash: {:?}", err))?;
-        transaction.try_partial_sign(&[self.signer.as_ref().clone()], blockhash)
+        transaction.try_partial_sign(&[self.signer.as_ref()], blockhash)
             .map_err(|err| format!("Failed to partial sign: {:?}", err))?;
-        transaction.try_sign(&[self.signer.as_ref().clone()], blockhash)
+        transaction.try_sign(&[self.signer.as_ref()], blockhash)
             .map_err(|err| format!("Failed to sign: {:?}", err))?;
         self.client.send_transaction(&transaction).map_err(|err| format!("Failed to send transaction: {:?}", err))
     }
@@ -177,7 +311,7 @@ impl AppContext {
 
         debug_assert_eq!(instruction_data.len(), signature_offset);
 
-        instruction_data.extend_from_slice(&signature.as_ref());
+        instruction_data.extend_from_slice(signature.as_ref());
 
         debug_assert_eq!(instruction_data.len(), message_data_offset);
 
@@ -191,20 +325,29 @@ impl AppContext {
     }
 }
 
-fn find_free_order_account(context: &AppContext) -> (Pubkey, u64) {
-    let latest_slot = context.client.get_slot().unwrap();
-    let (pubkey, _) = get_order_address(
+fn find_free_order_account(context: &AppContext) -> Result<(Pubkey, u64), String> {
+    let (order_counter, _) = get_order_counter_address(
         &context.p2p_swap,
         &context.signer.pubkey(),
-        latest_slot,
     );
 
-    if let Err(_) = context.client.get_account(&pubkey) {
-        // account absent
-        return (pubkey, latest_slot)
-    }
+    let order_index = match context.client.get_account(&order_counter) {
+        Ok(account) if account.owner == context.p2p_swap => {
+            OrderCounter::unpack(&account.data)
+                .map_err(|_| format!("Failed to parse OrderCounter from account {:?}", order_counter))?
+                .next_order_index
+        },
+        // counter absent - it will be created along with the first order
+        _ => 0,
+    };
 
-    panic!("Unable to generate new order address");
+    let (pubkey, _) = get_order_address(
+        &context.p2p_swap,
+        &context.signer.pubkey(),
+        order_index,
+    );
+
+    Ok((pubkey, order_index))
 }
 
 fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
@@ -215,13 +358,21 @@ fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
         let buy_token = Pubkey::try_from(args.value_of("buy-token").unwrap()).unwrap();
         let buy_amount = args.value_of("buy-amount").unwrap().parse::<u64>().unwrap();
         let is_private = parse_bool(args.value_of("is_private").unwrap()).unwrap();
+        let start_at = args.value_of("start_at")
+            .map_or(StartAt::Immediately, |start_at| parse_start_at(start_at).unwrap());
+        let payout_wallet = args.value_of("payout_wallet")
+            .map_or(Pubkey::default(), |payout_wallet| Pubkey::from_str(payout_wallet).unwrap());
+        // system program means the order is not gated
+        let gate_mint = args.value_of("gate_mint")
+            .map_or(solana_sdk::system_program::id(), |gate_mint| Pubkey::from_str(gate_mint).unwrap());
+        let gate_min_balance = args.value_of("gate_min_balance").unwrap().parse::<u64>().unwrap();
 
         let signer_wallet = spl_associated_token_account::get_associated_token_address(
             &context.signer.pubkey(),
             &sell_token,
         );
 
-        if context.client.get_account(&signer_wallet).ok() == None {
+        if context.client.get_account(&signer_wallet).is_err() {
             panic!("Seller has no wallet for token {:?}", sell_token);
         }
 
@@ -235,7 +386,7 @@ fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
 
         let mut instructions = Vec::new();
 
-        if context.client.get_account(&order_wallet).ok() == None {
+        if context.client.get_account(&order_wallet).is_err() {
             instructions.push(
                 spl_associated_token_account::instruction::create_associated_token_account(
                     &context.signer.pubkey(),
@@ -255,12 +406,18 @@ fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
             sell_amount,
         ).unwrap());
 
-        let (order_account, order_seed) = find_free_order_account(context);
+        let (order_account, order_seed) = find_free_order_account(context).unwrap();
+        let (order_counter, _) = get_order_counter_address(&context.p2p_swap, &context.signer.pubkey());
+        let (market, _) = get_market_address(&context.p2p_swap, &sell_token, &buy_token);
+        let (order_book, _) = get_order_book_address(&context.p2p_swap, &sell_token, &buy_token);
+        let (seller_epoch, _) = get_seller_epoch_address(&context.p2p_swap, &context.signer.pubkey());
 
         let mut sell_amount = sell_amount.to_le_bytes().to_vec();
         let mut buy_amount = buy_amount.to_le_bytes().to_vec();
         let mut min_sell_amount = sell_minimum.to_le_bytes().to_vec();
         let order_seed_arr = order_seed.to_le_bytes().to_vec();
+        let mut start_at_arr = [0u8; StartAt::LEN];
+        start_at.pack(&mut start_at_arr);
 
         if is_private {
             let mut data: Vec<u8> = vec![P2PSwapInstructions::CreatePrivateOrder as u8];
@@ -268,19 +425,29 @@ fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
             data.append(&mut buy_amount);
             data.append(&mut min_sell_amount);
             data.append(&mut order_seed_arr.clone());
+            data.extend_from_slice(&start_at_arr);
+            data.extend_from_slice(&gate_min_balance.to_le_bytes());
             instructions.push(Instruction {
-                program_id: context.p2p_swap.clone(),
+                program_id: context.p2p_swap,
                 accounts: vec![
-                    AccountMeta::new_readonly(solana_sdk::sysvar::clock::id(), false),
                     AccountMeta::new(context.signer.pubkey(), true),
                     AccountMeta::new(signer_wallet, false),
-                    AccountMeta::new_readonly(sell_token.clone(), false),
-                    AccountMeta::new_readonly(order_wallet_authority.clone(), false),
-                    AccountMeta::new_readonly(buy_token.clone(), false),
+                    AccountMeta::new_readonly(sell_token, false),
+                    AccountMeta::new_readonly(order_wallet_authority, false),
+                    AccountMeta::new_readonly(buy_token, false),
                     AccountMeta::new(order_wallet, false),
                     AccountMeta::new_readonly(spl_token::id(), false),
                     AccountMeta::new(order_account, false),
                     AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new(order_counter, false),
+                    AccountMeta::new_readonly(market, false),
+                    AccountMeta::new(order_book, false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                    AccountMeta::new_readonly(get_mint_list_entry_address(&context.p2p_swap, &sell_token).0, false),
+                    AccountMeta::new_readonly(get_mint_list_entry_address(&context.p2p_swap, &buy_token).0, false),
+                    AccountMeta::new_readonly(payout_wallet, false),
+                    AccountMeta::new_readonly(seller_epoch, false),
+                    AccountMeta::new_readonly(gate_mint, false),
                 ],
                 data,
             });
@@ -290,24 +457,57 @@ fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
             data.append(&mut buy_amount);
             data.append(&mut min_sell_amount);
             data.append(&mut order_seed_arr.clone());
+            data.extend_from_slice(&start_at_arr);
+            data.extend_from_slice(&gate_min_balance.to_le_bytes());
             instructions.push(Instruction {
-                program_id: context.p2p_swap.clone(),
+                program_id: context.p2p_swap,
                 accounts: vec![
-                    AccountMeta::new_readonly(solana_sdk::sysvar::clock::id(), false),
                     AccountMeta::new(context.signer.pubkey(), true),
                     AccountMeta::new(signer_wallet, false),
-                    AccountMeta::new_readonly(sell_token.clone(), false),
-                    AccountMeta::new_readonly(order_wallet_authority.clone(), false),
-                    AccountMeta::new_readonly(buy_token.clone(), false),
+                    AccountMeta::new_readonly(sell_token, false),
+                    AccountMeta::new_readonly(order_wallet_authority, false),
+                    AccountMeta::new_readonly(buy_token, false),
                     AccountMeta::new(order_wallet, false),
                     AccountMeta::new_readonly(spl_token::id(), false),
                     AccountMeta::new(order_account, false),
                     AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new(order_counter, false),
+                    AccountMeta::new_readonly(market, false),
+                    AccountMeta::new(order_book, false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                    AccountMeta::new_readonly(get_mint_list_entry_address(&context.p2p_swap, &sell_token).0, false),
+                    AccountMeta::new_readonly(get_mint_list_entry_address(&context.p2p_swap, &buy_token).0, false),
+                    AccountMeta::new_readonly(payout_wallet, false),
+                    AccountMeta::new_readonly(seller_epoch, false),
+                    AccountMeta::new_readonly(gate_mint, false),
                 ],
                 data,
             });
         }
 
+        if let Some(payouts) = args.values_of("payout") {
+            let mut data: Vec<u8> = vec![P2PSwapInstructions::SetPayoutSplit as u8];
+            let mut accounts = vec![
+                AccountMeta::new(context.signer.pubkey(), true),
+                AccountMeta::new_readonly(order_account, false),
+                AccountMeta::new(get_payout_split_address(&context.p2p_swap, &order_account).0, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_flip_address(&context.p2p_swap, &order_account).0, false),
+            ];
+            for payout in payouts {
+                let (address, bps) = parse_payout(payout).unwrap();
+                let (_, wallet) = resolve_token_account(context, &address, &buy_token);
+                data.extend_from_slice(&bps.to_le_bytes());
+                accounts.push(AccountMeta::new_readonly(wallet, false));
+            }
+
+            instructions.push(Instruction {
+                program_id: context.p2p_swap,
+                accounts,
+                data,
+            });
+        }
+
         let signature = context.send_transaction(&instructions).unwrap();
 
         println!("\n\nNew order created: {:?}", order_account);
@@ -323,10 +523,139 @@ fn process_create_order(context: &AppContext, args: &Option<&ArgMatches>) {
     }
 }
 
+// Address can be either token account of the mint or owner wallet (its associated token account
+// is used then). Returns owner and token account
+fn resolve_token_account(context: &AppContext, address: &Pubkey, mint: &Pubkey) -> (Pubkey, Pubkey) {
+    match context.client.get_account(address).ok()
+        .filter(|account| account.owner == spl_token::id())
+        .and_then(|account| SPLAccount::unpack(&account.data).ok())
+        .filter(|wallet| wallet.mint == *mint)
+    {
+        Some(wallet) => (wallet.owner, *address),
+        None => (
+            *address,
+            spl_associated_token_account::get_associated_token_address(address, mint),
+        ),
+    }
+}
+
+// Payout split account of the order followed by wallets of split recipients
+fn get_payout_split_accounts(context: &AppContext, order: &Pubkey) -> Vec<AccountMeta> {
+    let (payout_split, _) = get_payout_split_address(&context.p2p_swap, order);
+    let mut accounts = vec![AccountMeta::new_readonly(payout_split, false)];
+    let account = context.client.get_account(&payout_split).ok()
+        .filter(|account| account.owner == context.p2p_swap);
+    if let Some(account) = account {
+        let payout_split = PayoutSplit::unpack(&account.data).unwrap();
+        for recipient in payout_split.recipients {
+            accounts.push(AccountMeta::new(recipient.wallet, false));
+        }
+    }
+
+    accounts
+}
+
+fn get_flip(context: &AppContext, order: &Pubkey) -> Option<(Pubkey, Flip)> {
+    let (flip, _) = get_flip_address(&context.p2p_swap, order);
+    context.client.get_account(&flip).ok()
+        .filter(|account| account.owner == context.p2p_swap)
+        .map(|account| (flip, Flip::unpack(&account.data).unwrap()))
+}
+
+// Flip account of the order followed by reverse order, its wallet and order book. Reverse order
+// accounts are not used if the order is not a flip order (system program is passed instead)
+fn get_flip_accounts(context: &AppContext, order: &Pubkey) -> Vec<AccountMeta> {
+    let (flip_address, _) = get_flip_address(&context.p2p_swap, order);
+    let reverse_order = get_flip(context, order)
+        .and_then(|(_, flip)| get_order(context, &flip.reverse_order).ok().map(|reverse| (flip, reverse)));
+
+    match reverse_order {
+        Some((flip, reverse_order)) => {
+            let (reverse_order_book, _) = get_order_book_address(
+                &context.p2p_swap,
+                &reverse_order.token_mint,
+                &reverse_order.price_mint,
+            );
+            vec![
+                AccountMeta::new_readonly(flip_address, false),
+                AccountMeta::new(flip.reverse_order, false),
+                AccountMeta::new(reverse_order.order_wallet, false),
+                AccountMeta::new(reverse_order_book, false),
+            ]
+        },
+        None => vec![
+            AccountMeta::new_readonly(flip_address, false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+    }
+}
+
+// Operator (signer) and its delegate account are appended to management instructions when
+// signer is not the seller
+fn get_operator_accounts(context: &AppContext, seller: &Pubkey) -> Vec<AccountMeta> {
+    let operator = context.signer.pubkey();
+    if operator == *seller {
+        return vec![];
+    }
+
+    vec![
+        AccountMeta::new(operator, true),
+        AccountMeta::new_readonly(get_delegate_address(&context.p2p_swap, seller, &operator).0, false),
+    ]
+}
+
+fn get_seller_epoch(context: &AppContext, seller: &Pubkey) -> u64 {
+    let (seller_epoch, _) = get_seller_epoch_address(&context.p2p_swap, seller);
+    context.client.get_account(&seller_epoch).ok()
+        .filter(|account| account.owner == context.p2p_swap)
+        .map_or(0, |account| SellerEpoch::unpack(&account.data).unwrap().epoch)
+}
+
+fn get_reputation(context: &AppContext, wallet: &Pubkey) -> Option<(Pubkey, Reputation)> {
+    let (reputation, _) = get_reputation_address(&context.p2p_swap, wallet);
+    context.client.get_account(&reputation).ok()
+        .filter(|account| account.owner == context.p2p_swap)
+        .map(|account| (reputation, Reputation::unpack(&account.data).unwrap()))
+}
+
+// Optional trailing accounts of settlement instructions. Trade is recorded only when both sides have reputation
+fn get_trade_reputation_accounts(context: &AppContext, seller: &Pubkey, buyer: &Pubkey) -> Vec<AccountMeta> {
+    match (get_reputation(context, seller), get_reputation(context, buyer)) {
+        (Some((seller_reputation, _)), Some((buyer_reputation, _))) => vec![
+            AccountMeta::new(seller_reputation, false),
+            AccountMeta::new(buyer_reputation, false),
+        ],
+        _ => vec![],
+    }
+}
+
+fn print_reputation(context: &AppContext, wallet: &Pubkey) {
+    match get_reputation(context, wallet) {
+        Some((reputation_address, reputation)) => {
+            println!("Reputation {:?}", reputation_address);
+            println!("{:#?}", reputation);
+            if reputation.rating_count > 0 {
+                println!(
+                    "Average rating: {:.2} ({} ratings)",
+                    reputation.rating_sum as f64 / reputation.rating_count as f64,
+                    reputation.rating_count,
+                );
+            }
+        },
+        None => println!("Wallet {:?} has no reputation account", wallet),
+    }
+}
+
 fn get_order(context: &AppContext, order: &Pubkey) -> Result<SwapSPLOrder, String> {
     let order = context.client.get_account(order)
         .map_err(|_| format!("Order {:?} not found", order))?;
 
+    if order.data.len() == SwapSPLOrder::LEGACY_LEN {
+        return Err("Order is in legacy layout, convert it with migrate-order first".to_string());
+    }
+
     SwapSPLOrder::unpack(&order.data)
         .map_err(|_| format!("Failed to parse SwapSPLOrder from account {:?} data", order))
 }
@@ -337,6 +666,69 @@ fn process_get_order(context: &AppContext, args: &Option<&ArgMatches>) {
         let order = get_order(context, &order_address).unwrap();
         println!("\n\nOrder {:?}", order_address);
         println!("{:#?}", order);
+
+        let (payout_split, _) = get_payout_split_address(&context.p2p_swap, &order_address);
+        let account = context.client.get_account(&payout_split).ok()
+            .filter(|account| account.owner == context.p2p_swap);
+        if let Some(account) = account {
+            println!("Payout split {:?}", payout_split);
+            println!("{:#?}", PayoutSplit::unpack(&account.data).unwrap());
+        }
+
+        if let Some((flip_address, flip)) = get_flip(context, &order_address) {
+            println!("Flip {:?}", flip_address);
+            println!("{:#?}", flip);
+        }
+
+        print_reputation(context, &order.seller);
+
+        let seller_epoch = get_seller_epoch(context, &order.seller);
+        if order.epoch < seller_epoch {
+            println!(
+                "Order is cancelled by seller (order epoch {}, seller epoch {}). Anyone can revoke it to return \
+                tokens to seller",
+                order.epoch,
+                seller_epoch,
+            );
+        }
+
+        if order.gate_mint != Pubkey::default() {
+            println!(
+                "Order is gated: buyer should hold at least {} of token {}",
+                order.gate_min_balance,
+                order.gate_mint,
+            );
+        }
+
+        match order.start_at {
+            StartAt::Immediately => {},
+            StartAt::Slot(start_slot) => {
+                let slot = context.client.get_slot().unwrap();
+                if slot < start_slot {
+                    println!("Order starts in {} slots (at slot {})", start_slot - slot, start_slot);
+                } else {
+                    println!("Order started at slot {}", start_slot);
+                }
+            },
+            StartAt::UnixTimestamp(start_timestamp) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs() as i64;
+                if now < start_timestamp {
+                    let countdown = start_timestamp - now;
+                    println!(
+                        "Order starts in {}h {}m {}s (at unix timestamp {})",
+                        countdown / 3600,
+                        countdown % 3600 / 60,
+                        countdown % 60,
+                        start_timestamp,
+                    );
+                } else {
+                    println!("Order started at unix timestamp {}", start_timestamp);
+                }
+            },
+        }
     }
 }
 
@@ -353,11 +745,18 @@ fn get_order_token_mint(context: &AppContext, order: &SwapSPLOrder) -> Result<Pu
 
 fn process_buy_order(context: &AppContext, args: &Option<&ArgMatches>) {
     if let Some(args) = args {
-        let sell_token_amount = args.value_of("sell_token_amount").unwrap().parse::<u64>().unwrap();
-
         let order_address = Pubkey::try_from(args.value_of("order_address").unwrap()).unwrap();
         let order = get_order(context, &order_address).unwrap();
 
+        let (instruction, amount, buy_token_amount) = if let Some(spend) = args.value_of("spend") {
+            let spend = spend.parse::<u64>().unwrap();
+            (P2PSwapInstructions::FillOrderExactSpend, spend, spend)
+        } else {
+            let sell_token_amount = args.value_of("sell_token_amount").unwrap().parse::<u64>().unwrap();
+            let buy_token_amount = order.price_for_amount(sell_token_amount).unwrap();
+            (P2PSwapInstructions::FillOrder, sell_token_amount, buy_token_amount)
+        };
+
         let order_wallet_authority =
             get_order_wallet_authority(&context.p2p_swap, &order.seller).0;
 
@@ -370,21 +769,35 @@ fn process_buy_order(context: &AppContext, args: &Option<&ArgMatches>) {
                 &order.price_mint,
             );
 
-        let seller_buy_token_wallet =
-            spl_associated_token_account::get_associated_token_address(
-                &order.seller,
-                &order.price_mint,
-            );
+        let seller_buy_token_wallet = order.get_payout_wallet();
 
-        let buyer_sell_token_wallet =
-            spl_associated_token_account::get_associated_token_address(
-                &context.signer.pubkey(),
-                &order_token_mint,
-            );
+        let (recipient, recipient_wallet) = match args.value_of("recipient") {
+            Some(recipient) => resolve_token_account(context, &Pubkey::from_str(recipient).unwrap(), &order_token_mint),
+            None => (
+                context.signer.pubkey(),
+                spl_associated_token_account::get_associated_token_address(
+                    &context.signer.pubkey(),
+                    &order_token_mint,
+                ),
+            ),
+        };
+
+        let (market, _) = get_market_address(&context.p2p_swap, &order_token_mint, &order.price_mint);
+        let (order_book, _) = get_order_book_address(&context.p2p_swap, &order_token_mint, &order.price_mint);
+
+        let gate_wallet = if order.gate_mint == Pubkey::default() {
+            solana_sdk::system_program::id()
+        } else {
+            args.value_of("gate_wallet")
+                .map(|gate_wallet| Pubkey::from_str(gate_wallet).unwrap())
+                .unwrap_or_else(|| spl_associated_token_account::get_associated_token_address(
+                    &context.signer.pubkey(),
+                    &order.gate_mint,
+                ))
+        };
 
         let mut instructions = Vec::new();
 
-        let buy_token_amount = (sell_token_amount * order.buy_amount) / order.sell_amount;
         instructions.push(spl_token::instruction::approve(
             &spl_token::id(),
             &buyer_buy_token_wallet,
@@ -412,9 +825,9 @@ fn process_buy_order(context: &AppContext, args: &Option<&ArgMatches>) {
 
         let accounts = {
             let mut accounts = vec![
-                AccountMeta::new_readonly(order.seller.clone(), false), // seller
+                AccountMeta::new_readonly(order.seller, false), // seller
                 AccountMeta::new(context.signer.pubkey(), true),        // buyer
-                AccountMeta::new(order_address.clone(), false),         // order
+                AccountMeta::new(order_address, false),         // order
             ];
 
             if order.is_private {
@@ -423,25 +836,47 @@ fn process_buy_order(context: &AppContext, args: &Option<&ArgMatches>) {
 
             accounts.append(
                 &mut vec![
-                    AccountMeta::new_readonly(order_wallet_authority.clone(), false), // order wallet authority
-                    AccountMeta::new_readonly(order_token_mint.clone(), false),   // sell token mint
-                    AccountMeta::new(order.order_wallet.clone(), false),    // order wallet
+                    AccountMeta::new_readonly(order_wallet_authority, false), // order wallet authority
+                    AccountMeta::new_readonly(order_token_mint, false),   // sell token mint
+                    AccountMeta::new(order.order_wallet, false),    // order wallet
                     AccountMeta::new_readonly(order.price_mint, false),     // buy token mint
                     AccountMeta::new(buyer_buy_token_wallet, false),        // buyer buy token wallet
                     AccountMeta::new(seller_buy_token_wallet, false),       // seller buy token wallet
-                    AccountMeta::new(buyer_sell_token_wallet, false),       // buyer sell token wallet
+                    AccountMeta::new(recipient_wallet, false),              // recipient sell token wallet
+                    AccountMeta::new_readonly(recipient, false),            // recipient
                     AccountMeta::new_readonly(spl_token::id(), false),      // token program
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false), // system program
+                    AccountMeta::new_readonly(spl_associated_token_account::id(), false), // associated token program
+                    AccountMeta::new_readonly(market, false),               // market
+                    AccountMeta::new(order_book, false),                    // order book
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false), // config
+                ]
+            );
+            let mut payout_split_accounts = get_payout_split_accounts(context, &order_address);
+            let mut payout_recipient_wallets = payout_split_accounts.split_off(1);
+            accounts.append(&mut payout_split_accounts); // payout split
+            accounts.append(&mut get_flip_accounts(context, &order_address)); // flip
+            accounts.append(
+                &mut vec![
+                    AccountMeta::new(get_reputation_address(&context.p2p_swap, &order.seller).0, false), // seller reputation
+                    AccountMeta::new(get_reputation_address(&context.p2p_swap, &context.signer.pubkey()).0, false), // buyer reputation
+                    AccountMeta::new(
+                        get_trade_receipt_address(&context.p2p_swap, &order_address, &context.signer.pubkey()).0,
+                        false,
+                    ), // trade receipt
+                    AccountMeta::new_readonly(get_seller_epoch_address(&context.p2p_swap, &order.seller).0, false), // seller epoch
+                    AccountMeta::new_readonly(gate_wallet, false), // buyer gate wallet
                 ]
             );
+            accounts.append(&mut payout_recipient_wallets); // payout split recipient wallets
 
             accounts
         };
 
-        let mut data: Vec<u8> = vec![P2PSwapInstructions::FillOrder as u8];
-        let mut sell_token_amount = sell_token_amount.to_le_bytes().to_vec();
-        data.append(&mut sell_token_amount);
+        let mut data: Vec<u8> = vec![instruction as u8];
+        data.extend_from_slice(&amount.to_le_bytes());
         instructions.push(Instruction {
-            program_id: context.p2p_swap.clone(),
+            program_id: context.p2p_swap,
             accounts,
             data,
         });
@@ -451,6 +886,66 @@ fn process_buy_order(context: &AppContext, args: &Option<&ArgMatches>) {
     }
 }
 
+fn process_set_flip(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+        let reverse_order_address = Pubkey::from_str(args.value_of("reverse_order_address").unwrap()).unwrap();
+        let spread_bps = args.value_of("spread_bps").unwrap().parse::<u16>().unwrap();
+        let order = get_order(context, &order_address).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: [
+                    vec![
+                        AccountMeta::new(order.seller, order.seller == context.signer.pubkey()),
+                        AccountMeta::new_readonly(order_address, false),
+                        AccountMeta::new_readonly(reverse_order_address, false),
+                        AccountMeta::new(get_flip_address(&context.p2p_swap, &order_address).0, false),
+                        AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                        AccountMeta::new_readonly(get_payout_split_address(&context.p2p_swap, &order_address).0, false),
+                    ],
+                    get_operator_accounts(context, &order.seller),
+                ].concat(),
+                data: [vec![P2PSwapInstructions::SetFlip as u8], spread_bps.to_le_bytes().to_vec()].concat(),
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nFlip set. Txn: {:?}", signature);
+    }
+}
+
+fn process_remove_flip(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+        let order = get_order(context, &order_address).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: [
+                    vec![
+                        AccountMeta::new(order.seller, order.seller == context.signer.pubkey()),
+                        AccountMeta::new_readonly(order_address, false),
+                        AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                        AccountMeta::new(get_flip_address(&context.p2p_swap, &order_address).0, false),
+                        AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                        AccountMeta::new_readonly(get_payout_split_address(&context.p2p_swap, &order_address).0, false),
+                    ],
+                    get_operator_accounts(context, &order.seller),
+                ].concat(),
+                data: vec![P2PSwapInstructions::SetFlip as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nFlip removed. Txn: {:?}", signature);
+    }
+}
+
 fn process_revoke_order(context: &AppContext, args: &Option<&ArgMatches>) {
     if let Some(args) = args {
         let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
@@ -460,34 +955,59 @@ fn process_revoke_order(context: &AppContext, args: &Option<&ArgMatches>) {
         let (order_wallet_authority, _)
             = get_order_wallet_authority(&context.p2p_swap, &order.seller);
 
-        let order_token_mint = get_order_token_mint(&context, &order).unwrap();
+        let order_token_mint = get_order_token_mint(context, &order).unwrap();
         let order_wallet = get_order_wallet_address(&order_token_mint, &order_wallet_authority);
 
         let seller_wallet_address =
             spl_associated_token_account::
             get_associated_token_address(&order.seller, &order_token_mint);
 
+        let (order_book, _) = get_order_book_address(&context.p2p_swap, &order_token_mint, &order.price_mint);
+        // system program skips reputation of sellers without reputation account
+        let seller_reputation = match get_reputation(context, &order.seller) {
+            Some((reputation, _)) => AccountMeta::new(reputation, false),
+            None => AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        };
+        // operator revokes as seller, otherwise anyone can revoke orders with remains below the minimum
+        let (delegate, _) = get_delegate_address(&context.p2p_swap, &order.seller, &context.signer.pubkey());
+        let is_operator = context.signer.pubkey() != order.seller && context.client.get_account(&delegate)
+            .is_ok_and(|account| account.owner == context.p2p_swap);
+        let caller_delegate = if is_operator { delegate } else { solana_sdk::system_program::id() };
+        let (seller_epoch, _) = get_seller_epoch_address(&context.p2p_swap, &order.seller);
+        let (payout_split, _) = get_payout_split_address(&context.p2p_swap, &order_address);
+
         let accounts = if context.signer.pubkey() == order.seller {
             vec![
-                AccountMeta::new(context.signer.pubkey().clone(), true),
+                AccountMeta::new(context.signer.pubkey(), true),
                 AccountMeta::new(order.seller, true),
                 AccountMeta::new(order_address, false),
                 AccountMeta::new_readonly(order_wallet_authority, false),
                 AccountMeta::new(order_wallet, false),
                 AccountMeta::new(seller_wallet_address, false),
                 AccountMeta::new_readonly(spl_token::id(), false),
-                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new(order_book, false),
+                seller_reputation.clone(),
+                AccountMeta::new_readonly(caller_delegate, false),
+                AccountMeta::new_readonly(seller_epoch, false),
+                AccountMeta::new(payout_split, false),
+                AccountMeta::new(get_flip_address(&context.p2p_swap, &order_address).0, false),
             ]
         } else {
             vec![
-                AccountMeta::new(context.signer.pubkey().clone(), true),
-                AccountMeta::new_readonly(order.seller, false),
+                AccountMeta::new(context.signer.pubkey(), true),
+                // rent of the closed order goes to seller when revoked by operator
+                AccountMeta::new(order.seller, false),
                 AccountMeta::new(order_address, false),
                 AccountMeta::new_readonly(order_wallet_authority, false),
                 AccountMeta::new(order_wallet, false),
                 AccountMeta::new(seller_wallet_address, false),
                 AccountMeta::new_readonly(spl_token::id(), false),
-                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new(order_book, false),
+                seller_reputation,
+                AccountMeta::new_readonly(caller_delegate, false),
+                AccountMeta::new_readonly(seller_epoch, false),
+                AccountMeta::new(payout_split, false),
+                AccountMeta::new(get_flip_address(&context.p2p_swap, &order_address).0, false),
             ]
         };
 
@@ -496,7 +1016,7 @@ fn process_revoke_order(context: &AppContext, args: &Option<&ArgMatches>) {
         let mut revoke_amount = revoke_amount.to_le_bytes().to_vec();
         data.append(&mut revoke_amount);
         instructions.push(Instruction {
-            program_id: context.p2p_swap.clone(),
+            program_id: context.p2p_swap,
             accounts,
             data,
         });
@@ -507,11 +1027,137 @@ fn process_revoke_order(context: &AppContext, args: &Option<&ArgMatches>) {
     }
 }
 
-fn process_list_orders(context: &AppContext) {
+fn process_list_order(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+        let order = get_order(context, &order_address).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new(order_address, false),
+                    AccountMeta::new(get_order_book_address(&context.p2p_swap, &order.token_mint, &order.price_mint).0, false),
+                    AccountMeta::new_readonly(get_market_address(&context.p2p_swap, &order.token_mint, &order.price_mint).0, false),
+                    AccountMeta::new_readonly(get_seller_epoch_address(&context.p2p_swap, &order.seller).0, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::ListOrder as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nOrder listed in order book. Txn: {:?}", signature);
+    }
+}
+
+fn process_migrate_order(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new(order_address, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::MigrateOrder as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nOrder migrated. Txn: {:?}", signature);
+    }
+}
+
+fn process_set_order_status(context: &AppContext, args: &Option<&ArgMatches>, instruction: P2PSwapInstructions) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+        let order = get_order(context, &order_address).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: [
+                    vec![
+                        AccountMeta::new_readonly(order.seller, order.seller == context.signer.pubkey()),
+                        AccountMeta::new(order_address, false),
+                    ],
+                    get_operator_accounts(context, &order.seller),
+                ].concat(),
+                data: vec![instruction as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nOrder status changed. Txn: {:?}", signature);
+    }
+}
+
+fn parse_permissions(permissions: &str) -> Result<u8, String> {
+    permissions.split(',').try_fold(0, |permissions, permission| match permission.trim() {
+        "amend" => Ok(permissions | PERMISSION_AMEND),
+        "pause" => Ok(permissions | PERMISSION_PAUSE),
+        "revoke" => Ok(permissions | PERMISSION_REVOKE),
+        _ => Err(format!("Unknown permission {:?}. Expected amend, pause or revoke", permission)),
+    })
+}
+
+fn is_valid_permissions<T>(value: T) -> Result<(), String>
+    where
+        T: AsRef<str>,
+{
+    parse_permissions(value.as_ref()).map(|_| ())
+}
+
+fn process_set_operator(context: &AppContext, args: &Option<&ArgMatches>, is_removal: bool) {
+    if let Some(args) = args {
+        let operator = Pubkey::from_str(args.value_of("operator").unwrap()).unwrap();
+        let permissions = if is_removal {
+            0
+        } else {
+            parse_permissions(args.value_of("permissions").unwrap()).unwrap()
+        };
+        let seller = context.signer.pubkey();
+        let (delegate, _) = get_delegate_address(&context.p2p_swap, &seller, &operator);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(seller, true),
+                    AccountMeta::new_readonly(operator, false),
+                    AccountMeta::new(delegate, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::SetDelegate as u8, permissions],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        if is_removal {
+            println!("\n\nOperator {:?} removed. Txn: {:?}", operator, signature);
+        } else {
+            println!("\n\nOperator {:?} delegated: {:?}. Txn: {:?}", operator, delegate, signature);
+        }
+    }
+}
+
+fn process_list_operators(context: &AppContext) {
     match context.client.get_program_accounts_with_config(
         &context.p2p_swap,
         RpcProgramAccountsConfig {
-            filters: Some(vec![RpcFilterType::DataSize(SwapSPLOrder::LEN as u64)]),
+            filters: Some(vec![
+                RpcFilterType::DataSize(Delegate::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, context.signer.pubkey().to_bytes().to_vec())),
+            ]),
             account_config: RpcAccountInfoConfig {
                 encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
                 commitment: None,
@@ -521,193 +1167,3498 @@ fn process_list_orders(context: &AppContext) {
             with_context: None,
         }) {
         Ok(accounts) => {
-            println!("Orders: {:#?}", accounts);
+            for (address, account) in accounts {
+                match Delegate::unpack(&account.data) {
+                    Ok(delegate) => println!("Delegate {:?}\n{:#?}", address, delegate),
+                    Err(_) => println!("Failed to parse Delegate from account {:?} data", address),
+                }
+            }
         },
         Err(err) => {
-            println!("Failed to retrieve orders for swap contract: {:?}", err);
+            println!("Failed to retrieve operators: {:?}", err);
         },
     }
 }
 
-fn main() {
-    let matches = App::new("p2p-swap-cli")
-        .about("CLI to interact with p2p-swap smart-contract")
-        .arg({
-            let arg = Arg::with_name("config_file")
-                .short("C")
-                .long("config")
-                .value_name("PATH")
-                .takes_value(true)
-                .global(true)
-                .help("Configuration file to use");
+fn process_cancel_all_orders(context: &AppContext, args: &Option<&ArgMatches>) {
+    let seller = args.and_then(|args| args.value_of("seller"))
+        .map(|seller| Pubkey::from_str(seller).unwrap())
+        .unwrap_or_else(|| context.signer.pubkey());
+    let (seller_epoch, _) = get_seller_epoch_address(&context.p2p_swap, &seller);
 
-            if let Some(ref config_file) = *solana_cli_config::CONFIG_FILE {
-                arg.default_value(config_file)
-            } else {
-                arg
-            }
-        })
-        .arg(
-            Arg::with_name("json_rpc_url")
-                .short("u")
-                .long("url")
-                .value_name("URL")
-                .takes_value(true)
-                .global(true)
-                .validator(is_url_or_moniker)
-                .help("URL for Solana node"),
+    let instructions = vec![
+        Instruction {
+            program_id: context.p2p_swap,
+            accounts: [
+                vec![
+                    AccountMeta::new(seller, seller == context.signer.pubkey()),
+                    AccountMeta::new(seller_epoch, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                ],
+                get_operator_accounts(context, &seller),
+            ].concat(),
+            data: vec![P2PSwapInstructions::BumpEpoch as u8],
+        }
+    ];
+
+    let signature = context.send_transaction(&instructions).unwrap();
+
+    println!(
+        "\n\nAll orders of {:?} are cancelled (seller epoch {}). Txn: {:?}",
+        seller,
+        get_seller_epoch(context, &seller),
+        signature,
+    );
+    println!("Revoke cancelled orders with revoke-order to return tokens to the seller");
+}
+
+fn get_proposal(context: &AppContext, proposal: &Pubkey) -> Result<Proposal, String> {
+    let proposal = context.client.get_account(proposal)
+        .map_err(|_| format!("Proposal {:?} not found", proposal))?;
+
+    Proposal::unpack(&proposal.data)
+        .map_err(|_| format!("Failed to parse Proposal from account {:?} data", proposal))
+}
+
+fn process_propose(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+        let sell_amount = args.value_of("sell_amount").unwrap().parse::<u64>().unwrap();
+        let buy_amount = args.value_of("buy_amount").unwrap().parse::<u64>().unwrap();
+
+        let order = get_order(context, &order_address).unwrap();
+        let buyer = context.signer.pubkey();
+
+        let buyer_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&buyer, &order.price_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &buyer);
+        let escrow_wallet = get_order_wallet_address(&order.price_mint, &escrow_authority);
+        let (proposal, _) = get_proposal_address(&context.p2p_swap, &order_address, &buyer);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &buyer,
+                    &escrow_authority,
+                    &order.price_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateProposal as u8];
+        data.extend_from_slice(&sell_amount.to_le_bytes());
+        data.extend_from_slice(&buy_amount.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(buyer, true),
+                AccountMeta::new(buyer_price_token_wallet, false),
+                AccountMeta::new_readonly(order_address, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(proposal, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew proposal created: {:?}", proposal);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_list_proposals(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+
+        match context.client.get_program_accounts_with_config(
+            &context.p2p_swap,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(Proposal::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, order_address.to_bytes().to_vec())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                    commitment: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                with_context: None,
+            }) {
+            Ok(accounts) => {
+                for (address, account) in accounts {
+                    match Proposal::unpack(&account.data) {
+                        Ok(proposal) => println!("Proposal {:?}\n{:#?}", address, proposal),
+                        Err(_) => println!("Failed to parse Proposal from account {:?} data", address),
+                    }
+                }
+            },
+            Err(err) => {
+                println!("Failed to retrieve proposals for order: {:?}", err);
+            },
+        }
+    }
+}
+
+fn process_accept_proposal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let proposal_address = Pubkey::from_str(args.value_of("proposal_address").unwrap()).unwrap();
+        let proposal = get_proposal(context, &proposal_address).unwrap();
+        let order = get_order(context, &proposal.order).unwrap();
+
+        let (order_wallet_authority, _) = get_order_wallet_authority(&context.p2p_swap, &order.seller);
+        let buyer_sell_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&proposal.buyer, &order.token_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &proposal.buyer);
+        let escrow_wallet = get_order_wallet_address(&proposal.price_mint, &escrow_authority);
+        let seller_price_token_wallet = order.get_payout_wallet();
+        let (order_book, _) = get_order_book_address(&context.p2p_swap, &order.token_mint, &order.price_mint);
+        let (market, _) = get_market_address(&context.p2p_swap, &order.token_mint, &order.price_mint);
+        let buyer_gate_wallet = if order.gate_mint == Pubkey::default() {
+            solana_sdk::system_program::id()
+        } else {
+            spl_associated_token_account::get_associated_token_address(&proposal.buyer, &order.gate_mint)
+        };
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(proposal.order, false),
+                    AccountMeta::new(proposal_address, false),
+                    AccountMeta::new(proposal.buyer, false),
+                    AccountMeta::new_readonly(order_wallet_authority, false),
+                    AccountMeta::new(order.order_wallet, false),
+                    AccountMeta::new(buyer_sell_token_wallet, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(seller_price_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new(order_book, false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                    AccountMeta::new_readonly(market, false),
+                    AccountMeta::new_readonly(get_seller_epoch_address(&context.p2p_swap, &order.seller).0, false),
+                    AccountMeta::new_readonly(buyer_gate_wallet, false),
+                ].into_iter()
+                    .chain(get_payout_split_accounts(context, &proposal.order))
+                    .chain(get_trade_reputation_accounts(context, &order.seller, &proposal.buyer))
+                    .collect(),
+                data: vec![P2PSwapInstructions::AcceptProposal as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nProposal accepted. Txn: {:?}", signature);
+    }
+}
+
+fn process_withdraw_proposal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let proposal_address = Pubkey::from_str(args.value_of("proposal_address").unwrap()).unwrap();
+        let proposal = get_proposal(context, &proposal_address).unwrap();
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &proposal.buyer);
+        let escrow_wallet = get_order_wallet_address(&proposal.price_mint, &escrow_authority);
+        let buyer_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&proposal.buyer, &proposal.price_mint);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new(proposal_address, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(buyer_price_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::WithdrawProposal as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nProposal withdrawn. Txn: {:?}", signature);
+    }
+}
+
+fn get_deal(context: &AppContext, deal: &Pubkey) -> Result<Deal, String> {
+    let deal = context.client.get_account(deal)
+        .map_err(|_| format!("Deal {:?} not found", deal))?;
+
+    Deal::unpack(&deal.data)
+        .map_err(|_| format!("Failed to parse Deal from account {:?} data", deal))
+}
+
+fn settle_deal_instruction(context: &AppContext, deal_address: &Pubkey, deal: &Deal) -> Instruction {
+    let (escrow_authority_a, _) = get_order_wallet_authority(&context.p2p_swap, &deal.party_a);
+    let (escrow_authority_b, _) = get_order_wallet_authority(&context.p2p_swap, &deal.party_b);
+
+    Instruction {
+        program_id: context.p2p_swap,
+        accounts: vec![
+            AccountMeta::new(deal.party_a, false),
+            AccountMeta::new_readonly(deal.party_b, false),
+            AccountMeta::new(*deal_address, false),
+            AccountMeta::new_readonly(escrow_authority_a, false),
+            AccountMeta::new(get_order_wallet_address(&deal.mint_a, &escrow_authority_a), false),
+            AccountMeta::new_readonly(escrow_authority_b, false),
+            AccountMeta::new(get_order_wallet_address(&deal.mint_b, &escrow_authority_b), false),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(&deal.party_a, &deal.mint_b),
+                false,
+            ),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(&deal.party_b, &deal.mint_a),
+                false,
+            ),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ].into_iter().chain(get_trade_reputation_accounts(context, &deal.party_a, &deal.party_b)).collect(),
+        data: vec![P2PSwapInstructions::SettleDeal as u8],
+    }
+}
+
+fn process_create_deal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let party_b = Pubkey::from_str(args.value_of("party_b").unwrap()).unwrap();
+        let mint_a = Pubkey::from_str(args.value_of("mint_a").unwrap()).unwrap();
+        let amount_a = args.value_of("amount_a").unwrap().parse::<u64>().unwrap();
+        let mint_b = Pubkey::from_str(args.value_of("mint_b").unwrap()).unwrap();
+        let amount_b = args.value_of("amount_b").unwrap().parse::<u64>().unwrap();
+        let expires_in = args.value_of("expires_in").unwrap().parse::<u64>().unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let deadline = (now + expires_in) as i64;
+        let nonce = rand::random::<u64>();
+
+        let party_a = context.signer.pubkey();
+        let (deal, _) = get_deal_address(&context.p2p_swap, &party_a, nonce);
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateDeal as u8];
+        data.extend_from_slice(&amount_a.to_le_bytes());
+        data.extend_from_slice(&amount_b.to_le_bytes());
+        data.extend_from_slice(&deadline.to_le_bytes());
+        data.extend_from_slice(&nonce.to_le_bytes());
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(party_a, true),
+                    AccountMeta::new_readonly(party_b, false),
+                    AccountMeta::new_readonly(mint_a, false),
+                    AccountMeta::new_readonly(mint_b, false),
+                    AccountMeta::new(deal, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                ],
+                data,
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew deal created: {:?}", deal);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_get_deal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let deal_address = Pubkey::from_str(args.value_of("deal_address").unwrap()).unwrap();
+        let deal = get_deal(context, &deal_address).unwrap();
+        println!("\n\nDeal {:?}", deal_address);
+        println!("{:#?}", deal);
+    }
+}
+
+fn process_deposit_deal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let deal_address = Pubkey::from_str(args.value_of("deal_address").unwrap()).unwrap();
+        let deal = get_deal(context, &deal_address).unwrap();
+        let depositor = context.signer.pubkey();
+
+        let (mint, counterparty_deposited) = if depositor == deal.party_a {
+            (deal.mint_a, deal.b_deposited)
+        } else if depositor == deal.party_b {
+            (deal.mint_b, deal.a_deposited)
+        } else {
+            println!("Signer {:?} is not a party of the deal", depositor);
+            exit(1)
+        };
+
+        let depositor_wallet = spl_associated_token_account::get_associated_token_address(&depositor, &mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &depositor);
+        let escrow_wallet = get_order_wallet_address(&mint, &escrow_authority);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &depositor,
+                    &escrow_authority,
+                    &mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new_readonly(depositor, true),
+                AccountMeta::new(deal_address, false),
+                AccountMeta::new(depositor_wallet, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data: vec![P2PSwapInstructions::DepositDeal as u8],
+        });
+
+        if counterparty_deposited {
+            instructions.push(settle_deal_instruction(context, &deal_address, &deal));
+        }
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        if counterparty_deposited {
+            println!("\n\nDeposit made and deal settled. Txn: {:?}", signature);
+        } else {
+            println!("\n\nDeposit made. Txn: {:?}", signature);
+        }
+    }
+}
+
+fn process_settle_deal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let deal_address = Pubkey::from_str(args.value_of("deal_address").unwrap()).unwrap();
+        let deal = get_deal(context, &deal_address).unwrap();
+
+        let instructions = vec![settle_deal_instruction(context, &deal_address, &deal)];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nDeal settled. Txn: {:?}", signature);
+    }
+}
+
+fn process_reclaim_deal(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let deal_address = Pubkey::from_str(args.value_of("deal_address").unwrap()).unwrap();
+        let deal = get_deal(context, &deal_address).unwrap();
+        let party = context.signer.pubkey();
+
+        let mint = if party == deal.party_a {
+            deal.mint_a
+        } else if party == deal.party_b {
+            deal.mint_b
+        } else {
+            println!("Signer {:?} is not a party of the deal", party);
+            exit(1)
+        };
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &party);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(party, true),
+                    AccountMeta::new(deal_address, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(get_order_wallet_address(&mint, &escrow_authority), false),
+                    AccountMeta::new(
+                        spl_associated_token_account::get_associated_token_address(&party, &mint),
+                        false,
+                    ),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new(deal.party_a, false),
+                ],
+                data: vec![P2PSwapInstructions::ReclaimDeal as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nDeal deposit reclaimed. Txn: {:?}", signature);
+    }
+}
+
+fn get_rfq(context: &AppContext, rfq: &Pubkey) -> Result<Rfq, String> {
+    let rfq = context.client.get_account(rfq)
+        .map_err(|_| format!("RFQ {:?} not found", rfq))?;
+
+    Rfq::unpack(&rfq.data)
+        .map_err(|_| format!("Failed to parse Rfq from account {:?} data", rfq))
+}
+
+fn get_quote(context: &AppContext, quote: &Pubkey) -> Result<Quote, String> {
+    let quote = context.client.get_account(quote)
+        .map_err(|_| format!("Quote {:?} not found", quote))?;
+
+    Quote::unpack(&quote.data)
+        .map_err(|_| format!("Failed to parse Quote from account {:?} data", quote))
+}
+
+fn process_create_rfq(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let token_mint = Pubkey::from_str(args.value_of("token_mint").unwrap()).unwrap();
+        let price_mint = Pubkey::from_str(args.value_of("price_mint").unwrap()).unwrap();
+        let amount = args.value_of("amount").unwrap().parse::<u64>().unwrap();
+        let expires_in = args.value_of("expires_in").unwrap().parse::<u64>().unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let deadline = (now + expires_in) as i64;
+        let nonce = rand::random::<u64>();
+
+        let buyer = context.signer.pubkey();
+        let (rfq, _) = get_rfq_address(&context.p2p_swap, &buyer, nonce);
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateRfq as u8];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&deadline.to_le_bytes());
+        data.extend_from_slice(&nonce.to_le_bytes());
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(buyer, true),
+                    AccountMeta::new_readonly(token_mint, false),
+                    AccountMeta::new_readonly(price_mint, false),
+                    AccountMeta::new(rfq, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                ],
+                data,
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew RFQ created: {:?}", rfq);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_get_rfq(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let rfq_address = Pubkey::from_str(args.value_of("rfq_address").unwrap()).unwrap();
+        let rfq = get_rfq(context, &rfq_address).unwrap();
+        println!("\n\nRFQ {:?}", rfq_address);
+        println!("{:#?}", rfq);
+    }
+}
+
+fn process_create_quote(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let rfq_address = Pubkey::from_str(args.value_of("rfq_address").unwrap()).unwrap();
+        let price_amount = args.value_of("price_amount").unwrap().parse::<u64>().unwrap();
+
+        let rfq = get_rfq(context, &rfq_address).unwrap();
+        let seller = context.signer.pubkey();
+
+        let seller_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&seller, &rfq.token_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &seller);
+        let escrow_wallet = get_order_wallet_address(&rfq.token_mint, &escrow_authority);
+        let (quote, _) = get_quote_address(&context.p2p_swap, &rfq_address, &seller);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &seller,
+                    &escrow_authority,
+                    &rfq.token_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateQuote as u8];
+        data.extend_from_slice(&price_amount.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(seller, true),
+                AccountMeta::new(seller_token_wallet, false),
+                AccountMeta::new_readonly(rfq_address, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(quote, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew quote created: {:?}", quote);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_list_quotes(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let rfq_address = Pubkey::from_str(args.value_of("rfq_address").unwrap()).unwrap();
+
+        match context.client.get_program_accounts_with_config(
+            &context.p2p_swap,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(Quote::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, rfq_address.to_bytes().to_vec())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                    commitment: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                with_context: None,
+            }) {
+            Ok(accounts) => {
+                let mut quotes: Vec<(Pubkey, Quote)> = accounts
+                    .into_iter()
+                    .filter_map(|(address, account)| match Quote::unpack(&account.data) {
+                        Ok(quote) => Some((address, quote)),
+                        Err(_) => {
+                            println!("Failed to parse Quote from account {:?} data", address);
+                            None
+                        },
+                    })
+                    .collect();
+
+                // best (cheapest) quote first
+                quotes.sort_by_key(|(_, quote)| quote.price_amount);
+                for (address, quote) in quotes {
+                    println!("Quote {:?}\n{:#?}", address, quote);
+                }
+            },
+            Err(err) => {
+                println!("Failed to retrieve quotes for RFQ: {:?}", err);
+            },
+        }
+    }
+}
+
+fn process_accept_quote(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let quote_address = Pubkey::from_str(args.value_of("quote_address").unwrap()).unwrap();
+        let quote = get_quote(context, &quote_address).unwrap();
+        let buyer = context.signer.pubkey();
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &quote.seller);
+        let escrow_wallet = get_order_wallet_address(&quote.token_mint, &escrow_authority);
+        let buyer_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&buyer, &quote.token_mint);
+        let buyer_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&buyer, &quote.price_mint);
+        let seller_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&quote.seller, &quote.price_mint);
+
+        let instructions = vec![
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &buyer,
+                &buyer,
+                &quote.token_mint,
+                &spl_token::id(),
+            ),
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &buyer,
+                &quote.seller,
+                &quote.price_mint,
+                &spl_token::id(),
+            ),
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(buyer, true),
+                    AccountMeta::new(quote.rfq, false),
+                    AccountMeta::new(quote_address, false),
+                    AccountMeta::new(quote.seller, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(buyer_token_wallet, false),
+                    AccountMeta::new(buyer_price_token_wallet, false),
+                    AccountMeta::new(seller_price_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                ].into_iter().chain(get_trade_reputation_accounts(context, &quote.seller, &buyer)).collect(),
+                data: vec![P2PSwapInstructions::AcceptQuote as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nQuote accepted. Txn: {:?}", signature);
+    }
+}
+
+fn process_reclaim_quote(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let quote_address = Pubkey::from_str(args.value_of("quote_address").unwrap()).unwrap();
+        let quote = get_quote(context, &quote_address).unwrap();
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &quote.seller);
+        let escrow_wallet = get_order_wallet_address(&quote.token_mint, &escrow_authority);
+        let seller_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&quote.seller, &quote.token_mint);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new(quote_address, false),
+                    AccountMeta::new_readonly(quote.rfq, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(seller_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::ReclaimQuote as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nQuote reclaimed. Txn: {:?}", signature);
+    }
+}
+
+fn process_close_rfq(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let rfq_address = Pubkey::from_str(args.value_of("rfq_address").unwrap()).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new(rfq_address, false),
+                ],
+                data: vec![P2PSwapInstructions::CloseRfq as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nRFQ closed. Txn: {:?}", signature);
+    }
+}
+
+fn get_auction(context: &AppContext, auction: &Pubkey) -> Result<Auction, String> {
+    let auction = context.client.get_account(auction)
+        .map_err(|_| format!("Auction {:?} not found", auction))?;
+
+    Auction::unpack(&auction.data)
+        .map_err(|_| format!("Failed to parse Auction from account {:?} data", auction))
+}
+
+fn process_create_auction(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let token_mint = Pubkey::from_str(args.value_of("token_mint").unwrap()).unwrap();
+        let price_mint = Pubkey::from_str(args.value_of("price_mint").unwrap()).unwrap();
+        let amount = args.value_of("amount").unwrap().parse::<u64>().unwrap();
+        let min_bid = args.value_of("min_bid").unwrap().parse::<u64>().unwrap();
+        let min_increment = args.value_of("min_increment").unwrap().parse::<u64>().unwrap();
+        let duration_slots = args.value_of("duration_slots").unwrap().parse::<u64>().unwrap();
+        let extension_slots = args.value_of("extension_slots").unwrap().parse::<u64>().unwrap();
+
+        let end_slot = context.client.get_slot().unwrap() + duration_slots;
+        let nonce = rand::random::<u64>();
+
+        let seller = context.signer.pubkey();
+        let seller_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&seller, &token_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &seller);
+        let escrow_wallet = get_order_wallet_address(&token_mint, &escrow_authority);
+        let (auction, _) = get_auction_address(&context.p2p_swap, &seller, nonce);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &seller,
+                    &escrow_authority,
+                    &token_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateAuction as u8];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&min_bid.to_le_bytes());
+        data.extend_from_slice(&min_increment.to_le_bytes());
+        data.extend_from_slice(&end_slot.to_le_bytes());
+        data.extend_from_slice(&extension_slots.to_le_bytes());
+        data.extend_from_slice(&nonce.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(seller, true),
+                AccountMeta::new(seller_token_wallet, false),
+                AccountMeta::new_readonly(token_mint, false),
+                AccountMeta::new_readonly(price_mint, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(auction, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew auction created: {:?}", auction);
+        println!("Ends at slot {:?}", end_slot);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_get_auction(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let auction_address = Pubkey::from_str(args.value_of("auction_address").unwrap()).unwrap();
+        let auction = get_auction(context, &auction_address).unwrap();
+        println!("\n\nAuction {:?}", auction_address);
+        println!("{:#?}", auction);
+        println!("Next minimal bid: {:?}", auction.next_min_bid());
+    }
+}
+
+fn process_place_bid(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let auction_address = Pubkey::from_str(args.value_of("auction_address").unwrap()).unwrap();
+        let bid = args.value_of("bid").unwrap().parse::<u64>().unwrap();
+
+        let auction = get_auction(context, &auction_address).unwrap();
+        let bidder = context.signer.pubkey();
+
+        let bidder_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&bidder, &auction.price_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &bidder);
+        let escrow_wallet = get_order_wallet_address(&auction.price_mint, &escrow_authority);
+
+        // previous bid refund accounts are not used by the program when there are no bids yet
+        let (previous_escrow_authority, _) =
+            get_order_wallet_authority(&context.p2p_swap, &auction.highest_bidder);
+        let previous_escrow_wallet = get_order_wallet_address(&auction.price_mint, &previous_escrow_authority);
+        let previous_bidder_wallet =
+            spl_associated_token_account::get_associated_token_address(&auction.highest_bidder, &auction.price_mint);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &bidder,
+                    &escrow_authority,
+                    &auction.price_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::PlaceBid as u8];
+        data.extend_from_slice(&bid.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new_readonly(bidder, true),
+                AccountMeta::new(auction_address, false),
+                AccountMeta::new(bidder_price_token_wallet, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(previous_escrow_authority, false),
+                AccountMeta::new(previous_escrow_wallet, false),
+                AccountMeta::new(previous_bidder_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                AccountMeta::new_readonly(auction.highest_bidder, false),
+                AccountMeta::new_readonly(auction.price_mint, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nBid placed. Txn: {:?}", signature);
+    }
+}
+
+fn process_settle_auction(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let auction_address = Pubkey::from_str(args.value_of("auction_address").unwrap()).unwrap();
+        let auction = get_auction(context, &auction_address).unwrap();
+        let payer = context.signer.pubkey();
+
+        let winner = if auction.highest_bidder == Pubkey::default() {
+            auction.seller
+        } else {
+            auction.highest_bidder
+        };
+
+        let (seller_escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &auction.seller);
+        let seller_escrow_wallet = get_order_wallet_address(&auction.token_mint, &seller_escrow_authority);
+        let (winner_escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &auction.highest_bidder);
+        let winner_escrow_wallet = get_order_wallet_address(&auction.price_mint, &winner_escrow_authority);
+        let winner_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&winner, &auction.token_mint);
+        let seller_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&auction.seller, &auction.price_mint);
+
+        // missing winner and seller wallets are created by the program, payer pays for them
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(auction.seller, false),
+                    AccountMeta::new(auction_address, false),
+                    AccountMeta::new_readonly(seller_escrow_authority, false),
+                    AccountMeta::new(seller_escrow_wallet, false),
+                    AccountMeta::new_readonly(winner_escrow_authority, false),
+                    AccountMeta::new(winner_escrow_wallet, false),
+                    AccountMeta::new(winner_token_wallet, false),
+                    AccountMeta::new(seller_price_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new_readonly(winner, false),
+                    AccountMeta::new_readonly(auction.token_mint, false),
+                    AccountMeta::new_readonly(auction.price_mint, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+                ].into_iter().chain(get_trade_reputation_accounts(context, &auction.seller, &winner)).collect(),
+                data: vec![P2PSwapInstructions::SettleAuction as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nAuction settled. Txn: {:?}", signature);
+    }
+}
+
+// Salts of sealed bids are stored locally (one bid per line: AUCTION BIDDER BID SALT) until revealed
+struct StoredBid {
+    auction: Pubkey,
+    bidder: Pubkey,
+    bid: u64,
+    salt: [u8; 32],
+}
+
+fn load_stored_bids(bids_file: &str) -> Vec<StoredBid> {
+    fs::read_to_string(bids_file)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 4 || parts[3].len() != 64 {
+                warn!("Skip invalid line in bids file: {:?}", line);
+                return None;
+            }
+
+            let mut salt = [0u8; 32];
+            for (i, byte) in salt.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&parts[3][2 * i..2 * i + 2], 16).ok()?;
+            }
+
+            Some(StoredBid {
+                auction: Pubkey::from_str(parts[0]).ok()?,
+                bidder: Pubkey::from_str(parts[1]).ok()?,
+                bid: parts[2].parse::<u64>().ok()?,
+                salt,
+            })
+        })
+        .collect()
+}
+
+fn save_stored_bids(bids_file: &str, bids: &[StoredBid]) {
+    if let Some(dir) = Path::new(bids_file).parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+
+    let mut file = fs::File::create(bids_file).unwrap();
+    for bid in bids {
+        let salt: String = bid.salt.iter().map(|byte| format!("{:02x}", byte)).collect();
+        writeln!(file, "{} {} {} {}", bid.auction, bid.bidder, bid.bid, salt).unwrap();
+    }
+}
+
+fn get_sealed_auction(context: &AppContext, auction: &Pubkey) -> Result<SealedAuction, String> {
+    let auction = context.client.get_account(auction)
+        .map_err(|_| format!("Sealed auction {:?} not found", auction))?;
+
+    SealedAuction::unpack(&auction.data)
+        .map_err(|_| format!("Failed to parse SealedAuction from account {:?} data", auction))
+}
+
+fn get_sealed_bid(context: &AppContext, sealed_bid: &Pubkey) -> Result<SealedBid, String> {
+    let sealed_bid = context.client.get_account(sealed_bid)
+        .map_err(|_| format!("Sealed bid {:?} not found", sealed_bid))?;
+
+    SealedBid::unpack(&sealed_bid.data)
+        .map_err(|_| format!("Failed to parse SealedBid from account {:?} data", sealed_bid))
+}
+
+fn process_create_sealed_auction(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let token_mint = Pubkey::from_str(args.value_of("token_mint").unwrap()).unwrap();
+        let price_mint = Pubkey::from_str(args.value_of("price_mint").unwrap()).unwrap();
+        let amount = args.value_of("amount").unwrap().parse::<u64>().unwrap();
+        let min_bid = args.value_of("min_bid").unwrap().parse::<u64>().unwrap();
+        let min_deposit = args.value_of("min_deposit")
+            .map_or(min_bid, |min_deposit| min_deposit.parse::<u64>().unwrap());
+        let commit_slots = args.value_of("commit_slots").unwrap().parse::<u64>().unwrap();
+        let reveal_slots = args.value_of("reveal_slots").unwrap().parse::<u64>().unwrap();
+        let forfeit_unrevealed = args.is_present("forfeit_unrevealed");
+
+        let commit_end_slot = context.client.get_slot().unwrap() + commit_slots;
+        let reveal_end_slot = commit_end_slot + reveal_slots;
+        let nonce = rand::random::<u64>();
+
+        let seller = context.signer.pubkey();
+        let seller_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&seller, &token_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &seller);
+        let escrow_wallet = get_order_wallet_address(&token_mint, &escrow_authority);
+        let (auction, _) = get_sealed_auction_address(&context.p2p_swap, &seller, nonce);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &seller,
+                    &escrow_authority,
+                    &token_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateSealedAuction as u8];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&min_bid.to_le_bytes());
+        data.extend_from_slice(&min_deposit.to_le_bytes());
+        data.extend_from_slice(&commit_end_slot.to_le_bytes());
+        data.extend_from_slice(&reveal_end_slot.to_le_bytes());
+        data.extend_from_slice(&nonce.to_le_bytes());
+        data.push(forfeit_unrevealed as u8);
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(seller, true),
+                AccountMeta::new(seller_token_wallet, false),
+                AccountMeta::new_readonly(token_mint, false),
+                AccountMeta::new_readonly(price_mint, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(auction, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew sealed-bid auction created: {:?}", auction);
+        println!("Commits until slot {:?}, reveals until slot {:?}", commit_end_slot, reveal_end_slot);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_get_sealed_auction(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let auction_address = Pubkey::from_str(args.value_of("auction_address").unwrap()).unwrap();
+        let auction = get_sealed_auction(context, &auction_address).unwrap();
+        println!("\n\nSealed auction {:?}", auction_address);
+        println!("{:#?}", auction);
+    }
+}
+
+fn process_commit_bid(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let auction_address = Pubkey::from_str(args.value_of("auction_address").unwrap()).unwrap();
+        let bid = args.value_of("bid").unwrap().parse::<u64>().unwrap();
+        let deposit = args.value_of("deposit")
+            .map_or(bid, |deposit| deposit.parse::<u64>().unwrap());
+        let bids_file = args.value_of("bids_file").unwrap();
+
+        let auction = get_sealed_auction(context, &auction_address).unwrap();
+        if bid > deposit {
+            println!("Bid {:?} exceeds deposit {:?}", bid, deposit);
+            exit(1);
+        }
+        let deposit = deposit.max(auction.min_deposit);
+
+        let bidder = context.signer.pubkey();
+        let salt = rand::random::<[u8; 32]>();
+        let commitment = get_bid_commitment(&bidder, bid, &salt);
+
+        // salt is saved before sending so that the bid can be revealed even if confirmation is lost
+        let mut stored_bids = load_stored_bids(bids_file);
+        stored_bids.retain(|stored| stored.auction != auction_address || stored.bidder != bidder);
+        stored_bids.push(StoredBid { auction: auction_address, bidder, bid, salt });
+        save_stored_bids(bids_file, &stored_bids);
+
+        let bidder_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&bidder, &auction.price_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &bidder);
+        let escrow_wallet = get_order_wallet_address(&auction.price_mint, &escrow_authority);
+        let (sealed_bid, _) = get_sealed_bid_address(&context.p2p_swap, &auction_address, &bidder);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &bidder,
+                    &escrow_authority,
+                    &auction.price_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CommitBid as u8];
+        data.extend_from_slice(&commitment);
+        data.extend_from_slice(&deposit.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(bidder, true),
+                AccountMeta::new(auction_address, false),
+                AccountMeta::new(bidder_price_token_wallet, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(sealed_bid, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nBid committed: {:?}", sealed_bid);
+        println!("Deposit: {:?}. Salt is saved to {:?}", deposit, bids_file);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_reveal_bids(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let bids_file = args.value_of("bids_file").unwrap();
+        let wait = args.is_present("wait");
+        let only_auction = args.value_of("auction_address")
+            .map(|auction| Pubkey::from_str(auction).unwrap());
+        let bidder = context.signer.pubkey();
+
+        let mut stored_bids = load_stored_bids(bids_file);
+        let mut keep = vec![true; stored_bids.len()];
+
+        for (i, stored) in stored_bids.iter().enumerate() {
+            if stored.bidder != bidder || only_auction.is_some_and(|auction| auction != stored.auction) {
+                continue;
+            }
+
+            let auction = match get_sealed_auction(context, &stored.auction) {
+                Ok(auction) => auction,
+                Err(err) => {
+                    println!("{}. Forget bid", err);
+                    keep[i] = false;
+                    continue;
+                },
+            };
+
+            let mut slot = context.client.get_slot().unwrap();
+            if slot < auction.commit_end_slot {
+                if !wait {
+                    println!("Auction {:?}: reveal phase starts at slot {:?}", stored.auction, auction.commit_end_slot);
+                    continue;
+                }
+
+                println!("Auction {:?}: waiting for slot {:?}", stored.auction, auction.commit_end_slot);
+                while slot < auction.commit_end_slot {
+                    sleep(Duration::from_secs(2));
+                    slot = context.client.get_slot().unwrap();
+                }
+            }
+
+            let (sealed_bid_address, _) = get_sealed_bid_address(&context.p2p_swap, &stored.auction, &bidder);
+            match get_sealed_bid(context, &sealed_bid_address) {
+                Ok(sealed_bid) if !sealed_bid.is_revealed => {},
+                _ => {
+                    println!("Auction {:?}: no bid to reveal. Forget bid", stored.auction);
+                    keep[i] = false;
+                    continue;
+                },
+            }
+
+            if slot >= auction.reveal_end_slot {
+                println!("Auction {:?}: reveal phase ended at slot {:?}", stored.auction, auction.reveal_end_slot);
+                keep[i] = false;
+                continue;
+            }
+
+            let mut data: Vec<u8> = vec![P2PSwapInstructions::RevealBid as u8];
+            data.extend_from_slice(&stored.bid.to_le_bytes());
+            data.extend_from_slice(&stored.salt);
+            let instructions = vec![
+                Instruction {
+                    program_id: context.p2p_swap,
+                    accounts: vec![
+                        AccountMeta::new_readonly(bidder, true),
+                        AccountMeta::new(stored.auction, false),
+                        AccountMeta::new(sealed_bid_address, false),
+                    ],
+                    data,
+                }
+            ];
+
+            match context.send_transaction(&instructions) {
+                Ok(signature) => {
+                    println!("Auction {:?}: bid {:?} revealed. Txn: {:?}", stored.auction, stored.bid, signature);
+                    keep[i] = false;
+                },
+                Err(err) => println!("Auction {:?}: failed to reveal bid: {}", stored.auction, err),
+            }
+        }
+
+        let mut keep = keep.into_iter();
+        stored_bids.retain(|_| keep.next().unwrap());
+        save_stored_bids(bids_file, &stored_bids);
+    }
+}
+
+fn withdraw_sealed_bids(context: &AppContext, auction_address: &Pubkey, auction: &SealedAuction) {
+    let sealed_bids = context.client.get_program_accounts_with_config(
+        &context.p2p_swap,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::DataSize(SealedBid::LEN as u64),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, auction_address.to_bytes().to_vec())),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                commitment: None,
+                data_slice: None,
+                min_context_slot: None,
+            },
+            with_context: None,
+        }).unwrap();
+
+    let seller_price_token_wallet =
+        spl_associated_token_account::get_associated_token_address(&auction.seller, &auction.price_mint);
+
+    for (sealed_bid_address, account) in sealed_bids {
+        let sealed_bid = match SealedBid::unpack(&account.data) {
+            Ok(sealed_bid) => sealed_bid,
+            Err(_) => {
+                println!("Failed to parse SealedBid from account {:?} data", sealed_bid_address);
+                continue;
+            },
+        };
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &sealed_bid.bidder);
+        let escrow_wallet = get_order_wallet_address(&auction.price_mint, &escrow_authority);
+        let bidder_price_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&sealed_bid.bidder, &auction.price_mint);
+
+        let instructions = vec![
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &context.signer.pubkey(),
+                &sealed_bid.bidder,
+                &auction.price_mint,
+                &spl_token::id(),
+            ),
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(sealed_bid.bidder, false),
+                    AccountMeta::new(auction.seller, false),
+                    AccountMeta::new(*auction_address, false),
+                    AccountMeta::new(sealed_bid_address, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(bidder_price_token_wallet, false),
+                    AccountMeta::new(seller_price_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::WithdrawSealedBid as u8],
+            }
+        ];
+
+        match context.send_transaction(&instructions) {
+            Ok(signature) => println!("Bid {:?} withdrawn. Txn: {:?}", sealed_bid_address, signature),
+            Err(err) => println!("Failed to withdraw bid {:?}: {}", sealed_bid_address, err),
+        }
+    }
+}
+
+fn process_settle_sealed_auction(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let auction_address = Pubkey::from_str(args.value_of("auction_address").unwrap()).unwrap();
+        let auction = get_sealed_auction(context, &auction_address).unwrap();
+        let payer = context.signer.pubkey();
+
+        if !auction.is_settled {
+            let winner = if auction.highest_bidder == Pubkey::default() {
+                auction.seller
+            } else {
+                auction.highest_bidder
+            };
+
+            let (seller_escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &auction.seller);
+            let seller_escrow_wallet = get_order_wallet_address(&auction.token_mint, &seller_escrow_authority);
+            let (winner_sealed_bid, _) =
+                get_sealed_bid_address(&context.p2p_swap, &auction_address, &auction.highest_bidder);
+            let (winner_escrow_authority, _) =
+                get_order_wallet_authority(&context.p2p_swap, &auction.highest_bidder);
+            let winner_escrow_wallet = get_order_wallet_address(&auction.price_mint, &winner_escrow_authority);
+            let winner_token_wallet =
+                spl_associated_token_account::get_associated_token_address(&winner, &auction.token_mint);
+            let seller_price_token_wallet =
+                spl_associated_token_account::get_associated_token_address(&auction.seller, &auction.price_mint);
+
+            // missing winner and seller wallets are created by the program, payer pays for them
+            let instructions = vec![
+                Instruction {
+                    program_id: context.p2p_swap,
+                    accounts: vec![
+                        AccountMeta::new(auction.seller, false),
+                        AccountMeta::new(auction_address, false),
+                        AccountMeta::new_readonly(seller_escrow_authority, false),
+                        AccountMeta::new(seller_escrow_wallet, false),
+                        AccountMeta::new(winner_sealed_bid, false),
+                        AccountMeta::new_readonly(winner_escrow_authority, false),
+                        AccountMeta::new(winner_escrow_wallet, false),
+                        AccountMeta::new(winner_token_wallet, false),
+                        AccountMeta::new(seller_price_token_wallet, false),
+                        AccountMeta::new_readonly(spl_token::id(), false),
+                        AccountMeta::new(payer, true),
+                        AccountMeta::new_readonly(winner, false),
+                        AccountMeta::new_readonly(auction.token_mint, false),
+                        AccountMeta::new_readonly(auction.price_mint, false),
+                        AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+                    ].into_iter().chain(get_trade_reputation_accounts(context, &auction.seller, &winner)).collect(),
+                    data: vec![P2PSwapInstructions::SettleSealedAuction as u8],
+                }
+            ];
+
+            let signature = context.send_transaction(&instructions).unwrap();
+
+            println!("\n\nAuction settled. Txn: {:?}", signature);
+        }
+
+        withdraw_sealed_bids(context, &auction_address, &auction);
+    }
+}
+
+fn get_fiat_trade(context: &AppContext, trade: &Pubkey) -> Result<FiatTrade, String> {
+    let trade = context.client.get_account(trade)
+        .map_err(|_| format!("Fiat trade {:?} not found", trade))?;
+
+    FiatTrade::unpack(&trade.data)
+        .map_err(|_| format!("Failed to parse FiatTrade from account {:?} data", trade))
+}
+
+fn print_fiat_trade(trade_address: &Pubkey, trade: &FiatTrade) {
+    println!("Fiat trade {:?}", trade_address);
+    println!("{:#?}", trade);
+    println!(
+        "Fiat payment: {} {} (minor units)",
+        trade.fiat_amount,
+        String::from_utf8_lossy(&trade.fiat_currency),
+    );
+}
+
+fn process_create_fiat_trade(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let buyer = Pubkey::from_str(args.value_of("buyer").unwrap()).unwrap();
+        let token_mint = Pubkey::from_str(args.value_of("token_mint").unwrap()).unwrap();
+        let amount = args.value_of("amount").unwrap().parse::<u64>().unwrap();
+        let fiat_amount = args.value_of("fiat_amount").unwrap().parse::<u64>().unwrap();
+        let fiat_currency = parse_currency(args.value_of("fiat_currency").unwrap()).unwrap();
+        let payment_window = args.value_of("payment_window").unwrap().parse::<u64>().unwrap();
+        // system program means default arbitrator
+        let arbitrator = args.value_of("arbitrator")
+            .map(|arbitrator| Pubkey::from_str(arbitrator).unwrap())
+            .unwrap_or_else(solana_sdk::system_program::id);
+        let arbitration_fee_bps = args.value_of("arbitration_fee_bps").unwrap().parse::<u16>().unwrap();
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let payment_deadline = (now + payment_window) as i64;
+        let nonce = rand::random::<u64>();
+
+        let seller = context.signer.pubkey();
+        let seller_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&seller, &token_mint);
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &seller);
+        let escrow_wallet = get_order_wallet_address(&token_mint, &escrow_authority);
+        let (trade, _) = get_fiat_trade_address(&context.p2p_swap, &seller, nonce);
+
+        let mut instructions = Vec::new();
+
+        if context.client.get_account(&escrow_wallet).is_err() {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account(
+                    &seller,
+                    &escrow_authority,
+                    &token_mint,
+                    &spl_token::id(),
+                )
+            )
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateFiatTrade as u8];
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.extend_from_slice(&fiat_amount.to_le_bytes());
+        data.extend_from_slice(&fiat_currency);
+        data.extend_from_slice(&payment_deadline.to_le_bytes());
+        data.extend_from_slice(&nonce.to_le_bytes());
+        data.extend_from_slice(&arbitration_fee_bps.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(seller, true),
+                AccountMeta::new_readonly(buyer, false),
+                AccountMeta::new_readonly(token_mint, false),
+                AccountMeta::new(seller_token_wallet, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(trade, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                AccountMeta::new_readonly(arbitrator, false),
+                AccountMeta::new_readonly(get_arbitration_config_address(&context.p2p_swap).0, false),
+            ],
+            data,
+        });
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew fiat trade created: {:?}", trade);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_get_fiat_trade(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let trade_address = Pubkey::from_str(args.value_of("trade_address").unwrap()).unwrap();
+        let trade = get_fiat_trade(context, &trade_address).unwrap();
+        println!("\n");
+        print_fiat_trade(&trade_address, &trade);
+    }
+}
+
+fn process_list_fiat_trades(context: &AppContext) {
+    let signer = context.signer.pubkey();
+    // seller is stored at offset 0, buyer at offset 32
+    for offset in [0, 32] {
+        match context.client.get_program_accounts_with_config(
+            &context.p2p_swap,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![
+                    RpcFilterType::DataSize(FiatTrade::LEN as u64),
+                    RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, signer.to_bytes().to_vec())),
+                ]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                    commitment: None,
+                    data_slice: None,
+                    min_context_slot: None,
+                },
+                with_context: None,
+            }) {
+            Ok(accounts) => {
+                for (address, account) in accounts {
+                    match FiatTrade::unpack(&account.data) {
+                        Ok(trade) => print_fiat_trade(&address, &trade),
+                        Err(_) => println!("Failed to parse FiatTrade from account {:?} data", address),
+                    }
+                }
+            },
+            Err(err) => {
+                println!("Failed to retrieve fiat trades: {:?}", err);
+            },
+        }
+    }
+}
+
+fn process_mark_fiat_sent(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let trade_address = Pubkey::from_str(args.value_of("trade_address").unwrap()).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(trade_address, false),
+                ],
+                data: vec![P2PSwapInstructions::MarkFiatSent as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nFiat payment marked as sent. Txn: {:?}", signature);
+    }
+}
+
+fn process_release_fiat_trade(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let trade_address = Pubkey::from_str(args.value_of("trade_address").unwrap()).unwrap();
+        let trade = get_fiat_trade(context, &trade_address).unwrap();
+        let seller = context.signer.pubkey();
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &seller);
+        let escrow_wallet = get_order_wallet_address(&trade.token_mint, &escrow_authority);
+        let buyer_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&trade.buyer, &trade.token_mint);
+
+        // missing buyer wallet is created by the program, seller pays for it
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(seller, true),
+                    AccountMeta::new(trade_address, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(buyer_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                    AccountMeta::new_readonly(trade.buyer, false),
+                    AccountMeta::new_readonly(trade.token_mint, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+                ].into_iter().chain(get_trade_reputation_accounts(context, &seller, &trade.buyer)).collect(),
+                data: vec![P2PSwapInstructions::ReleaseFiatTrade as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nTokens released to buyer. Txn: {:?}", signature);
+    }
+}
+
+fn process_cancel_fiat_trade(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let trade_address = Pubkey::from_str(args.value_of("trade_address").unwrap()).unwrap();
+        let trade = get_fiat_trade(context, &trade_address).unwrap();
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &trade.seller);
+        let escrow_wallet = get_order_wallet_address(&trade.token_mint, &escrow_authority);
+        let seller_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&trade.seller, &trade.token_mint);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(trade.seller, false),
+                    AccountMeta::new(trade_address, false),
+                    AccountMeta::new_readonly(escrow_authority, false),
+                    AccountMeta::new(escrow_wallet, false),
+                    AccountMeta::new(seller_token_wallet, false),
+                    AccountMeta::new_readonly(spl_token::id(), false),
+                ],
+                data: vec![P2PSwapInstructions::CancelFiatTrade as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nFiat trade cancelled. Txn: {:?}", signature);
+    }
+}
+
+fn process_raise_dispute(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let trade_address = Pubkey::from_str(args.value_of("trade_address").unwrap()).unwrap();
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(trade_address, false),
+                ],
+                data: vec![P2PSwapInstructions::RaiseDispute as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nDispute raised. Txn: {:?}", signature);
+    }
+}
+
+fn process_resolve_dispute(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let trade_address = Pubkey::from_str(args.value_of("trade_address").unwrap()).unwrap();
+        let trade = get_fiat_trade(context, &trade_address).unwrap();
+        let (to_buyer, winner, loser) = match args.value_of("winner").unwrap() {
+            "buyer" => (true, trade.buyer, trade.seller),
+            _ => (false, trade.seller, trade.buyer),
+        };
+        let fee_bps = args.value_of("fee_bps")
+            .map(|fee_bps| fee_bps.parse::<u16>().unwrap())
+            .unwrap_or(trade.arbitration_fee_bps);
+        let arbitrator = context.signer.pubkey();
+
+        let (escrow_authority, _) = get_order_wallet_authority(&context.p2p_swap, &trade.seller);
+        let escrow_wallet = get_order_wallet_address(&trade.token_mint, &escrow_authority);
+        let winner_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&winner, &trade.token_mint);
+        let arbitrator_token_wallet =
+            spl_associated_token_account::get_associated_token_address(&arbitrator, &trade.token_mint);
+
+        let mut instructions = vec![
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &arbitrator,
+                &winner,
+                &trade.token_mint,
+                &spl_token::id(),
+            ),
+        ];
+
+        if fee_bps > 0 {
+            instructions.push(
+                spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                    &arbitrator,
+                    &arbitrator,
+                    &trade.token_mint,
+                    &spl_token::id(),
+                )
+            );
+        }
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::ResolveDispute as u8, to_buyer as u8];
+        data.extend_from_slice(&fee_bps.to_le_bytes());
+        instructions.push(Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(arbitrator, true),
+                AccountMeta::new(trade.seller, false),
+                AccountMeta::new(trade_address, false),
+                AccountMeta::new_readonly(escrow_authority, false),
+                AccountMeta::new(escrow_wallet, false),
+                AccountMeta::new(winner_token_wallet, false),
+                AccountMeta::new(arbitrator_token_wallet, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(get_reputation_address(&context.p2p_swap, &loser).0, false),
+            ],
+            data,
+        });
+
+        // default arbitrator resolves disputes which arbitrator of the trade did not resolve in time
+        if arbitrator != trade.arbitrator {
+            instructions.last_mut().unwrap().accounts.push(
+                AccountMeta::new_readonly(get_arbitration_config_address(&context.p2p_swap).0, false)
+            );
+        }
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nDispute resolved in favor of {:?}. Txn: {:?}", winner, signature);
+    }
+}
+
+fn process_create_reputation(context: &AppContext, args: &Option<&ArgMatches>) {
+    let wallet = args.and_then(|args| args.value_of("wallet"))
+        .map(|wallet| Pubkey::from_str(wallet).unwrap())
+        .unwrap_or_else(|| context.signer.pubkey());
+    let (reputation, _) = get_reputation_address(&context.p2p_swap, &wallet);
+
+    let instructions = vec![
+        Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(context.signer.pubkey(), true),
+                AccountMeta::new_readonly(wallet, false),
+                AccountMeta::new(reputation, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data: vec![P2PSwapInstructions::CreateReputation as u8],
+        }
+    ];
+
+    let signature = context.send_transaction(&instructions).unwrap();
+
+    println!("\n\nReputation of {:?} created: {:?}", wallet, reputation);
+    println!("Transaction: {:?}", signature);
+}
+
+fn process_get_reputation(context: &AppContext, args: &Option<&ArgMatches>) {
+    let wallet = args.and_then(|args| args.value_of("wallet"))
+        .map(|wallet| Pubkey::from_str(wallet).unwrap())
+        .unwrap_or_else(|| context.signer.pubkey());
+    println!("\n");
+    print_reputation(context, &wallet);
+}
+
+fn process_rate_trade(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let order_address = Pubkey::from_str(args.value_of("order_address").unwrap()).unwrap();
+        let rating = args.value_of("rating").unwrap().parse::<u8>().unwrap();
+        let buyer = args.value_of("buyer")
+            .map(|buyer| Pubkey::from_str(buyer).unwrap())
+            .unwrap_or_else(|| context.signer.pubkey());
+
+        let (receipt_address, _) = get_trade_receipt_address(&context.p2p_swap, &order_address, &buyer);
+        let receipt = context.client.get_account(&receipt_address)
+            .map_err(|_| format!("Trade receipt {:?} not found", receipt_address))
+            .and_then(|account| TradeReceipt::unpack(&account.data)
+                .map_err(|_| format!("Failed to parse TradeReceipt from account {:?} data", receipt_address)))
+            .unwrap();
+        let counterparty = if context.signer.pubkey() == receipt.seller { receipt.buyer } else { receipt.seller };
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(receipt.buyer, false),
+                    AccountMeta::new(receipt_address, false),
+                    AccountMeta::new(get_reputation_address(&context.p2p_swap, &counterparty).0, false),
+                ],
+                data: vec![P2PSwapInstructions::RateTrade as u8, rating],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\n{:?} rated with {}. Txn: {:?}", counterparty, rating, signature);
+    }
+}
+
+fn process_list_orders(context: &AppContext, args: &Option<&ArgMatches>) {
+    let pair = args.and_then(|args| {
+        let token_mint = args.value_of("token_mint")?;
+        let price_mint = args.value_of("price_mint")?;
+        Some((Pubkey::try_from(token_mint).unwrap(), Pubkey::try_from(price_mint).unwrap()))
+    });
+
+    if let Some((token_mint, price_mint)) = pair {
+        // order book of the pair is read with a single request
+        let (order_book_address, _) = get_order_book_address(&context.p2p_swap, &token_mint, &price_mint);
+        match context.client.get_account(&order_book_address) {
+            Ok(account) => match OrderBook::unpack(&account.data) {
+                Ok(order_book) => println!("Orders: {:#?}", order_book.entries),
+                Err(_) => println!("Failed to parse OrderBook from account {:?} data", order_book_address),
+            },
+            Err(_) => println!("No orders for pair {:?} / {:?}", token_mint, price_mint),
+        }
+        return;
+    }
+
+    match context.client.get_program_accounts_with_config(
+        &context.p2p_swap,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(SwapSPLOrder::LEN as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                commitment: None,
+                data_slice: None,
+                min_context_slot: None,
+            },
+            with_context: None,
+        }) {
+        Ok(accounts) => {
+            println!("Orders: {:#?}", accounts);
+        },
+        Err(err) => {
+            println!("Failed to retrieve orders for swap contract: {:?}", err);
+        },
+    }
+}
+
+fn process_create_market(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let token_mint = Pubkey::try_from(args.value_of("token_mint").unwrap()).unwrap();
+        let price_mint = Pubkey::try_from(args.value_of("price_mint").unwrap()).unwrap();
+        let tick_size = args.value_of("tick_size").unwrap().parse::<u64>().unwrap();
+        let lot_size = args.value_of("lot_size").unwrap().parse::<u64>().unwrap();
+        let min_order_size = args.value_of("min_order_size").unwrap().parse::<u64>().unwrap();
+
+        let (market, _) = get_market_address(&context.p2p_swap, &token_mint, &price_mint);
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::CreateMarket as u8];
+        data.extend_from_slice(&tick_size.to_le_bytes());
+        data.extend_from_slice(&lot_size.to_le_bytes());
+        data.extend_from_slice(&min_order_size.to_le_bytes());
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new_readonly(token_mint, false),
+                    AccountMeta::new_readonly(price_mint, false),
+                    AccountMeta::new(market, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                ],
+                data,
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nNew market created: {:?}", market);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_update_market(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let token_mint = Pubkey::try_from(args.value_of("token_mint").unwrap()).unwrap();
+        let price_mint = Pubkey::try_from(args.value_of("price_mint").unwrap()).unwrap();
+        let tick_size = args.value_of("tick_size").unwrap().parse::<u64>().unwrap();
+        let lot_size = args.value_of("lot_size").unwrap().parse::<u64>().unwrap();
+        let min_order_size = args.value_of("min_order_size").unwrap().parse::<u64>().unwrap();
+
+        let (market, _) = get_market_address(&context.p2p_swap, &token_mint, &price_mint);
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::UpdateMarket as u8];
+        data.extend_from_slice(&tick_size.to_le_bytes());
+        data.extend_from_slice(&lot_size.to_le_bytes());
+        data.extend_from_slice(&min_order_size.to_le_bytes());
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(market, false),
+                    AccountMeta::new_readonly(get_config_address(&context.p2p_swap).0, false),
+                ],
+                data,
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nMarket updated: {:?}", market);
+        println!("Transaction: {:?}", signature);
+    }
+}
+
+fn process_list_markets(context: &AppContext) {
+    match context.client.get_program_accounts_with_config(
+        &context.p2p_swap,
+        RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(Market::LEN as u64)]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(solana_account_decoder::UiAccountEncoding::Base64),
+                commitment: None,
+                data_slice: None,
+                min_context_slot: None,
+            },
+            with_context: None,
+        }) {
+        Ok(accounts) => {
+            for (address, account) in accounts {
+                match Market::unpack(&account.data) {
+                    Ok(market) => println!("Market {:?}\n{:#?}", address, market),
+                    Err(_) => println!("Failed to parse Market from account {:?} data", address),
+                }
+            }
+        },
+        Err(err) => {
+            println!("Failed to retrieve markets for swap contract: {:?}", err);
+        },
+    }
+}
+
+fn process_init_config(context: &AppContext) {
+    let (config, _) = get_config_address(&context.p2p_swap);
+    let (program_data, _) = Pubkey::find_program_address(
+        &[context.p2p_swap.as_ref()],
+        &solana_sdk::bpf_loader_upgradeable::id(),
+    );
+
+    let instructions = vec![
+        Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(context.signer.pubkey(), true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(program_data, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data: vec![P2PSwapInstructions::InitConfig as u8],
+        }
+    ];
+
+    let signature = context.send_transaction(&instructions).unwrap();
+
+    println!("\n\nConfig initialized: {:?}", config);
+    println!("Transaction: {:?}", signature);
+}
+
+fn process_get_config(context: &AppContext) {
+    let (config_address, _) = get_config_address(&context.p2p_swap);
+    match context.client.get_account(&config_address) {
+        Ok(account) => match Config::unpack(&account.data) {
+            Ok(config) => println!("\n\nConfig {:?}\n{:#?}", config_address, config),
+            Err(_) => println!("Failed to parse Config from account {:?} data", config_address),
+        },
+        Err(_) => println!("Config {:?} is not initialized", config_address),
+    }
+
+    let (arbitration_address, _) = get_arbitration_config_address(&context.p2p_swap);
+    if let Ok(account) = context.client.get_account(&arbitration_address) {
+        match ArbitrationConfig::unpack(&account.data) {
+            Ok(arbitration) => println!("Arbitration config {:?}\n{:#?}", arbitration_address, arbitration),
+            Err(_) => println!("Failed to parse ArbitrationConfig from account {:?} data", arbitration_address),
+        }
+    }
+}
+
+fn process_set_paused(context: &AppContext, is_paused: bool) {
+    let (config, _) = get_config_address(&context.p2p_swap);
+
+    let instructions = vec![
+        Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new_readonly(context.signer.pubkey(), true),
+                AccountMeta::new(config, false),
+            ],
+            data: vec![P2PSwapInstructions::SetPaused as u8, is_paused as u8],
+        }
+    ];
+
+    let signature = context.send_transaction(&instructions).unwrap();
+
+    println!("\n\nPaused flag set to {:?}. Txn: {:?}", is_paused, signature);
+}
+
+fn process_set_admin(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let new_admin = Pubkey::try_from(args.value_of("new_admin").unwrap()).unwrap();
+        let (config, _) = get_config_address(&context.p2p_swap);
+
+        let mut data: Vec<u8> = vec![P2PSwapInstructions::SetAdmin as u8];
+        data.extend_from_slice(&new_admin.to_bytes());
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                ],
+                data,
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nAdmin changed to {:?}. Txn: {:?}", new_admin, signature);
+    }
+}
+
+fn process_set_mint_list_mode(context: &AppContext, args: &Option<&ArgMatches>) {
+    if let Some(args) = args {
+        let mint_list_mode = match args.value_of("mode").unwrap() {
+            "open" => MintListMode::Open,
+            "denylist" => MintListMode::Denylist,
+            "allowlist" => MintListMode::Allowlist,
+            mode => {
+                println!("Unknown mint list mode {:?}", mode);
+                exit(1);
+            }
+        };
+
+        let (config, _) = get_config_address(&context.p2p_swap);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new_readonly(context.signer.pubkey(), true),
+                    AccountMeta::new(config, false),
+                ],
+                data: vec![P2PSwapInstructions::SetMintListMode as u8, mint_list_mode as u8],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nMint list mode set to {:?}. Txn: {:?}", mint_list_mode, signature);
+    }
+}
+
+fn process_set_mint_status(context: &AppContext, args: &Option<&ArgMatches>, status: Option<MintStatus>) {
+    if let Some(args) = args {
+        let mint = Pubkey::try_from(args.value_of("mint").unwrap()).unwrap();
+        let (config, _) = get_config_address(&context.p2p_swap);
+        let (mint_list_entry, _) = get_mint_list_entry_address(&context.p2p_swap, &mint);
+
+        let instructions = vec![
+            Instruction {
+                program_id: context.p2p_swap,
+                accounts: vec![
+                    AccountMeta::new(context.signer.pubkey(), true),
+                    AccountMeta::new_readonly(config, false),
+                    AccountMeta::new_readonly(mint, false),
+                    AccountMeta::new(mint_list_entry, false),
+                    AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                ],
+                data: vec![
+                    P2PSwapInstructions::SetMintStatus as u8,
+                    status.map_or(0, |status| status as u8),
+                ],
+            }
+        ];
+
+        let signature = context.send_transaction(&instructions).unwrap();
+
+        println!("\n\nStatus of mint {:?} set to {:?}. Txn: {:?}", mint, status, signature);
+    }
+}
+
+fn process_set_arbitrator(context: &AppContext, args: &Option<&ArgMatches>) {
+    let arbitration = args.map(|args| ArbitrationConfig {
+        arbitrator: Pubkey::from_str(args.value_of("arbitrator").unwrap()).unwrap(),
+        fee_bps: args.value_of("fee_bps").unwrap().parse::<u16>().unwrap(),
+    });
+    let (config, _) = get_config_address(&context.p2p_swap);
+    let (arbitration_config, _) = get_arbitration_config_address(&context.p2p_swap);
+
+    let mut data: Vec<u8> = vec![P2PSwapInstructions::SetArbitrationConfig as u8];
+    if let Some(arbitration) = &arbitration {
+        data.extend_from_slice(&arbitration.arbitrator.to_bytes());
+        data.extend_from_slice(&arbitration.fee_bps.to_le_bytes());
+    }
+
+    let instructions = vec![
+        Instruction {
+            program_id: context.p2p_swap,
+            accounts: vec![
+                AccountMeta::new(context.signer.pubkey(), true),
+                AccountMeta::new_readonly(config, false),
+                AccountMeta::new(arbitration_config, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data,
+        }
+    ];
+
+    let signature = context.send_transaction(&instructions).unwrap();
+
+    println!("\n\nDefault arbitrator set to {:?}. Txn: {:?}", arbitration, signature);
+}
+
+fn main() {
+    let default_bids_file = solana_cli_config::CONFIG_FILE.as_ref()
+        .map(|config_file| Path::new(config_file).with_file_name("p2p-swap-sealed-bids"))
+        .map(|bids_file| bids_file.to_string_lossy().to_string())
+        .unwrap_or_else(|| "p2p-swap-sealed-bids".to_string());
+    let bids_file_arg = Arg::with_name("bids_file")
+        .long("bids-file")
+        .value_name("PATH")
+        .takes_value(true)
+        .default_value(&default_bids_file)
+        .help("File to store salts of sealed bids");
+
+    let matches = App::new("p2p-swap-cli")
+        .about("CLI to interact with p2p-swap smart-contract")
+        .arg({
+            let arg = Arg::with_name("config_file")
+                .short("C")
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .global(true)
+                .help("Configuration file to use");
+
+            if let Some(ref config_file) = *solana_cli_config::CONFIG_FILE {
+                arg.default_value(config_file)
+            } else {
+                arg
+            }
+        })
+        .arg(
+            Arg::with_name("json_rpc_url")
+                .short("u")
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .global(true)
+                .validator(is_url_or_moniker)
+                .help("URL for Solana node"),
+        )
+        .arg(
+            Arg::with_name("p2p-swap")
+                .short("p")
+                .long("p2p-swap")
+                .value_name("P2P_SWAP")
+                .takes_value(true)
+                .required(false)
+                .global(true)
+                .validator(is_valid_pubkey)
+                .help("Address of P2P-Swap smart contract")
+        )
+        .arg(
+            Arg::with_name("keypair")
+                .short("k")
+                .long("keypair")
+                .takes_value(true)
+                .global(true)
+                .help("Specify signer for transactions (use default solana account if not specified)")
+        )
+        .subcommand(
+            SubCommand::with_name("create-order")
+                .about("Creates new public order in p2p-swap")
+                .arg(
+                    Arg::with_name("sell-token")
+                        .index(1)
+                        .value_name("SELL_TOKEN")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token to sell")
+                )
+                .arg(
+                    Arg::with_name("sell-amount")
+                        .index(2)
+                        .value_name("SELL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("How much of sell-token to place into order \
+                        (count in smallest possible portions - see decimals)")
+                )
+                .arg(
+                    Arg::with_name("sell-minimum")
+                        .index(3)
+                        .value_name("SELL_MINIMUM")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("The minimum amount of sell-token to buy \
+                        (count in smallest possible portions - see decimals)")
+                )
+                .arg(
+                    Arg::with_name("buy-token")
+                        .index(4)
+                        .value_name("BUY_TOKEN")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token to buy")
+                )
+                .arg(
+                    Arg::with_name("buy-amount")
+                        .index(5)
+                        .value_name("BUY_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("How much of buy-token to receive in case order will be completely \
+                        filled (count in smallest possible portions - see decimals). \
+                        Actually, price of a single sell-token will be BUY_AMOUNT / SELL_AMOUNT")
+                )
+                .arg(
+                    Arg::with_name("is_private")
+                        .long("is-private")
+                        .value_name("IS_PRIVATE")
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("false")
+                        .validator(is_valid_bool)
+                        .help("Whether to create private order")
+                )
+                .arg(
+                    Arg::with_name("start_at")
+                        .long("start-at")
+                        .value_name("START_AT")
+                        .takes_value(true)
+                        .required(false)
+                        .validator(is_valid_start_at)
+                        .help("Order can not be filled before this moment. \
+                        Format: slot:<SLOT> or time:<UNIX_TIMESTAMP>")
+                )
+                .arg(
+                    Arg::with_name("payout_wallet")
+                        .long("payout-wallet")
+                        .value_name("PAYOUT_WALLET")
+                        .takes_value(true)
+                        .required(false)
+                        .validator(is_valid_pubkey)
+                        .help("Token account of buy token which receives payments \
+                        (associated token account of seller by default)")
+                )
+                .arg(
+                    Arg::with_name("gate_mint")
+                        .long("gate-mint")
+                        .value_name("GATE_MINT")
+                        .takes_value(true)
+                        .required(false)
+                        .validator(is_valid_pubkey)
+                        .help("Only holders of this token (e.g. NFT or membership token) can fill the order")
+                )
+                .arg(
+                    Arg::with_name("gate_min_balance")
+                        .long("gate-min-balance")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .default_value("1")
+                        .validator(is_valid_u64)
+                        .help("Minimum balance of --gate-mint buyer should hold")
+                )
+                .arg(
+                    Arg::with_name("payout")
+                        .long("payout")
+                        .value_name("ADDRESS:BPS")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required(false)
+                        .validator(is_valid_payout)
+                        .help("Partner receiving share of every payment in basis points. ADDRESS is token \
+                        account of buy token or wallet (its associated token account is used). \
+                        Can be repeated, rest of payments goes to payout wallet")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("get-order")
+                .about("Read order information from chain")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("buy-order")
+                .about("Fills given order by buying specified amount of order token or by spending \
+                specified amount of price token")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+                .arg(
+                    Arg::with_name("sell_token_amount")
+                        .index(2)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required_unless("spend")
+                        .conflicts_with("spend")
+                        .validator(is_valid_u64)
+                        .help("Amount of order token to buy")
+                )
+                .arg(
+                    Arg::with_name("spend")
+                        .long("spend")
+                        .value_name("PRICE_AMOUNT")
+                        .takes_value(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of price token to spend instead of AMOUNT. Program buys maximum amount \
+                        of order token this budget allows (rounded down to market lot size)")
+                )
+                .arg(
+                    Arg::with_name("recipient")
+                        .long("recipient")
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token account or wallet which receives bought tokens (signer by default)")
+                )
+                .arg(
+                    Arg::with_name("gate_wallet")
+                        .long("gate-wallet")
+                        .value_name("TOKEN_ACCOUNT")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Signer's token account of gate mint of gated order (associated token account \
+                        by default)")
+                )
+                .arg(
+                    Arg::with_name("unlock_signature")
+                        .long("unlock-signature")
+                        .value_name("UNLOCK_SIGNATURE")
+                        .takes_value(true)
+                        .required(false)
+                        .help("base58 encoded signature")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("revoke-order")
+                .about("Revokes given order buy parts or whole at once")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+                .arg(
+                    Arg::with_name("revoke_amount")
+                        .index(2)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("0")
+                        .validator(is_valid_u64)
+                        .help("Amount of tokens to revoke (only seller or its operator can specify this parameter)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-flip")
+                .about("Makes ORDER a flip order: price tokens collected by its fills are added to REVERSE_ORDER \
+                (seller's order selling price token for the order token) at the order price improved by \
+                SPREAD_BPS. Set flip on both orders for a grid")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+                .arg(
+                    Arg::with_name("reverse_order_address")
+                        .index(2)
+                        .value_name("REVERSE_ORDER")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of reverse order (account). Can be created empty with \
+                        create-order <PRICE_MINT> 0 0 <TOKEN_MINT> 0")
+                )
+                .arg(
+                    Arg::with_name("spread_bps")
+                        .index(3)
+                        .value_name("SPREAD_BPS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_bps)
+                        .help("Spread between the order price and reverse order price in basis points")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("remove-flip")
+                .about("Stops refilling reverse order from fills of given order (seller or operator with amend \
+                permission)")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("list-order")
+                .about("Lists public order which is not in the order book of its pair (e.g. it did not fit into \
+                full book)")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("migrate-order")
+                .about("Converts order created before order counter was introduced to the current layout (signer \
+                tops up its rent)")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("pause-order")
+                .about("Temporarily blocks filling of given order (seller or operator with pause permission)")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("resume-order")
+                .about("Allows filling of previously paused order (seller or operator with pause permission)")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("set-operator")
+                .about("Allows OPERATOR to manage signer's orders. Tokens and payments still go to the seller")
+                .arg(
+                    Arg::with_name("operator")
+                        .index(1)
+                        .value_name("OPERATOR")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Operator (hot) key")
+                )
+                .arg(
+                    Arg::with_name("permissions")
+                        .index(2)
+                        .value_name("PERMISSIONS")
+                        .takes_value(true)
+                        .default_value("amend,pause,revoke")
+                        .validator(is_valid_permissions)
+                        .help("Comma separated permissions: amend (set/remove flip), pause (pause/resume orders), \
+                        revoke (revoke orders)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("remove-operator")
+                .about("Removes OPERATOR of signer's orders")
+                .arg(
+                    Arg::with_name("operator")
+                        .index(1)
+                        .value_name("OPERATOR")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Operator (hot) key")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("list-operators")
+                .about("Returns operators of signer's orders")
+        )
+        .subcommand(
+            SubCommand::with_name("cancel-all-orders")
+                .about("Cancels all open orders of the seller at once by bumping seller epoch (seller or \
+                operator with revoke permission). Cancelled orders can't be filled and can be revoked by anyone")
+                .arg(
+                    Arg::with_name("seller")
+                        .long("seller")
+                        .value_name("SELLER")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Seller whose orders are cancelled. Signer by default (operator has to specify it)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("propose")
+                .about("Proposes different terms for given order. Payment is escrowed until \
+                seller accepts proposal or buyer withdraws it")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+                .arg(
+                    Arg::with_name("sell_amount")
+                        .index(2)
+                        .value_name("SELL_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of order token to buy")
+                )
+                .arg(
+                    Arg::with_name("buy_amount")
+                        .index(3)
+                        .value_name("BUY_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of price token to pay")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("list-proposals")
+                .about("Returns list of proposals made for given order")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of order (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("accept-proposal")
+                .about("Settles given proposal at proposed terms (seller only)")
+                .arg(
+                    Arg::with_name("proposal_address")
+                        .index(1)
+                        .value_name("PROPOSAL_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of proposal (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("withdraw-proposal")
+                .about("Cancels given proposal and returns escrowed payment (buyer only)")
+                .arg(
+                    Arg::with_name("proposal_address")
+                        .index(1)
+                        .value_name("PROPOSAL_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of proposal (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-deal")
+                .about("Creates two-sided OTC deal between signer (party A) and PARTY_B. Both parties \
+                deposit their side into escrow and deal settles once both deposits are made")
+                .arg(
+                    Arg::with_name("party_b")
+                        .index(1)
+                        .value_name("PARTY_B")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of counterparty wallet")
+                )
+                .arg(
+                    Arg::with_name("mint_a")
+                        .index(2)
+                        .value_name("MINT_A")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Mint of token deposited by party A")
+                )
+                .arg(
+                    Arg::with_name("amount_a")
+                        .index(3)
+                        .value_name("AMOUNT_A")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of token deposited by party A")
+                )
+                .arg(
+                    Arg::with_name("mint_b")
+                        .index(4)
+                        .value_name("MINT_B")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Mint of token deposited by party B")
+                )
+                .arg(
+                    Arg::with_name("amount_b")
+                        .index(5)
+                        .value_name("AMOUNT_B")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of token deposited by party B")
+                )
+                .arg(
+                    Arg::with_name("expires_in")
+                        .long("expires-in")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("86400")
+                        .validator(is_valid_u64)
+                        .help("Seconds until deal deadline. After deadline deposits can be reclaimed \
+                        if deal is not funded by both parties")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("get-deal")
+                .about("Returns deal information")
+                .arg(
+                    Arg::with_name("deal_address")
+                        .index(1)
+                        .value_name("DEAL_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of deal (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("deposit-deal")
+                .about("Deposits signer side of the deal into escrow. Settles the deal if counterparty has already deposited")
+                .arg(
+                    Arg::with_name("deal_address")
+                        .index(1)
+                        .value_name("DEAL_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of deal (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("settle-deal")
+                .about("Settles deal funded by both parties")
+                .arg(
+                    Arg::with_name("deal_address")
+                        .index(1)
+                        .value_name("DEAL_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of deal (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("reclaim-deal")
+                .about("Returns signer deposit of expired deal that was not funded by counterparty")
+                .arg(
+                    Arg::with_name("deal_address")
+                        .index(1)
+                        .value_name("DEAL_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of deal (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-rfq")
+                .about("Requests quotes for buying AMOUNT of TOKEN_MINT paid in PRICE_MINT. Sellers compete \
+                by posting quotes with escrowed tokens")
+                .arg(
+                    Arg::with_name("token_mint")
+                        .index(1)
+                        .value_name("TOKEN_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token to buy")
+                )
+                .arg(
+                    Arg::with_name("price_mint")
+                        .index(2)
+                        .value_name("PRICE_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token for payment")
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .index(3)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of token to buy")
+                )
+                .arg(
+                    Arg::with_name("expires_in")
+                        .long("expires-in")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("3600")
+                        .validator(is_valid_u64)
+                        .help("Seconds until RFQ deadline. Quotes can be made and accepted only before deadline")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("get-rfq")
+                .about("Returns RFQ information")
+                .arg(
+                    Arg::with_name("rfq_address")
+                        .index(1)
+                        .value_name("RFQ_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of RFQ (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("quote")
+                .about("Responds to RFQ with a price for the whole RFQ amount. Tokens are escrowed until \
+                the quote is accepted or reclaimed")
+                .arg(
+                    Arg::with_name("rfq_address")
+                        .index(1)
+                        .value_name("RFQ_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of RFQ (account)")
+                )
+                .arg(
+                    Arg::with_name("price_amount")
+                        .index(2)
+                        .value_name("PRICE_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of price token to get for the whole RFQ amount")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("list-quotes")
+                .about("Returns quotes made for given RFQ (best first)")
+                .arg(
+                    Arg::with_name("rfq_address")
+                        .index(1)
+                        .value_name("RFQ_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of RFQ (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("accept-quote")
+                .about("Settles RFQ with given quote (buyer only)")
+                .arg(
+                    Arg::with_name("quote_address")
+                        .index(1)
+                        .value_name("QUOTE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of quote (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("reclaim-quote")
+                .about("Returns escrowed tokens of a quote which was not accepted (seller only)")
+                .arg(
+                    Arg::with_name("quote_address")
+                        .index(1)
+                        .value_name("QUOTE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of quote (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("close-rfq")
+                .about("Closes RFQ and returns its rent (buyer only). Closing open RFQ cancels it")
+                .arg(
+                    Arg::with_name("rfq_address")
+                        .index(1)
+                        .value_name("RFQ_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of RFQ (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-auction")
+                .about("Sells AMOUNT of TOKEN_MINT in English auction paid in PRICE_MINT. Tokens and bids \
+                are escrowed until the auction is settled")
+                .arg(
+                    Arg::with_name("token_mint")
+                        .index(1)
+                        .value_name("TOKEN_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token to sell")
+                )
+                .arg(
+                    Arg::with_name("price_mint")
+                        .index(2)
+                        .value_name("PRICE_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token for payment")
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .index(3)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of token to sell")
+                )
+                .arg(
+                    Arg::with_name("min_bid")
+                        .index(4)
+                        .value_name("MIN_BID")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Minimal first bid (reserve price)")
+                )
+                .arg(
+                    Arg::with_name("min_increment")
+                        .long("min-increment")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .default_value("1")
+                        .validator(is_valid_u64)
+                        .help("Minimal amount a new bid should exceed the highest bid by")
+                )
+                .arg(
+                    Arg::with_name("duration_slots")
+                        .long("duration-slots")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .default_value("216000")
+                        .validator(is_valid_u64)
+                        .help("Auction duration in slots")
+                )
+                .arg(
+                    Arg::with_name("extension_slots")
+                        .long("extension-slots")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .default_value("150")
+                        .validator(is_valid_u64)
+                        .help("Anti-sniping window. A bid placed closer than SLOTS to the end extends \
+                        the auction to SLOTS after the bid")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("get-auction")
+                .about("Returns auction information")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of auction (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("bid")
+                .about("Places a bid in auction. Previous highest bid is refunded to its bidder")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of auction (account)")
+                )
+                .arg(
+                    Arg::with_name("bid")
+                        .index(2)
+                        .value_name("BID")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of price token to bid")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("settle-auction")
+                .about("Transfers tokens to the winner and the winning bid to the seller after auction \
+                end. Can be called by anyone")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of auction (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-sealed-auction")
+                .about("Sells AMOUNT of TOKEN_MINT in sealed-bid auction paid in PRICE_MINT. Bidders commit \
+                hashes of their bids with deposits and reveal bids after the commit phase")
+                .arg(
+                    Arg::with_name("token_mint")
+                        .index(1)
+                        .value_name("TOKEN_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token to sell")
+                )
+                .arg(
+                    Arg::with_name("price_mint")
+                        .index(2)
+                        .value_name("PRICE_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token for payment")
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .index(3)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of token to sell")
+                )
+                .arg(
+                    Arg::with_name("min_bid")
+                        .index(4)
+                        .value_name("MIN_BID")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Minimal bid (reserve price)")
+                )
+                .arg(
+                    Arg::with_name("min_deposit")
+                        .long("min-deposit")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .validator(is_valid_u64)
+                        .help("Minimal deposit escrowed with a commitment [default: MIN_BID]")
+                )
+                .arg(
+                    Arg::with_name("commit_slots")
+                        .long("commit-slots")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .default_value("216000")
+                        .validator(is_valid_u64)
+                        .help("Duration of commit phase in slots")
+                )
+                .arg(
+                    Arg::with_name("reveal_slots")
+                        .long("reveal-slots")
+                        .value_name("SLOTS")
+                        .takes_value(true)
+                        .default_value("216000")
+                        .validator(is_valid_u64)
+                        .help("Duration of reveal phase in slots")
+                )
+                .arg(
+                    Arg::with_name("forfeit_unrevealed")
+                        .long("forfeit-unrevealed")
+                        .takes_value(false)
+                        .help("Deposits of bids which were not revealed go to seller instead of being refunded")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("get-sealed-auction")
+                .about("Returns sealed-bid auction information")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of sealed-bid auction (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("commit-bid")
+                .about("Commits a sealed bid with a deposit. Salt is stored locally for reveal-bids")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of sealed-bid auction (account)")
+                )
+                .arg(
+                    Arg::with_name("bid")
+                        .index(2)
+                        .value_name("BID")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of price token to bid")
+                )
+                .arg(
+                    Arg::with_name("deposit")
+                        .long("deposit")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of price token to escrow. Deposit above the bid hides the bid \
+                        [default: BID]")
+                )
+                .arg(bids_file_arg.clone())
+        )
+        .subcommand(
+            SubCommand::with_name("reveal-bids")
+                .about("Reveals bids stored by commit-bid once their auctions enter reveal phase")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Reveal bid in this auction only")
+                )
+                .arg(
+                    Arg::with_name("wait")
+                        .long("wait")
+                        .takes_value(false)
+                        .help("Wait for reveal phase instead of skipping auctions in commit phase")
+                )
+                .arg(bids_file_arg)
+        )
+        .subcommand(
+            SubCommand::with_name("settle-sealed-auction")
+                .about("Transfers tokens to the winner and the winning bid to the seller after reveal phase, \
+                then returns deposits of all bids. Can be called by anyone")
+                .arg(
+                    Arg::with_name("auction_address")
+                        .index(1)
+                        .value_name("AUCTION_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of sealed-bid auction (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-fiat-trade")
+                .about("Escrows AMOUNT of TOKEN_MINT for BUYER who pays FIAT_AMOUNT off-chain. Buyer marks \
+                payment as sent, then seller releases tokens")
+                .arg(
+                    Arg::with_name("buyer")
+                        .index(1)
+                        .value_name("BUYER")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Wallet of the buyer")
+                )
+                .arg(
+                    Arg::with_name("token_mint")
+                        .index(2)
+                        .value_name("TOKEN_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token to sell")
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .index(3)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Amount of token to sell")
+                )
+                .arg(
+                    Arg::with_name("fiat_amount")
+                        .index(4)
+                        .value_name("FIAT_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Fiat payment in minor units of the currency (e.g. cents)")
+                )
+                .arg(
+                    Arg::with_name("fiat_currency")
+                        .index(5)
+                        .value_name("CURRENCY")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_currency)
+                        .help("ISO 4217 code of fiat currency (e.g. USD)")
+                )
+                .arg(
+                    Arg::with_name("payment_window")
+                        .long("payment-window")
+                        .value_name("SECONDS")
+                        .takes_value(true)
+                        .default_value("1800")
+                        .validator(is_valid_u64)
+                        .help("Seconds for buyer to mark payment as sent. After that seller can cancel the trade")
+                )
+                .arg(
+                    Arg::with_name("arbitrator")
+                        .long("arbitrator")
+                        .value_name("ARBITRATOR")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Wallet resolving disputes of the trade. Default arbitrator is used if not specified")
+                )
+                .arg(
+                    Arg::with_name("arbitration_fee_bps")
+                        .long("arbitration-fee-bps")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .default_value("0")
+                        .validator(is_valid_bps)
+                        .help("Maximum fee of --arbitrator in basis points of AMOUNT")
+                )
         )
-        .arg(
-            Arg::with_name("p2p-swap")
-                .short("p")
-                .long("p2p-swap")
-                .value_name("P2P_SWAP")
-                .takes_value(true)
-                .required(false)
-                .global(true)
-                .validator(is_valid_pubkey)
-                .help("Address of P2P-Swap smart contract")
+        .subcommand(
+            SubCommand::with_name("get-fiat-trade")
+                .about("Returns fiat trade information")
+                .arg(
+                    Arg::with_name("trade_address")
+                        .index(1)
+                        .value_name("TRADE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of fiat trade (account)")
+                )
         )
-        .arg(
-            Arg::with_name("keypair")
-                .short("k")
-                .long("keypair")
-                .takes_value(true)
-                .global(true)
-                .help("Specify signer for transactions (use default solana account if not specified)")
+        .subcommand(
+            SubCommand::with_name("list-fiat-trades")
+                .about("Returns fiat trades of the signer (as seller or buyer)")
         )
         .subcommand(
-            SubCommand::with_name("create-order")
-                .about("Creates new public order in p2p-swap")
+            SubCommand::with_name("mark-fiat-sent")
+                .about("Marks fiat payment as sent (buyer only)")
                 .arg(
-                    Arg::with_name("sell-token")
+                    Arg::with_name("trade_address")
                         .index(1)
-                        .value_name("SELL_TOKEN")
+                        .value_name("TRADE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of fiat trade (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("release-fiat-trade")
+                .about("Releases escrowed tokens to buyer after fiat payment is received (seller only)")
+                .arg(
+                    Arg::with_name("trade_address")
+                        .index(1)
+                        .value_name("TRADE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of fiat trade (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("cancel-fiat-trade")
+                .about("Returns escrowed tokens to seller. Buyer can cancel any time, seller only if payment \
+                was not marked as sent in the payment window")
+                .arg(
+                    Arg::with_name("trade_address")
+                        .index(1)
+                        .value_name("TRADE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of fiat trade (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("raise-dispute")
+                .about("Freezes fiat trade until arbitrator awards escrowed tokens (buyer or seller)")
+                .arg(
+                    Arg::with_name("trade_address")
+                        .index(1)
+                        .value_name("TRADE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of fiat trade (account)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("resolve-dispute")
+                .about("Awards escrowed tokens of disputed fiat trade to WINNER (arbitrator of the trade or default \
+                arbitrator after dispute deadline)")
+                .arg(
+                    Arg::with_name("trade_address")
+                        .index(1)
+                        .value_name("TRADE_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of fiat trade (account)")
+                )
+                .arg(
+                    Arg::with_name("winner")
+                        .index(2)
+                        .value_name("WINNER")
+                        .takes_value(true)
+                        .required(true)
+                        .possible_values(&["buyer", "seller"])
+                        .help("Party receiving escrowed tokens")
+                )
+                .arg(
+                    Arg::with_name("fee_bps")
+                        .long("fee-bps")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .validator(is_valid_bps)
+                        .help("Arbitration fee in basis points of trade amount. Maximum fee of the trade if not specified")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-reputation")
+                .about("Creates reputation account of WALLET (signer by default). Trades of wallets with \
+                reputation are recorded on-chain and can be rated by counterparties")
+                .arg(
+                    Arg::with_name("wallet")
+                        .index(1)
+                        .value_name("WALLET")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Wallet to create reputation for")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("get-reputation")
+                .about("Returns reputation of WALLET (signer by default)")
+                .arg(
+                    Arg::with_name("wallet")
+                        .index(1)
+                        .value_name("WALLET")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Wallet to show reputation of")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("rate-trade")
+                .about("Rates counterparty of a trade (fills of ORDER by BUYER). Each side can rate the trade once")
+                .arg(
+                    Arg::with_name("order_address")
+                        .index(1)
+                        .value_name("ORDER_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of filled order (account)")
+                )
+                .arg(
+                    Arg::with_name("rating")
+                        .index(2)
+                        .value_name("RATING")
+                        .takes_value(true)
+                        .required(true)
+                        .possible_values(&["1", "2", "3", "4", "5"])
+                        .help("Rating from 1 to 5")
+                )
+                .arg(
+                    Arg::with_name("buyer")
+                        .long("buyer")
+                        .value_name("BUYER")
+                        .takes_value(true)
+                        .validator(is_valid_pubkey)
+                        .help("Buyer of the trade. Signer by default (seller has to specify it)")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("list-orders")
+                .about("Returns list of orders. If token pair is specified, orders are read from \
+                on-chain order book of this pair sorted by price")
+                .arg(
+                    Arg::with_name("token_mint")
+                        .index(1)
+                        .value_name("TOKEN_MINT")
+                        .takes_value(true)
+                        .required(false)
+                        .requires("price_mint")
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token to sell")
+                )
+                .arg(
+                    Arg::with_name("price_mint")
+                        .index(2)
+                        .value_name("PRICE_MINT")
+                        .takes_value(true)
+                        .required(false)
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token for payment")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("create-market")
+                .about("Registers market for a token pair with tick and lot size rules. Signer must be admin")
+                .arg(
+                    Arg::with_name("token_mint")
+                        .index(1)
+                        .value_name("TOKEN_MINT")
                         .takes_value(true)
                         .required(true)
                         .validator(is_valid_pubkey)
                         .help("Token mint of the token to sell")
                 )
                 .arg(
-                    Arg::with_name("sell-amount")
+                    Arg::with_name("price_mint")
                         .index(2)
-                        .value_name("SELL_AMOUNT")
+                        .value_name("PRICE_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token for payment")
+                )
+                .arg(
+                    Arg::with_name("tick_size")
+                        .index(3)
+                        .value_name("TICK_SIZE")
                         .takes_value(true)
                         .required(true)
                         .validator(is_valid_u64)
-                        .help("How much of sell-token to place into order \
+                        .help("Price of a single lot should be a multiple of this value \
+                        (count in smallest possible portions of price token - see decimals)")
+                )
+                .arg(
+                    Arg::with_name("lot_size")
+                        .index(4)
+                        .value_name("LOT_SIZE")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Order and fill amounts should be a multiple of this value \
+                        (count in smallest possible portions of token - see decimals)")
+                )
+                .arg(
+                    Arg::with_name("min_order_size")
+                        .index(5)
+                        .value_name("MIN_ORDER_SIZE")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_u64)
+                        .help("Minimum amount of token to place into order \
                         (count in smallest possible portions - see decimals)")
                 )
+        )
+        .subcommand(
+            SubCommand::with_name("update-market")
+                .about("Changes tick and lot size rules of registered market. Signer must be admin")
                 .arg(
-                    Arg::with_name("sell-minimum")
+                    Arg::with_name("token_mint")
+                        .index(1)
+                        .value_name("TOKEN_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token to sell")
+                )
+                .arg(
+                    Arg::with_name("price_mint")
+                        .index(2)
+                        .value_name("PRICE_MINT")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("Token mint of the token for payment")
+                )
+                .arg(
+                    Arg::with_name("tick_size")
                         .index(3)
-                        .value_name("SELL_MINIMUM")
+                        .value_name("TICK_SIZE")
                         .takes_value(true)
                         .required(true)
                         .validator(is_valid_u64)
-                        .help("The minimum amount of sell-token to buy \
-                        (count in smallest possible portions - see decimals)")
+                        .help("Price of a single lot should be a multiple of this value \
+                        (count in smallest possible portions of price token - see decimals)")
                 )
                 .arg(
-                    Arg::with_name("buy-token")
+                    Arg::with_name("lot_size")
                         .index(4)
-                        .value_name("BUY_TOKEN")
+                        .value_name("LOT_SIZE")
                         .takes_value(true)
                         .required(true)
-                        .validator(is_valid_pubkey)
-                        .help("Token mint of the token to buy")
+                        .validator(is_valid_u64)
+                        .help("Order and fill amounts should be a multiple of this value \
+                        (count in smallest possible portions of token - see decimals)")
                 )
                 .arg(
-                    Arg::with_name("buy-amount")
+                    Arg::with_name("min_order_size")
                         .index(5)
-                        .value_name("BUY_AMOUNT")
+                        .value_name("MIN_ORDER_SIZE")
                         .takes_value(true)
                         .required(true)
                         .validator(is_valid_u64)
-                        .help("How much of buy-token to receive in case order will be completely \
-                        filled (count in smallest possible portions - see decimals). \
-                        Actually, price of a single sell-token will be BUY_AMOUNT / SELL_AMOUNT")
+                        .help("Minimum amount of token to place into order \
+                        (count in smallest possible portions - see decimals)")
                 )
+        )
+        .subcommand(
+            SubCommand::with_name("list-markets")
+                .about("Returns list of registered markets")
+        )
+        .subcommand(
+            SubCommand::with_name("init-config")
+                .about("Initializes global config. Signer must be upgrade authority of p2p-swap program \
+                and becomes its admin")
+        )
+        .subcommand(
+            SubCommand::with_name("get-config")
+                .about("Read global config from chain")
+        )
+        .subcommand(
+            SubCommand::with_name("pause")
+                .about("Blocks creation and filling of orders (admin only)")
+        )
+        .subcommand(
+            SubCommand::with_name("unpause")
+                .about("Allows creation and filling of orders again (admin only)")
+        )
+        .subcommand(
+            SubCommand::with_name("set-admin")
+                .about("Transfers admin authority to another account (admin only)")
                 .arg(
-                    Arg::with_name("is_private")
-                        .long("is-private")
-                        .value_name("IS_PRIVATE")
+                    Arg::with_name("new_admin")
+                        .index(1)
+                        .value_name("NEW_ADMIN")
                         .takes_value(true)
-                        .required(false)
-                        .default_value("false")
-                        .validator(is_valid_bool)
-                        .help("Whether to create private order")
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of new admin")
                 )
         )
         .subcommand(
-            SubCommand::with_name("get-order")
-                .about("Read order information from chain")
+            SubCommand::with_name("set-mint-list-mode")
+                .about("Sets how mint lists are applied to new orders (admin only)")
                 .arg(
-                    Arg::with_name("order_address")
+                    Arg::with_name("mode")
                         .index(1)
-                        .value_name("ORDER_ADDRESS")
+                        .value_name("MODE")
                         .takes_value(true)
                         .required(true)
-                        .validator(is_valid_pubkey)
-                        .help("base58 address of order (account)")
+                        .possible_values(&["open", "denylist", "allowlist"])
+                        .help("open - any mint can be used in orders, denylist - denied mints can not \
+                        be used in orders, allowlist - only allowed mints can be used in orders")
                 )
         )
         .subcommand(
-            SubCommand::with_name("buy-order")
-                .about("Fills given order by buying specified amount of order token")
+            SubCommand::with_name("allow-mint")
+                .about("Adds mint to allow list (admin only)")
                 .arg(
-                    Arg::with_name("order_address")
+                    Arg::with_name("mint")
                         .index(1)
-                        .value_name("ORDER_ADDRESS")
+                        .value_name("MINT")
                         .takes_value(true)
                         .required(true)
                         .validator(is_valid_pubkey)
-                        .help("base58 address of order (account)")
+                        .help("base58 address of token mint")
                 )
+        )
+        .subcommand(
+            SubCommand::with_name("deny-mint")
+                .about("Adds mint to deny list (admin only)")
                 .arg(
-                    Arg::with_name("sell_token_amount")
-                        .index(2)
-                        .value_name("AMOUNT")
+                    Arg::with_name("mint")
+                        .index(1)
+                        .value_name("MINT")
                         .takes_value(true)
                         .required(true)
-                        .validator(is_valid_u64)
-                        .help("Amount of order token to buy")
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of token mint")
                 )
+        )
+        .subcommand(
+            SubCommand::with_name("remove-mint")
+                .about("Removes mint from allow and deny lists (admin only)")
                 .arg(
-                    Arg::with_name("unlock_signature")
-                        .long("unlock-signature")
-                        .value_name("UNLOCK_SIGNATURE")
+                    Arg::with_name("mint")
+                        .index(1)
+                        .value_name("MINT")
                         .takes_value(true)
-                        .required(false)
-                        .help("base58 encoded signature")
+                        .required(true)
+                        .validator(is_valid_pubkey)
+                        .help("base58 address of token mint")
                 )
         )
         .subcommand(
-            SubCommand::with_name("revoke-order")
-                .about("Revokes given order buy parts or whole at once")
+            SubCommand::with_name("set-arbitrator")
+                .about("Sets default arbitrator of new fiat trades (admin only)")
                 .arg(
-                    Arg::with_name("order_address")
+                    Arg::with_name("arbitrator")
                         .index(1)
-                        .value_name("ORDER_ADDRESS")
+                        .value_name("ARBITRATOR")
                         .takes_value(true)
                         .required(true)
                         .validator(is_valid_pubkey)
-                        .help("base58 address of order (account)")
+                        .help("Wallet resolving disputes")
                 )
                 .arg(
-                    Arg::with_name("revoke_amount")
-                        .index(2)
-                        .value_name("AMOUNT")
+                    Arg::with_name("fee_bps")
+                        .long("fee-bps")
+                        .value_name("BPS")
                         .takes_value(true)
-                        .required(false)
                         .default_value("0")
-                        .validator(is_valid_u64)
-                        .help("Amount of tokens to revoke (only seller can specify this parameter)")
+                        .validator(is_valid_bps)
+                        .help("Maximum arbitration fee in basis points of trade amount")
                 )
         )
         .subcommand(
-            SubCommand::with_name("list-orders")
-                .about("Returns list of orders")
+            SubCommand::with_name("remove-arbitrator")
+                .about("Removes default arbitrator of new fiat trades (admin only)")
         )
         .get_matches();
 
@@ -719,7 +4670,67 @@ fn main() {
         "get-order" => process_get_order(&context, &args),
         "buy-order" => process_buy_order(&context, &args),
         "revoke-order" => process_revoke_order(&context, &args),
-        "list-orders" => process_list_orders(&context),
+        "set-flip" => process_set_flip(&context, &args),
+        "remove-flip" => process_remove_flip(&context, &args),
+        "list-order" => process_list_order(&context, &args),
+        "migrate-order" => process_migrate_order(&context, &args),
+        "pause-order" => process_set_order_status(&context, &args, P2PSwapInstructions::PauseOrder),
+        "resume-order" => process_set_order_status(&context, &args, P2PSwapInstructions::ResumeOrder),
+        "set-operator" => process_set_operator(&context, &args, false),
+        "remove-operator" => process_set_operator(&context, &args, true),
+        "list-operators" => process_list_operators(&context),
+        "cancel-all-orders" => process_cancel_all_orders(&context, &args),
+        "propose" => process_propose(&context, &args),
+        "list-proposals" => process_list_proposals(&context, &args),
+        "accept-proposal" => process_accept_proposal(&context, &args),
+        "withdraw-proposal" => process_withdraw_proposal(&context, &args),
+        "create-deal" => process_create_deal(&context, &args),
+        "get-deal" => process_get_deal(&context, &args),
+        "deposit-deal" => process_deposit_deal(&context, &args),
+        "settle-deal" => process_settle_deal(&context, &args),
+        "reclaim-deal" => process_reclaim_deal(&context, &args),
+        "create-rfq" => process_create_rfq(&context, &args),
+        "get-rfq" => process_get_rfq(&context, &args),
+        "quote" => process_create_quote(&context, &args),
+        "list-quotes" => process_list_quotes(&context, &args),
+        "accept-quote" => process_accept_quote(&context, &args),
+        "reclaim-quote" => process_reclaim_quote(&context, &args),
+        "close-rfq" => process_close_rfq(&context, &args),
+        "create-auction" => process_create_auction(&context, &args),
+        "get-auction" => process_get_auction(&context, &args),
+        "bid" => process_place_bid(&context, &args),
+        "settle-auction" => process_settle_auction(&context, &args),
+        "create-sealed-auction" => process_create_sealed_auction(&context, &args),
+        "get-sealed-auction" => process_get_sealed_auction(&context, &args),
+        "commit-bid" => process_commit_bid(&context, &args),
+        "reveal-bids" => process_reveal_bids(&context, &args),
+        "settle-sealed-auction" => process_settle_sealed_auction(&context, &args),
+        "create-fiat-trade" => process_create_fiat_trade(&context, &args),
+        "get-fiat-trade" => process_get_fiat_trade(&context, &args),
+        "list-fiat-trades" => process_list_fiat_trades(&context),
+        "mark-fiat-sent" => process_mark_fiat_sent(&context, &args),
+        "release-fiat-trade" => process_release_fiat_trade(&context, &args),
+        "cancel-fiat-trade" => process_cancel_fiat_trade(&context, &args),
+        "raise-dispute" => process_raise_dispute(&context, &args),
+        "resolve-dispute" => process_resolve_dispute(&context, &args),
+        "create-reputation" => process_create_reputation(&context, &args),
+        "get-reputation" => process_get_reputation(&context, &args),
+        "rate-trade" => process_rate_trade(&context, &args),
+        "list-orders" => process_list_orders(&context, &args),
+        "create-market" => process_create_market(&context, &args),
+        "update-market" => process_update_market(&context, &args),
+        "list-markets" => process_list_markets(&context),
+        "init-config" => process_init_config(&context),
+        "get-config" => process_get_config(&context),
+        "pause" => process_set_paused(&context, true),
+        "unpause" => process_set_paused(&context, false),
+        "set-admin" => process_set_admin(&context, &args),
+        "set-mint-list-mode" => process_set_mint_list_mode(&context, &args),
+        "allow-mint" => process_set_mint_status(&context, &args, Some(MintStatus::Allowed)),
+        "deny-mint" => process_set_mint_status(&context, &args, Some(MintStatus::Denied)),
+        "remove-mint" => process_set_mint_status(&context, &args, None),
+        "set-arbitrator" => process_set_arbitrator(&context, &args),
+        "remove-arbitrator" => process_set_arbitrator(&context, &None),
         _ => {
             warn!("Unknown subcommand '{:?}'", subcommand);
             exit(1)
@@ -1,6 +1,61 @@
 use {
     crate::{
         SwapSPLOrder,
+        OrderStatus,
+        StartAt,
+        OrderCounter,
+        SellerEpoch,
+        Market,
+        OrderBook,
+        OrderBookEntry,
+        Config,
+        Proposal,
+        Deal,
+        PayoutRecipient,
+        PayoutSplit,
+        Rfq,
+        Quote,
+        Auction,
+        SealedAuction,
+        SealedBid,
+        Flip,
+        FiatTrade,
+        FiatTradeStatus,
+        FiatTradeEvent,
+        ArbitrationConfig,
+        Reputation,
+        TradeReceipt,
+        Delegate,
+        MintListMode,
+        MintListEntry,
+        MintStatus,
+        get_config_address,
+        get_mint_list_entry_address,
+        get_proposal_address,
+        get_deal_address,
+        get_payout_split_address,
+        get_rfq_address,
+        get_quote_address,
+        get_auction_address,
+        get_sealed_auction_address,
+        get_sealed_bid_address,
+        get_bid_commitment,
+        get_flip_address,
+        get_fiat_trade_address,
+        get_arbitration_config_address,
+        get_reputation_address,
+        get_trade_receipt_address,
+        get_delegate_address,
+        get_seller_epoch_address,
+        BPS_DENOMINATOR,
+        DISPUTE_RESOLUTION_PERIOD,
+        PERMISSION_AMEND,
+        PERMISSION_PAUSE,
+        PERMISSION_REVOKE,
+        PERMISSION_ALL,
+        get_order_counter_address,
+        get_market_address,
+        get_order_book_address,
         get_order_wallet_address,
         get_order_address,
         get_order_wallet_authority,
@@ -8,69 +63,421 @@ use {
         P2PSwapError,
     },
     arrayref::{array_ref, array_refs},
-    num_traits::cast::ToPrimitive,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
-        clock::Slot,
-        entrypoint,
+        bpf_loader_upgradeable,
+        clock::Clock,
         entrypoint::ProgramResult,
+        log::sol_log_data,
         msg,
         program::invoke_signed,
         program_error::ProgramError,
         program_memory::sol_memset,
-        program_pack::Pack,
+        program_pack::{IsInitialized, Pack},
         pubkey::Pubkey,
         rent::Rent,
         system_instruction, system_program,
         sysvar::{self, Sysvar},
     },
-    spl_token::state::Account as SPLAccount,
+    spl_token::state::{Account as SPLAccount, Mint as SPLMint},
     spl_associated_token_account::get_associated_token_address,
-    std::ops::DerefMut,
+    std::{ops::DerefMut, slice::Iter},
 };
 
-entrypoint!(process_instruction);
+#[cfg(not(feature="no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
 
-// Latest slot number is used as seed to generate order accounts
-// This constant prevents seed to bee too far in past
-const MAX_SLOT_DIFFERENCE: Slot = 150;
+// Program account is missing until it is allocated and assigned to the program. Lamports alone do not
+// create it: anyone can transfer lamports to the address of a PDA before the program creates it
+fn is_program_account_missing(account: &AccountInfo) -> bool {
+    account.lamports() == 0 || (system_program::check_id(account.owner) && account.data_is_empty())
+}
+
+// Creates PDA as transfer + allocate + assign instead of create_account, which fails if the address
+// already holds lamports. Payer only tops the balance up to rent exemption
+fn create_program_account<'a>(
+    system_account: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<(), ProgramError> {
+    let rent = Rent::get()?;
+    let minimum_balance = rent.minimum_balance(space).max(1);
+
+    let lamports_to_transfer = minimum_balance.saturating_sub(new_account.lamports());
+    if lamports_to_transfer > 0 {
+        invoke_signed(
+            &system_instruction::transfer(payer.key, new_account.key, lamports_to_transfer),
+            &[
+                (*payer).clone(),
+                new_account.clone(),
+                system_account.clone(),
+            ],
+            &[],
+        )?;
+    }
+
+    invoke_signed(
+        &system_instruction::allocate(new_account.key, space as u64),
+        &[new_account.clone(), system_account.clone()],
+        &[signer_seeds],
+    )?;
+
+    invoke_signed(
+        &system_instruction::assign(new_account.key, program_id),
+        &[new_account.clone(), system_account.clone()],
+        &[signer_seeds],
+    )
+}
 
 fn create_order_account<'a>(
     system_account: &AccountInfo<'a>,
     program_id: &Pubkey,
     seller: &AccountInfo<'a>,
     order_account: &AccountInfo<'a>,
-    creation_slot: u64,
+    order_index: u64,
     bump_seed: u8,
 ) -> Result<(), ProgramError> {
-    let rent = Rent::get()?;
-    let minimum_balance = rent.minimum_balance(SwapSPLOrder::LEN).max(1);
-
-    if order_account.lamports() > 0 {
+    if !is_program_account_missing(order_account) {
         msg!("Order {:?} already exists", order_account.key);
         return Err(ProgramError::Custom(P2PSwapError::OrderExists as u32));
+    }
+
+    create_program_account(
+        system_account,
+        program_id,
+        seller,
+        order_account,
+        SwapSPLOrder::LEN,
+        &[
+            b"OrderAccount",
+            &seller.key.to_bytes(),
+            &order_index.to_le_bytes(),
+            &[bump_seed],
+        ],
+    )
+}
+
+fn check_and_get_order_counter<'a>(
+    system_account: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    seller: &AccountInfo<'a>,
+    order_counter_account: &AccountInfo<'a>,
+) -> Result<OrderCounter, ProgramError> {
+    let (expected_order_counter, bump_seed) = get_order_counter_address(program_id, seller.key);
+    if expected_order_counter != *order_counter_account.key {
+        msg!(
+            "Order counter not match. Expected {:?}",
+            expected_order_counter,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(order_counter_account) {
+        // first order of the seller - counter starts from 0
+        create_program_account(
+            system_account,
+            program_id,
+            seller,
+            order_counter_account,
+            OrderCounter::LEN,
+            &[b"OrderCounter", &seller.key.to_bytes(), &[bump_seed]],
+        )?;
+
+        return Ok(OrderCounter {
+            seller: *seller.key,
+            next_order_index: 0,
+        });
+    }
+
+    if *order_counter_account.owner != *program_id {
+        msg!("Order counter is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    OrderCounter::unpack(&order_counter_account.data.borrow())
+}
+
+// Buyer of gated order should own a token account of gate mint with at least gate_min_balance
+fn check_gate(order: &SwapSPLOrder, buyer: &Pubkey, buyer_gate_wallet: &AccountInfo) -> ProgramResult {
+    if order.gate_mint == Pubkey::default() {
+        return Ok(());
+    }
+
+    spl_token::check_program_account(buyer_gate_wallet.owner)?;
+    let gate_wallet = SPLAccount::unpack(&buyer_gate_wallet.data.borrow())?;
+    if gate_wallet.owner != *buyer || gate_wallet.mint != order.gate_mint {
+        msg!("Buyer gate wallet should be token account of {:?} owned by buyer", order.gate_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if gate_wallet.amount < order.gate_min_balance {
+        msg!(
+            "Buyer should hold at least {:?} of {:?}. Got {:?}",
+            order.gate_min_balance,
+            order.gate_mint,
+            gate_wallet.amount,
+        );
+        return Err(ProgramError::Custom(P2PSwapError::GateTokenRequired as u32));
+    }
+
+    Ok(())
+}
+
+// Order rules every fill should satisfy, whichever instruction fills the order (FillOrder, AcceptProposal)
+fn check_order_fillable(
+    order: &SwapSPLOrder,
+    market: Option<&Market>,
+    sell_token_amount: u64,
+    seller_epoch: u64,
+    buyer: &Pubkey,
+    buyer_gate_wallet: &AccountInfo,
+) -> ProgramResult {
+    if order.status == OrderStatus::Paused {
+        msg!("Order is paused by seller");
+        return Err(ProgramError::Custom(P2PSwapError::OrderPaused as u32));
+    }
+
+    let clock = Clock::get()?;
+    if !order.start_at.is_started(clock.slot, clock.unix_timestamp) {
+        msg!("Order is not started yet: {:?}", order.start_at);
+        return Err(ProgramError::Custom(P2PSwapError::OrderNotStarted as u32));
+    }
+
+    if order.epoch < seller_epoch {
+        msg!("Order is cancelled by seller epoch {:?}", seller_epoch);
+        return Err(ProgramError::Custom(P2PSwapError::OrderEpochExpired as u32));
+    }
+
+    if let Some(market) = market {
+        market.check_fill(sell_token_amount).map_err(|err| {
+            msg!("Fill does not satisfy market rules: {:?}", err);
+            ProgramError::Custom(err as u32)
+        })?;
+    }
+
+    check_gate(order, buyer, buyer_gate_wallet)
+}
+
+// Current epoch of the seller. 0 until seller bumps it for the first time
+fn get_seller_epoch(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    seller_epoch_account: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    let (expected_seller_epoch, _) = get_seller_epoch_address(program_id, seller);
+    if expected_seller_epoch != *seller_epoch_account.key {
+        msg!(
+            "Seller epoch not match. Expected {:?}",
+            expected_seller_epoch,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(seller_epoch_account) {
+        return Ok(0);
+    }
+
+    if *seller_epoch_account.owner != *program_id {
+        msg!("Seller epoch is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(SellerEpoch::unpack(&seller_epoch_account.data.borrow())?.epoch)
+}
+
+fn check_and_get_market(
+    program_id: &Pubkey,
+    token_mint: &Pubkey,
+    price_mint: &Pubkey,
+    market_account: &AccountInfo,
+) -> Result<Option<Market>, ProgramError> {
+    let (expected_market, _) = get_market_address(program_id, token_mint, price_mint);
+    if expected_market != *market_account.key {
+        msg!(
+            "Market not match. Expected {:?}",
+            expected_market,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(market_account) {
+        // market is not registered for this pair - no rules to check
+        return Ok(None);
+    }
+
+    if *market_account.owner != *program_id {
+        msg!("Market is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Some(Market::unpack(&market_account.data.borrow())?))
+}
+
+fn check_and_get_order_book<'a>(
+    program_id: &Pubkey,
+    token_mint: &Pubkey,
+    price_mint: &Pubkey,
+    order_book_account: &AccountInfo<'a>,
+) -> Result<Option<OrderBook>, ProgramError> {
+    let (expected_order_book, _) = get_order_book_address(program_id, token_mint, price_mint);
+    if expected_order_book != *order_book_account.key {
+        msg!(
+            "Order book not match. Expected {:?}",
+            expected_order_book,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(order_book_account) {
+        return Ok(None);
+    }
+
+    if *order_book_account.owner != *program_id {
+        msg!("Order book is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Some(OrderBook::unpack(&order_book_account.data.borrow())?))
+}
+
+fn create_order_book_account<'a>(
+    system_account: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    token_mint: &Pubkey,
+    price_mint: &Pubkey,
+    order_book_account: &AccountInfo<'a>,
+) -> Result<OrderBook, ProgramError> {
+    let (_, bump_seed) = get_order_book_address(program_id, token_mint, price_mint);
+    create_program_account(
+        system_account,
+        program_id,
+        payer,
+        order_book_account,
+        OrderBook::LEN,
+        &[
+            b"OrderBook",
+            &token_mint.to_bytes(),
+            &price_mint.to_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    Ok(OrderBook {
+        token_mint: *token_mint,
+        price_mint: *price_mint,
+        entries: Vec::new(),
+    })
+}
+
+fn check_and_get_config(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<Option<Config>, ProgramError> {
+    let (expected_config, _) = get_config_address(program_id);
+    if expected_config != *config_account.key {
+        msg!(
+            "Config not match. Expected {:?}",
+            expected_config,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(config_account) {
+        // config is not initialized yet - no restrictions
+        return Ok(None);
+    }
+
+    if *config_account.owner != *program_id {
+        msg!("Config is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Some(Config::unpack(&config_account.data.borrow())?))
+}
+
+fn check_not_paused(
+    program_id: &Pubkey,
+    config_account: &AccountInfo,
+) -> Result<Option<Config>, ProgramError> {
+    let config = check_and_get_config(program_id, config_account)?;
+    if let Some(config) = &config {
+        if config.is_paused {
+            msg!("Program is paused");
+            return Err(ProgramError::Custom(P2PSwapError::ProgramPaused as u32));
+        }
+    }
+
+    Ok(config)
+}
+
+fn check_mint_allowed(
+    program_id: &Pubkey,
+    config: &Option<Config>,
+    mint: &Pubkey,
+    mint_list_entry_account: &AccountInfo,
+) -> ProgramResult {
+    let (expected_entry, _) = get_mint_list_entry_address(program_id, mint);
+    if expected_entry != *mint_list_entry_account.key {
+        msg!(
+            "Mint list entry not match. Expected {:?}",
+            expected_entry,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mint_list_mode = config
+        .as_ref()
+        .map_or(MintListMode::Open, |config| config.mint_list_mode);
+    if mint_list_mode == MintListMode::Open {
+        return Ok(());
+    }
+
+    let status = if is_program_account_missing(mint_list_entry_account) {
+        None
     } else {
-        invoke_signed(
-            &system_instruction::create_account(
-                seller.key,
-                order_account.key,
-                minimum_balance,
-                SwapSPLOrder::LEN as u64,
-                program_id,
-            ),
-            &[
-                (*seller).clone(),
-                order_account.clone(),
-                system_account.clone(),
-            ],
-            &[&[
-                b"OrderAccount",
-                &seller.key.to_bytes(),
-                &creation_slot.to_le_bytes(),
-                &[bump_seed],
-            ]],
-        )
+        if *mint_list_entry_account.owner != *program_id {
+            msg!("Mint list entry is not owned by program");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Some(MintListEntry::unpack(&mint_list_entry_account.data.borrow())?.status)
+    };
+
+    let is_allowed = match mint_list_mode {
+        MintListMode::Open => true,
+        MintListMode::Denylist => status != Some(MintStatus::Denied),
+        MintListMode::Allowlist => status == Some(MintStatus::Allowed),
+    };
+
+    if !is_allowed {
+        msg!("Mint {:?} is not allowed", mint);
+        return Err(ProgramError::Custom(P2PSwapError::MintNotAllowed as u32));
+    }
+
+    Ok(())
+}
+
+fn check_admin_and_get_config(
+    program_id: &Pubkey,
+    admin: &AccountInfo,
+    config_account: &AccountInfo,
+) -> Result<Config, ProgramError> {
+    let config = check_and_get_config(program_id, config_account)?
+        .ok_or_else(|| {
+            msg!("Config is not initialized");
+            ProgramError::UninitializedAccount
+        })?;
+
+    if !admin.is_signer || config.admin != *admin.key {
+        msg!("Admin not match. Expected {:?}", config.admin);
+        return Err(ProgramError::Custom(P2PSwapError::NotAdmin as u32));
     }
+
+    Ok(config)
 }
 
 fn _create_order<'a>(
@@ -81,51 +488,37 @@ fn _create_order<'a>(
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
-    let (sell_amount, buy_amount, min_sell_amount, creation_slot) = if instruction_data.len() == 32 {
-        let instruction_data = array_ref![instruction_data, 0, 32];
-        let (sell_amount, buy_amount, min_sell_amount, creation_slot)
-            = array_refs![instruction_data, 8, 8, 8, 8];
+    let (sell_amount, buy_amount, min_sell_amount, order_index, start_at, gate_min_balance) =
+        if instruction_data.len() == 49 {
+        let instruction_data = array_ref![instruction_data, 0, 49];
+        let (sell_amount, buy_amount, min_sell_amount, order_index, start_at, gate_min_balance)
+            = array_refs![instruction_data, 8, 8, 8, 8, StartAt::LEN, 8];
         (
             u64::from_le_bytes(*sell_amount),
             u64::from_le_bytes(*buy_amount),
             u64::from_le_bytes(*min_sell_amount),
-            u64::from_le_bytes(*creation_slot),
+            u64::from_le_bytes(*order_index),
+            StartAt::unpack(start_at).map_err(|_| ProgramError::InvalidInstructionData)?,
+            u64::from_le_bytes(*gate_min_balance),
         )
     } else {
         msg!(
-            "Invalid data - expected 32 bytes - {:?}",
+            "Invalid data - expected 49 bytes - {:?}",
             instruction_data,
         );
         return Err(ProgramError::InvalidInstructionData);
     };
 
-    let clock = next_account_info(account_info_iter)?; // 1 - clock account
-    if !sysvar::clock::check_id(clock.key) {
-        msg!("Clock not match");
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    let clock = sysvar::clock::Clock::from_account_info(clock)?;
-    let newest_slot = clock.slot;
-    if creation_slot > newest_slot || newest_slot - creation_slot > MAX_SLOT_DIFFERENCE {
-        msg!(
-            "creation slot {:?} is too far from current {:?}. Please, generate new order account with latest slot number as seed",
-            creation_slot,
-            newest_slot,
-        );
-        return Err(ProgramError::Custom(P2PSwapError::CreationSlotToFar as u32));
-    }
-
-    let seller = next_account_info(account_info_iter)?; // 2 - seller Pubkey
+    let seller = next_account_info(account_info_iter)?; // 1 - seller Pubkey
 
-    let seller_token_account_info = next_account_info(account_info_iter)?; // 3 - seller token account
+    let seller_token_account_info = next_account_info(account_info_iter)?; // 2 - seller token account
     let seller_token_account = SPLAccount::unpack(&seller_token_account_info.data.borrow())?;
     if seller_token_account.owner != *seller.key {
         msg!("Token account owner not match. Expected {:?}", seller.key,);
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let sell_token_mint = next_account_info(account_info_iter)?; // 4 - selling token mint
+    let sell_token_mint = next_account_info(account_info_iter)?; // 3 - selling token mint
     if seller_token_account.mint != *sell_token_mint.key {
         msg!(
             "Token mint not match. Expected {:?}",
@@ -135,7 +528,7 @@ fn _create_order<'a>(
     }
 
     let (expected_order_wallet_authority, bump_seed) = get_order_wallet_authority(program_id, seller.key);
-    let order_wallet_authority = next_account_info(account_info_iter)?; // 5 - order wallet authority
+    let order_wallet_authority = next_account_info(account_info_iter)?; // 4 - order wallet authority
     if expected_order_wallet_authority != *order_wallet_authority.key {
         msg!(
             "Order wallet authority not match. Expected {:?}",
@@ -144,9 +537,9 @@ fn _create_order<'a>(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let buy_token_mint = next_account_info(account_info_iter)?; // 6 - buy token mint
+    let buy_token_mint = next_account_info(account_info_iter)?; // 5 - buy token mint
 
-    let order_wallet = next_account_info(account_info_iter)?; // 7 - order wallet address
+    let order_wallet = next_account_info(account_info_iter)?; // 6 - order wallet address
     let expected_order_wallet = get_order_wallet_address(
         sell_token_mint.key,
         order_wallet_authority.key,
@@ -160,7 +553,7 @@ fn _create_order<'a>(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let token_program = next_account_info(account_info_iter)?; // 8 - token_program
+    let token_program = next_account_info(account_info_iter)?; // 7 - token_program
     spl_token::check_program_account(token_program.key)?;
 
     invoke_signed(
@@ -180,9 +573,9 @@ fn _create_order<'a>(
         &[&[b"OrderWalletAuthority", &seller.key.to_bytes(), &[bump_seed]]],
     )?;
 
-    let order_account = next_account_info(account_info_iter)?; // 9 - order account
+    let order_account = next_account_info(account_info_iter)?; // 8 - order account
     let (expected_order_account, bump_seed) =
-        get_order_address(program_id, seller.key, creation_slot);
+        get_order_address(program_id, seller.key, order_index);
     if expected_order_account != *order_account.key {
         msg!(
             "Order account not match. Expected {:?}",
@@ -191,34 +584,150 @@ fn _create_order<'a>(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let system_account = next_account_info(account_info_iter)?; // 10 - system account
+    let system_account = next_account_info(account_info_iter)?; // 9 - system account
     if !system_program::check_id(system_account.key) {
         msg!("System program not match. Got {:?}", system_account.key,);
         return Err(ProgramError::InvalidAccountData);
     }
 
+    let order_counter_account = next_account_info(account_info_iter)?; // 10 - order counter
+    let mut order_counter = check_and_get_order_counter(
+        system_account,
+        program_id,
+        seller,
+        order_counter_account,
+    )?;
+
+    if order_counter.next_order_index != order_index {
+        msg!(
+            "Order index not match. Expected {:?}",
+            order_counter.next_order_index,
+        );
+        return Err(ProgramError::Custom(P2PSwapError::OrderIndexMismatch as u32));
+    }
+
+    order_counter.next_order_index = order_index
+        .checked_add(1)
+        .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+    OrderCounter::pack(order_counter, order_counter_account.data.borrow_mut().deref_mut())?;
+
+    let market_account = next_account_info(account_info_iter)?; // 11 - market
+    let market = check_and_get_market(
+        program_id,
+        sell_token_mint.key,
+        buy_token_mint.key,
+        market_account,
+    )?;
+    if let Some(market) = &market {
+        market.check_order(sell_amount, buy_amount, min_sell_amount).map_err(|err| {
+            msg!("Order does not satisfy market rules: {:?}", err);
+            ProgramError::Custom(err as u32)
+        })?;
+    }
+
     create_order_account(
         system_account,
         program_id,
         seller,
         order_account,
-        creation_slot,
+        order_index,
         bump_seed,
     )?;
 
-    let order = SwapSPLOrder {
-        creation_slot,
-        seller: seller.key.clone(),
+    let order_book_account = next_account_info(account_info_iter)?; // 12 - order book
+    let order_book = check_and_get_order_book(
+        program_id,
+        sell_token_mint.key,
+        buy_token_mint.key,
+        order_book_account,
+    )?;
+
+    let config_account = next_account_info(account_info_iter)?; // 13 - config
+    let config = check_not_paused(program_id, config_account)?;
+
+    let token_mint_list_entry = next_account_info(account_info_iter)?; // 14 - token mint list entry
+    check_mint_allowed(program_id, &config, sell_token_mint.key, token_mint_list_entry)?;
+
+    let price_mint_list_entry = next_account_info(account_info_iter)?; // 15 - price mint list entry
+    check_mint_allowed(program_id, &config, buy_token_mint.key, price_mint_list_entry)?;
+
+    let payout_wallet = next_account_info(account_info_iter)?; // 16 - payout wallet (system program if not set)
+    if *payout_wallet.key != Pubkey::default() {
+        spl_token::check_program_account(payout_wallet.owner)?;
+        let payout_wallet_data = SPLAccount::unpack(&payout_wallet.data.borrow())?;
+        if payout_wallet_data.mint != *buy_token_mint.key {
+            msg!("Payout wallet mint not match. Expected {:?}", buy_token_mint.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let seller_epoch_account = next_account_info(account_info_iter)?; // 17 - seller epoch
+    let epoch = get_seller_epoch(program_id, seller.key, seller_epoch_account)?;
+
+    let gate_mint = next_account_info(account_info_iter)?; // 18 - gate mint (system program if not gated)
+    let gate_min_balance = if system_program::check_id(gate_mint.key) {
+        0
+    } else {
+        spl_token::check_program_account(gate_mint.owner)?;
+        SPLMint::unpack(&gate_mint.data.borrow())?;
+        if gate_min_balance == 0 {
+            msg!("Gate minimum balance should be greater than 0");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        gate_min_balance
+    };
+
+    let mut order = SwapSPLOrder {
+        order_index,
+        seller: *seller.key,
         sell_amount,
-        order_wallet: order_wallet.key.clone(),
-        token_mint: sell_token_mint.key.clone(),
-        price_mint: buy_token_mint.key.clone(),
+        order_wallet: *order_wallet.key,
+        token_mint: *sell_token_mint.key,
+        price_mint: *buy_token_mint.key,
         buy_amount,
         min_sell_amount,
         remains_to_fill: sell_amount,
         is_private,
+        status: OrderStatus::Active,
+        start_at,
+        payout_wallet: *payout_wallet.key,
+        epoch,
+        gate_mint: *gate_mint.key,
+        gate_min_balance,
+        is_listed: false,
     };
 
+    // private orders and orders of pairs without market are not listed in order book
+    if let Some(market) = market.filter(|_| !is_private) {
+        let mut order_book = match order_book {
+            Some(order_book) => order_book,
+            None => create_order_book_account(
+                system_account,
+                program_id,
+                seller,
+                sell_token_mint.key,
+                buy_token_mint.key,
+                order_book_account,
+            )?,
+        };
+
+        // order below listing minimum (e.g. empty reverse order of a flip order) or order which does not fit
+        // into full book is still created, it is just not listed until ListOrder
+        if sell_amount >= market.min_listing_amount() {
+            match order_book.insert(OrderBookEntry {
+                order: *order_account.key,
+                sell_amount,
+                buy_amount,
+                remains_to_fill: sell_amount,
+            }) {
+                Ok(()) => order.is_listed = true,
+                Err(err) => msg!("Order is not listed in order book: {:?}", err),
+            }
+        }
+
+        OrderBook::pack(order_book, order_book_account.data.borrow_mut().deref_mut())?;
+    }
+
     SwapSPLOrder::pack(order, order_account.data.borrow_mut().deref_mut())
 }
 
@@ -238,24 +747,16 @@ fn create_private_order<'a>(
     _create_order(program_id, accounts, instruction_data, true)
 }
 
-fn check_and_get_order(
+fn check_and_get_order_by_address(
     program_id: &Pubkey,
-    seller_account: &AccountInfo,
     order_account: &AccountInfo
 ) -> Result<(SwapSPLOrder, u8), ProgramError> {
     let order = SwapSPLOrder::unpack(&order_account.data.borrow())?;
-    if order.seller != *seller_account.key {
-        msg!(
-                "Seller not match. Expected: {:?}",
-                order.seller,
-            );
-        return Err(ProgramError::InvalidAccountData);
-    }
 
     let (expected_order_account, bump_seed) = get_order_address(
         program_id,
         &order.seller,
-        order.creation_slot
+        order.order_index
     );
 
     if expected_order_account != *order_account.key {
@@ -270,6 +771,23 @@ fn check_and_get_order(
     Ok((order, bump_seed))
 }
 
+fn check_and_get_order(
+    program_id: &Pubkey,
+    seller_account: &AccountInfo,
+    order_account: &AccountInfo
+) -> Result<(SwapSPLOrder, u8), ProgramError> {
+    let (order, bump_seed) = check_and_get_order_by_address(program_id, order_account)?;
+    if order.seller != *seller_account.key {
+        msg!(
+                "Seller not match. Expected: {:?}",
+                order.seller,
+            );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok((order, bump_seed))
+}
+
 fn check_and_get_order_wallet(
     program_id: &Pubkey,
     order: &SwapSPLOrder,
@@ -308,21 +826,440 @@ fn check_and_get_order_wallet(
     Ok((order_wallet, bump_seed))
 }
 
-fn revoke_order<'a>(
-    program_id: &'a Pubkey,
-    accounts: &'a [AccountInfo<'a>],
-    instruction_data: &[u8],
-) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-
-    let caller = next_account_info(account_info_iter)?; // 0 - caller
-    if !caller.is_signer {
-        msg!("Caller must be signer");
+// Checks escrow wallet of the owner for given mint. Escrow wallets use the same scheme as
+// order wallets: ATA of owner's order wallet authority
+fn check_escrow_wallet(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    escrow_authority: &AccountInfo,
+    escrow_wallet: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected_escrow_authority, bump_seed) = get_order_wallet_authority(program_id, owner);
+    if expected_escrow_authority != *escrow_authority.key {
+        msg!(
+            "Escrow authority not match. Expected: {:?}",
+            expected_escrow_authority,
+        );
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let seller = next_account_info(account_info_iter)?; // 1 - seller
-    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    let expected_escrow_wallet = get_order_wallet_address(mint, escrow_authority.key);
+    if expected_escrow_wallet != *escrow_wallet.key {
+        msg!(
+            "Escrow wallet not match. Expected: {:?}",
+            expected_escrow_wallet,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(bump_seed)
+}
+
+fn check_associated_wallet(owner: &Pubkey, mint: &Pubkey, wallet: &AccountInfo) -> ProgramResult {
+    let expected_wallet = get_associated_token_address(owner, mint);
+    if expected_wallet != *wallet.key {
+        msg!("Wallet not match. Expected: {:?}", expected_wallet);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+// Recipient wallet can be any token account of the mint owned by recipient. Wallet which does not exist
+// yet should be associated token account of recipient (so it can be created on the fly)
+fn check_recipient_wallet(recipient: &Pubkey, mint: &Pubkey, wallet: &AccountInfo) -> ProgramResult {
+    if wallet.data_is_empty() {
+        return check_associated_wallet(recipient, mint, wallet);
+    }
+
+    spl_token::check_program_account(wallet.owner)?;
+    let wallet_data = SPLAccount::unpack(&wallet.data.borrow())?;
+    if wallet_data.mint != *mint {
+        msg!("Recipient wallet mint not match. Expected: {:?}", mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if wallet_data.owner != *recipient {
+        msg!("Recipient wallet owner not match. Expected: {:?}", recipient);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    if !spl_token::check_id(token_program.key) {
+        msg!("Token program not match: {:?}", token_program.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+fn check_associated_token_program(associated_token_program: &AccountInfo) -> ProgramResult {
+    if !spl_associated_token_account::check_id(associated_token_program.key) {
+        msg!("Associated token program not match: {:?}", associated_token_program.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+fn create_associated_wallet_if_missing<'a>(
+    payer: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    wallet: &AccountInfo<'a>,
+    system_account: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    associated_token_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    if !wallet.data_is_empty() {
+        return Ok(());
+    }
+
+    msg!("Creating associated token account {:?}", wallet.key);
+    let create_inst = spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        payer.key,
+        owner.key,
+        mint.key,
+        &spl_token::id(),
+    );
+
+    invoke_signed(
+        &create_inst,
+        &[
+            payer.clone(),
+            wallet.clone(),
+            owner.clone(),
+            mint.clone(),
+            system_account.clone(),
+            token_program.clone(),
+            associated_token_program.clone(),
+        ],
+        &[],
+    )
+}
+
+// Optional trailing account. Missing account or system program in its place means the account is skipped
+fn next_optional_account_info<'a>(
+    account_info_iter: &mut Iter<'a, AccountInfo<'a>>,
+) -> Option<&'a AccountInfo<'a>> {
+    account_info_iter.next().filter(|account| !system_program::check_id(account.key))
+}
+
+fn check_system_program(system_account: &AccountInfo) -> ProgramResult {
+    if !system_program::check_id(system_account.key) {
+        msg!("System program not match. Got {:?}", system_account.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(())
+}
+
+// Transfers tokens from order (escrow) wallet of the owner
+fn transfer_from_escrow<'a>(
+    escrow_wallet: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    escrow_authority: &AccountInfo<'a>,
+    owner: &Pubkey,
+    bump_seed: u8,
+    amount: u64,
+) -> ProgramResult {
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            escrow_wallet.key,
+            destination.key,
+            escrow_authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            escrow_wallet.clone(),
+            destination.clone(),
+            escrow_authority.clone(),
+        ],
+        &[&[b"OrderWalletAuthority", &owner.to_bytes(), &[bump_seed]]],
+    )
+}
+
+// Transfers tokens from wallet of the transaction signer
+fn transfer_from_signer<'a>(
+    source: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    signer: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            &spl_token::id(),
+            source.key,
+            destination.key,
+            signer.key,
+            &[],
+            amount,
+        )?,
+        &[
+            source.clone(),
+            destination.clone(),
+            signer.clone(),
+        ],
+        &[],
+    )
+}
+
+fn check_and_get_payout_split(
+    program_id: &Pubkey,
+    order: &Pubkey,
+    payout_split_account: &AccountInfo,
+) -> Result<Option<PayoutSplit>, ProgramError> {
+    let (expected_payout_split, _) = get_payout_split_address(program_id, order);
+    if expected_payout_split != *payout_split_account.key {
+        msg!(
+            "Payout split not match. Expected {:?}",
+            expected_payout_split,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(payout_split_account) {
+        // order has no partners - whole payment goes to payout wallet
+        return Ok(None);
+    }
+
+    if *payout_split_account.owner != *program_id {
+        msg!("Payout split is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Some(PayoutSplit::unpack(&payout_split_account.data.borrow())?))
+}
+
+// Returns flip of the order if it is set and its reverse order still exists
+fn check_and_get_flip(
+    program_id: &Pubkey,
+    order: &Pubkey,
+    flip_account: &AccountInfo,
+    reverse_order_account: &AccountInfo,
+) -> Result<Option<Flip>, ProgramError> {
+    let (expected_flip, _) = get_flip_address(program_id, order);
+    if expected_flip != *flip_account.key {
+        msg!(
+            "Flip not match. Expected {:?}",
+            expected_flip,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(flip_account) {
+        return Ok(None);
+    }
+
+    if *flip_account.owner != *program_id {
+        msg!("Flip is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let flip = Flip::unpack(&flip_account.data.borrow())?;
+    if flip.reverse_order != *reverse_order_account.key {
+        msg!("Reverse order not match. Expected {:?}", flip.reverse_order);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(reverse_order_account) {
+        // reverse order is revoked - payment goes to payout wallet
+        return Ok(None);
+    }
+
+    Ok(Some(flip))
+}
+
+// Splits payment between recipients of payout split and payout wallet of the order. Recipient wallets
+// are read from account_info_iter in the same order as they are listed in payout split
+fn get_payout_transfers<'a>(
+    payout_split: Option<PayoutSplit>,
+    payout_wallet: &'a AccountInfo<'a>,
+    account_info_iter: &mut Iter<'a, AccountInfo<'a>>,
+    amount: u64,
+) -> Result<Vec<(&'a AccountInfo<'a>, u64)>, ProgramError> {
+    let mut transfers = Vec::new();
+    let mut rest = amount;
+    if let Some(payout_split) = payout_split {
+        for (recipient, share) in payout_split.recipients.iter().zip(payout_split.shares(amount)) {
+            let recipient_wallet = next_account_info(account_info_iter)?;
+            if recipient.wallet != *recipient_wallet.key {
+                msg!("Payout recipient wallet not match. Expected {:?}", recipient.wallet);
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            rest = rest
+                .checked_sub(share)
+                .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+            transfers.push((recipient_wallet, share));
+        }
+    }
+
+    transfers.insert(0, (payout_wallet, rest));
+    transfers.retain(|(_, amount)| *amount > 0);
+    Ok(transfers)
+}
+
+// Returns lamports of program account to recipient and erases its data
+fn close_program_account(account: &AccountInfo, recipient: &AccountInfo) -> ProgramResult {
+    let recipient_starting_lamports = recipient.lamports();
+    **recipient.lamports.borrow_mut() = recipient_starting_lamports
+        .checked_add(account.lamports())
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    **account.lamports.borrow_mut() = 0;
+
+    let data_len = account.data_len();
+    sol_memset(*account.data.borrow_mut(), 0, data_len);
+
+    Ok(())
+}
+
+// Operator signs instead of the seller. Its Delegate account should grant the permission
+fn check_operator(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    operator: &AccountInfo,
+    delegate_account: &AccountInfo,
+    permission: u8,
+) -> ProgramResult {
+    if !operator.is_signer {
+        msg!("Operator must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let (expected_delegate, _) = get_delegate_address(program_id, seller, operator.key);
+    if expected_delegate != *delegate_account.key {
+        msg!(
+            "Delegate not match. Expected {:?}",
+            expected_delegate,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(delegate_account) {
+        msg!("{:?} is not an operator of seller {:?}", operator.key, seller);
+        return Err(ProgramError::Custom(P2PSwapError::OperatorNotAllowed as u32));
+    }
+
+    if *delegate_account.owner != *program_id {
+        msg!("Delegate {:?} is not owned by program", delegate_account.key);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let delegate = Delegate::unpack(&delegate_account.data.borrow())?;
+    if !delegate.has_permission(permission) {
+        msg!("Operator {:?} has no permission {:?}", operator.key, permission);
+        return Err(ProgramError::Custom(P2PSwapError::OperatorNotAllowed as u32));
+    }
+
+    Ok(())
+}
+
+// Returns signer of a management instruction: seller itself or its operator. Operator and its
+// Delegate account follow the other accounts of the instruction when seller does not sign
+fn check_seller_or_operator<'a>(
+    program_id: &Pubkey,
+    seller: &'a AccountInfo<'a>,
+    account_info_iter: &mut Iter<'a, AccountInfo<'a>>,
+    permission: u8,
+) -> Result<&'a AccountInfo<'a>, ProgramError> {
+    if seller.is_signer {
+        return Ok(seller);
+    }
+
+    let (operator, delegate_account) = match (account_info_iter.next(), account_info_iter.next()) {
+        (Some(operator), Some(delegate_account)) => (operator, delegate_account),
+        _ => {
+            msg!("Seller or its operator must be signer");
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    };
+
+    check_operator(program_id, seller.key, operator, delegate_account, permission)?;
+    Ok(operator)
+}
+
+fn check_and_get_reputation(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    reputation_account: &AccountInfo,
+) -> Result<Option<Reputation>, ProgramError> {
+    let (expected_reputation, _) = get_reputation_address(program_id, wallet);
+    if expected_reputation != *reputation_account.key {
+        msg!(
+            "Reputation of {:?} not match. Expected {:?}",
+            wallet,
+            expected_reputation,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(reputation_account) {
+        return Ok(None);
+    }
+
+    if *reputation_account.owner != *program_id {
+        msg!("Reputation of {:?} is not owned by program", wallet);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Some(Reputation::unpack(&reputation_account.data.borrow())?))
+}
+
+// Reads optional seller and buyer reputation accounts and records the trade
+// when both sides have reputation
+fn record_trade_in_reputations<'a>(
+    program_id: &Pubkey,
+    seller: &Pubkey,
+    buyer: &Pubkey,
+    account_info_iter: &mut Iter<'a, AccountInfo<'a>>,
+) -> ProgramResult {
+    let seller_reputation_account = next_optional_account_info(account_info_iter);
+    let buyer_reputation_account = next_optional_account_info(account_info_iter);
+    let (seller_reputation_account, buyer_reputation_account) =
+        match (seller_reputation_account, buyer_reputation_account) {
+            (Some(seller_reputation_account), Some(buyer_reputation_account)) if seller != buyer => {
+                (seller_reputation_account, buyer_reputation_account)
+            }
+            _ => return Ok(()),
+        };
+
+    let seller_reputation = check_and_get_reputation(program_id, seller, seller_reputation_account)?;
+    let buyer_reputation = check_and_get_reputation(program_id, buyer, buyer_reputation_account)?;
+    if let (Some(mut seller_reputation), Some(mut buyer_reputation)) = (seller_reputation, buyer_reputation) {
+        msg!("Recording trade in reputations");
+        let clock = Clock::get()?;
+        seller_reputation.record_trade(clock.slot);
+        buyer_reputation.record_trade(clock.slot);
+        Reputation::pack(seller_reputation, seller_reputation_account.data.borrow_mut().deref_mut())?;
+        Reputation::pack(buyer_reputation, buyer_reputation_account.data.borrow_mut().deref_mut())?;
+    }
+
+    Ok(())
+}
+
+fn revoke_order<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?; // 0 - caller
+    if !caller.is_signer {
+        msg!("Caller must be signer");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
     let (mut order, _) = check_and_get_order(program_id, seller, order_account)?;
 
     let order_wallet_authority = next_account_info(account_info_iter)?; // 3 - order wallet authority
@@ -335,11 +1272,72 @@ fn revoke_order<'a>(
         order_wallet_account,
     )?;
 
-    let revoke_amount = if *caller.key != *seller.key {
-        if order.remains_to_fill > order.min_sell_amount {
+    let seller_wallet = next_account_info(account_info_iter)?; // 5 - seller wallet address
+    let expected_seller_wallet = get_associated_token_address(seller.key, &order_wallet.mint);
+    if expected_seller_wallet != *seller_wallet.key {
+        msg!(
+            "Seller wallet not match. Expected {:?}",
+            expected_seller_wallet,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    if !spl_token::check_id(token_program.key) {
+        msg!(
+            "Token program not match. Expected {:?}",
+            spl_token::id(),
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Accounts 7..12 are optional: they can be omitted from the end, system program in place of an account
+    // skips it. Seller revoking own order which is not listed in order book needs none of them
+    let order_book_account = next_optional_account_info(account_info_iter); // 7 - order book (if order is listed)
+    let seller_reputation_account = next_optional_account_info(account_info_iter); // 8 - seller reputation
+
+    // Operator with revoke permission acts as seller. Tokens and rent still go to seller
+    let delegate_account = next_optional_account_info(account_info_iter); // 9 - caller delegate (operator only)
+    let is_operator = match delegate_account {
+        Some(delegate_account) if *caller.key != *seller.key => {
+            check_operator(program_id, seller.key, caller, delegate_account, PERMISSION_REVOKE)?;
+            true
+        },
+        _ => false,
+    };
+    let is_seller = *caller.key == *seller.key || is_operator;
+
+    // orders cancelled by seller epoch can be revoked by anyone, tokens still go to seller
+    let seller_epoch_account = next_optional_account_info(account_info_iter); // 10 - seller epoch
+    let is_cancelled = match seller_epoch_account {
+        Some(seller_epoch_account) => order.epoch < get_seller_epoch(program_id, seller.key, seller_epoch_account)?,
+        None => false,
+    };
+
+    // closed along with the order, their rent goes to seller
+    let payout_split_account = match next_optional_account_info(account_info_iter) { // 11 - payout split
+        Some(payout_split_account) => check_and_get_payout_split(program_id, order_account.key, payout_split_account)?
+            .map(|_| payout_split_account),
+        None => None,
+    };
+
+    let flip_account = next_optional_account_info(account_info_iter); // 12 - flip
+    if let Some(flip_account) = flip_account {
+        let (expected_flip, _) = get_flip_address(program_id, order_account.key);
+        if expected_flip != *flip_account.key {
+            msg!(
+                "Flip not match. Expected {:?}",
+                expected_flip,
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let revoke_amount = if !is_seller {
+        if order.remains_to_fill > order.min_sell_amount && !is_cancelled {
             // order still have enough tokens on the balance to make transactions
-            // it can be closed only by owner (seller)
-            msg!("Only seller can revoke unfinished orders");
+            // it can be closed only by owner (seller) or its operator
+            msg!("Only seller or its operator can revoke unfinished orders");
             return Err(ProgramError::InvalidAccountData);
         }
         // order should be revoked entirely
@@ -360,35 +1358,43 @@ fn revoke_order<'a>(
     }
 
     let revoke_amount = if revoke_amount == 0 {
-        // this case only reached if caller == seller
+        // this case only reached if caller is seller or its operator
         order.remains_to_fill
     } else {
         revoke_amount
     };
 
-    let seller_wallet = next_account_info(account_info_iter)?; // 5 - seller wallet address
-    let expected_seller_wallet = get_associated_token_address(seller.key, &order_wallet.mint);
-    if expected_seller_wallet != *seller_wallet.key {
-        msg!(
-            "Seller wallet not match. Expected {:?}",
-            expected_seller_wallet,
-        );
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    let token_program = next_account_info(account_info_iter)?; // 6 - token program
-    if !spl_token::check_id(token_program.key) {
-        msg!(
-            "Token program not match. Expected {:?}",
-            spl_token::id(),
-        );
-        return Err(ProgramError::InvalidAccountData);
-    }
-
     let remains_to_fill_after = order.remains_to_fill
         .checked_sub(revoke_amount)
         .ok_or(ProgramError::InvalidInstructionData)?;
 
+    if order.is_listed {
+        let order_book_account = order_book_account.ok_or_else(|| {
+            msg!("Order is listed in order book. Order book account is required");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+
+        if let Some(mut order_book) = check_and_get_order_book(
+            program_id,
+            &order.token_mint,
+            &order.price_mint,
+            order_book_account,
+        )? {
+            order_book.update(order_account.key, remains_to_fill_after);
+            order.is_listed = order_book.contains(order_account.key);
+            OrderBook::pack(order_book, order_book_account.data.borrow_mut().deref_mut())?;
+        }
+    }
+
+    if let Some(seller_reputation_account) = seller_reputation_account {
+        if let Some(mut seller_reputation) = check_and_get_reputation(program_id, seller.key, seller_reputation_account)? {
+            if is_seller {
+                seller_reputation.revoke_count = seller_reputation.revoke_count.saturating_add(1);
+                Reputation::pack(seller_reputation, seller_reputation_account.data.borrow_mut().deref_mut())?;
+            }
+        }
+    }
+
     let tfer_inst = spl_token::instruction::transfer(
         &spl_token::id(),
         order_wallet_account.key,
@@ -409,8 +1415,10 @@ fn revoke_order<'a>(
     )?;
 
     if remains_to_fill_after == 0 {
-        let caller_starting_lamports = caller.lamports();
-        **caller.lamports.borrow_mut() = caller_starting_lamports
+        // rent of orders cancelled by seller epoch goes to seller too, not to whoever cleans them up
+        let rent_recipient = if is_operator || is_cancelled { seller } else { caller };
+        let rent_recipient_starting_lamports = rent_recipient.lamports();
+        **rent_recipient.lamports.borrow_mut() = rent_recipient_starting_lamports
             .checked_add(order_account.lamports())
             .ok_or(ProgramError::InvalidInstructionData)?;
 
@@ -418,6 +1426,14 @@ fn revoke_order<'a>(
 
         sol_memset(*order_account.data.borrow_mut(), 0, SwapSPLOrder::LEN);
 
+        if let Some(payout_split_account) = payout_split_account {
+            close_program_account(payout_split_account, seller)?;
+        }
+
+        if let Some(flip_account) = flip_account.filter(|flip_account| !is_program_account_missing(flip_account)) {
+            close_program_account(flip_account, seller)?;
+        }
+
         Ok(())
     } else {
         order.remains_to_fill = remains_to_fill_after;
@@ -425,14 +1441,21 @@ fn revoke_order<'a>(
     }
 }
 
-fn fill_order<'a>(
+// When is_exact_spend is set instruction data contains amount of price token to spend instead of
+// amount of order token to buy. Order token amount is then computed as:
+// 1. amount which can be bought for the whole budget (rounded down)
+// 2. limited by remains_to_fill of the order
+// 3. rounded down to lot size of the market (if the market exists)
+// Buyer pays for resulting amount at order price (rounded up) which still never exceeds the budget.
+fn _fill_order<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
     instruction_data: &[u8],
+    is_exact_spend: bool,
 ) -> ProgramResult {
-    let sell_token_amount = if instruction_data.len() == 8 {
-        let sell_token_amount = array_ref![instruction_data, 0, 8];
-        u64::from_le_bytes(*sell_token_amount)
+    let amount = if instruction_data.len() == 8 {
+        let amount = array_ref![instruction_data, 0, 8];
+        u64::from_le_bytes(*amount)
     } else {
         msg!(
             "Invalid data - expected 8 bytes - {:?}",
@@ -485,16 +1508,6 @@ fn fill_order<'a>(
         }
     }
 
-    if order.min_sell_amount > sell_token_amount {
-        msg!("Buy amount is below minimum");
-        return Err(ProgramError::Custom(P2PSwapError::BuyAmountBelowMinimum as u32));
-    }
-
-    if order.remains_to_fill < sell_token_amount {
-        msg!("Order has not enough tokens");
-        return Err(ProgramError::Custom(P2PSwapError::NotEnoughTokensInOrder as u32));
-    }
-
     let order_wallet_authority = next_account_info(account_info_iter)?; // 5 - order wallet authority
     let sell_token = next_account_info(account_info_iter)?; // 6 - sell token mint
     let order_wallet_accinfo = next_account_info(account_info_iter)?; // 7 - order wallet
@@ -526,47 +1539,82 @@ fn fill_order<'a>(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let seller_buy_token_wallet_address =
-        get_associated_token_address(
-            seller.key,
-            buy_token.key
-        );
-    let seller_buy_token_wallet = next_account_info(account_info_iter)?; // 10 - seller buy token wallet
+    let seller_buy_token_wallet_address = order.get_payout_wallet();
+    let seller_buy_token_wallet = next_account_info(account_info_iter)?; // 10 - seller buy token (payout) wallet
     if seller_buy_token_wallet_address != *seller_buy_token_wallet.key {
         msg!("Seller buy token wallet not match. Expected: {:?}", seller_buy_token_wallet_address);
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let buyer_sell_token_wallet_address =
-        get_associated_token_address(
-            buyer.key,
-            sell_token.key
-        );
-    let buyer_sell_token_wallet = next_account_info(account_info_iter)?; // 11 - buyer sell token wallet
-    if buyer_sell_token_wallet_address != *buyer_sell_token_wallet.key {
-        msg!("Buyer sell token wallet not match. Expected: {:?}", buyer_sell_token_wallet_address);
-        return Err(ProgramError::InvalidAccountData);
-    }
+    let recipient_wallet = next_account_info(account_info_iter)?; // 11 - recipient sell token wallet
+    let recipient = next_account_info(account_info_iter)?; // 12 - recipient (owner of recipient wallet)
+    check_recipient_wallet(recipient.key, sell_token.key, recipient_wallet)?;
 
-    let token_program = next_account_info(account_info_iter)?; // 12 - token program
+    let token_program = next_account_info(account_info_iter)?; // 13 - token program
     if !spl_token::check_id(token_program.key) {
         msg!("Token program not match: {:?}", token_program.key);
         return Err(ProgramError::InvalidAccountData);
     }
 
-    let buy_token_amount = (
-        (sell_token_amount as u128)
-            .checked_mul(order.buy_amount as u128)
-            .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32)))?
-        .checked_div(order.sell_amount as u128)
-        .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?
-        .to_u64().ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+    let system_account = next_account_info(account_info_iter)?; // 14 - system program
+    check_system_program(system_account)?;
 
-    msg!("Transfering from order to buyer");
+    let associated_token_program = next_account_info(account_info_iter)?; // 15 - associated token program
+    check_associated_token_program(associated_token_program)?;
+
+    let market_account = next_account_info(account_info_iter)?; // 16 - market
+    let market = check_and_get_market(
+        program_id,
+        &order.token_mint,
+        &order.price_mint,
+        market_account,
+    )?;
+
+    let sell_token_amount = if is_exact_spend {
+        let mut sell_token_amount = order.amount_for_price(amount)
+            .map_err(|err| ProgramError::Custom(err as u32))?
+            .min(order.remains_to_fill);
+        if let Some(market) = &market {
+            sell_token_amount = market.round_down_to_lot(sell_token_amount);
+        }
+
+        if sell_token_amount == 0 {
+            msg!("Spend amount {:?} is not enough to buy any tokens", amount);
+            return Err(ProgramError::Custom(P2PSwapError::BuyAmountBelowMinimum as u32));
+        }
+
+        sell_token_amount
+    } else {
+        amount
+    };
+
+    order.check_fill_amount(sell_token_amount).map_err(|err| {
+        msg!("Invalid fill amount: {:?}", err);
+        ProgramError::Custom(err as u32)
+    })?;
+
+    let buy_token_amount = order.price_for_amount(sell_token_amount)
+        .map_err(|err| ProgramError::Custom(err as u32))?;
+    if buy_token_amount == 0 {
+        msg!("Payment for the fill rounds to 0");
+        return Err(ProgramError::Custom(P2PSwapError::ZeroPayment as u32));
+    }
+    msg!("Filling {:?} tokens for {:?}", sell_token_amount, buy_token_amount);
+
+    create_associated_wallet_if_missing(
+        buyer,
+        recipient,
+        sell_token,
+        recipient_wallet,
+        system_account,
+        token_program,
+        associated_token_program,
+    )?;
+    msg!("Transfering from order to recipient");
     let tfer_inst = spl_token::instruction::transfer(
         &spl_token::id(),
         order_wallet_accinfo.key,
-        buyer_sell_token_wallet.key,
+        recipient_wallet.key,
         order_wallet_authority.key,
         &[],
         sell_token_amount,
@@ -576,37 +1624,3809 @@ fn fill_order<'a>(
         &tfer_inst,
         &[
             order_wallet_accinfo.clone(),
-            buyer_sell_token_wallet.clone(),
+            recipient_wallet.clone(),
             order_wallet_authority.clone(),
         ],
         &[&[b"OrderWalletAuthority", &seller.key.to_bytes(), &[order_wallet_seed]]],
     )?;
 
-    msg!("Transfering from buyer to seller");
-    let tfer_inst = spl_token::instruction::transfer(
-        &spl_token::id(),
-        buyer_buy_token_wallet.key,
-        seller_buy_token_wallet.key,
-        buyer.key,
-        &[],
-        buy_token_amount,
-    )?;
+    order.remains_to_fill -= sell_token_amount;
 
-    invoke_signed(
-        &tfer_inst,
+    let order_book_account = next_account_info(account_info_iter)?; // 17 - order book
+    if let Some(mut order_book) = check_and_get_order_book(
+        program_id,
+        &order.token_mint,
+        &order.price_mint,
+        order_book_account,
+    )? {
+        order_book.update(order_account.key, order.remains_to_fill);
+        order.is_listed = order_book.contains(order_account.key);
+        OrderBook::pack(order_book, order_book_account.data.borrow_mut().deref_mut())?;
+    }
+
+    let config_account = next_account_info(account_info_iter)?; // 18 - config
+    check_not_paused(program_id, config_account)?;
+
+    let payout_split_account = next_account_info(account_info_iter)?; // 19 - payout split
+    let payout_split = check_and_get_payout_split(program_id, order_account.key, payout_split_account)?;
+
+    // 21..23 - reverse order accounts (ignored when the order is not a flip order)
+    let flip_account = next_account_info(account_info_iter)?; // 20 - flip
+    let reverse_order_account = next_account_info(account_info_iter)?; // 21 - reverse order
+    let reverse_order_wallet = next_account_info(account_info_iter)?; // 22 - reverse order wallet
+    let reverse_order_book_account = next_account_info(account_info_iter)?; // 23 - reverse order book
+    let flip = check_and_get_flip(program_id, order_account.key, flip_account, reverse_order_account)?;
+
+    let seller_reputation_account = next_account_info(account_info_iter)?; // 24 - seller reputation
+    let buyer_reputation_account = next_account_info(account_info_iter)?; // 25 - buyer reputation
+    let trade_receipt_account = next_account_info(account_info_iter)?; // 26 - trade receipt
+    // Reputation and receipt are only recorded when both sides created their reputation accounts
+    let clock = Clock::get()?;
+    let seller_reputation = check_and_get_reputation(program_id, seller.key, seller_reputation_account)?;
+    let buyer_reputation = check_and_get_reputation(program_id, buyer.key, buyer_reputation_account)?;
+    if let (Some(mut seller_reputation), Some(mut buyer_reputation)) = (seller_reputation, buyer_reputation) {
+        if seller.key != buyer.key {
+            let (expected_receipt, bump_seed) =
+                get_trade_receipt_address(program_id, order_account.key, buyer.key);
+            if expected_receipt != *trade_receipt_account.key {
+                msg!(
+                    "Trade receipt not match. Expected {:?}",
+                    expected_receipt,
+                );
+                return Err(ProgramError::InvalidAccountData);
+            }
+
+            let receipt = if is_program_account_missing(trade_receipt_account) {
+                create_program_account(
+                    system_account,
+                    program_id,
+                    buyer,
+                    trade_receipt_account,
+                    TradeReceipt::LEN,
+                    &[b"TradeReceipt", &order_account.key.to_bytes(), &buyer.key.to_bytes(), &[bump_seed]],
+                )?;
+
+                TradeReceipt {
+                    order: *order_account.key,
+                    seller: *seller.key,
+                    buyer: *buyer.key,
+                    volume: sell_token_amount,
+                    is_rated_by_seller: false,
+                    is_rated_by_buyer: false,
+                }
+            } else {
+                let mut receipt = TradeReceipt::unpack(&trade_receipt_account.data.borrow())?;
+                receipt.volume = receipt.volume.saturating_add(sell_token_amount);
+                receipt
+            };
+
+            msg!("Recording trade in reputations");
+            seller_reputation.record_trade(clock.slot);
+            buyer_reputation.record_trade(clock.slot);
+            Reputation::pack(seller_reputation, seller_reputation_account.data.borrow_mut().deref_mut())?;
+            Reputation::pack(buyer_reputation, buyer_reputation_account.data.borrow_mut().deref_mut())?;
+            TradeReceipt::pack(receipt, trade_receipt_account.data.borrow_mut().deref_mut())?;
+        }
+    }
+
+    let seller_epoch_account = next_account_info(account_info_iter)?; // 27 - seller epoch
+    let seller_epoch = get_seller_epoch(program_id, seller.key, seller_epoch_account)?;
+
+    let buyer_gate_wallet = next_account_info(account_info_iter)?; // 28 - buyer gate wallet (system program if not gated)
+    check_order_fillable(&order, market.as_ref(), sell_token_amount, seller_epoch, buyer.key, buyer_gate_wallet)?;
+
+    // cancelled reverse order is not refilled, payment goes to seller instead
+    let reverse_order = match flip {
+        Some(flip) => {
+            let (reverse_order, _) = check_and_get_order(program_id, seller, reverse_order_account)?;
+            if reverse_order.epoch < seller_epoch {
+                None
+            } else {
+                Some((flip, reverse_order))
+            }
+        },
+        None => None,
+    };
+
+    if let Some((flip, mut reverse_order)) = reverse_order {
+        if reverse_order.order_wallet != *reverse_order_wallet.key {
+            msg!("Reverse order wallet not match. Expected {:?}", reverse_order.order_wallet);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let reverse_buy_amount = order.flip_buy_amount(buy_token_amount, flip.spread_bps)
+            .map_err(|err| ProgramError::Custom(err as u32))?;
+        reverse_order.refill(buy_token_amount, reverse_buy_amount)
+            .map_err(|err| ProgramError::Custom(err as u32))?;
+
+        if !reverse_order.is_private {
+            if let Some(mut reverse_order_book) = check_and_get_order_book(
+                program_id,
+                &reverse_order.token_mint,
+                &reverse_order.price_mint,
+                reverse_order_book_account,
+            )? {
+                // listed reverse order moves to its new price, unlisted one is listed with ListOrder
+                if reverse_order_book.contains(reverse_order_account.key) {
+                    if let Err(err) = reverse_order_book.replace(OrderBookEntry {
+                        order: *reverse_order_account.key,
+                        sell_amount: reverse_order.sell_amount,
+                        buy_amount: reverse_order.buy_amount,
+                        remains_to_fill: reverse_order.remains_to_fill,
+                    }) {
+                        msg!("Reverse order is not listed in order book: {:?}", err);
+                    }
+                    OrderBook::pack(reverse_order_book, reverse_order_book_account.data.borrow_mut().deref_mut())?;
+                }
+            }
+        }
+
+        msg!("Transfering from buyer to reverse order");
+        transfer_from_signer(buyer_buy_token_wallet, reverse_order_wallet, buyer, buy_token_amount)?;
+        SwapSPLOrder::pack(reverse_order, reverse_order_account.data.borrow_mut().deref_mut())?;
+    } else {
+        // custom payout wallet is expected to exist, only seller's associated wallet can be created
+        if order.payout_wallet == Pubkey::default() {
+            create_associated_wallet_if_missing(
+                buyer,
+                seller,
+                buy_token,
+                seller_buy_token_wallet,
+                system_account,
+                token_program,
+                associated_token_program,
+            )?;
+        }
+
+        // 29.. - payout split recipient wallets
+        let payout_transfers = get_payout_transfers(
+            payout_split,
+            seller_buy_token_wallet,
+            account_info_iter,
+            buy_token_amount,
+        )?;
+
+        msg!("Transfering from buyer to seller");
+        for (payout_wallet, amount) in payout_transfers {
+            transfer_from_signer(buyer_buy_token_wallet, payout_wallet, buyer, amount)?;
+        }
+    }
+
+    SwapSPLOrder::pack(order, order_account.data.borrow_mut().deref_mut())
+}
+
+fn fill_order<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    _fill_order(program_id, accounts, instruction_data, false)
+}
+
+fn fill_order_exact_spend<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    _fill_order(program_id, accounts, instruction_data, true)
+}
+
+// Tick size, lot size and minimum order size of a market
+fn unpack_market_rules(instruction_data: &[u8]) -> Result<(u64, u64, u64), ProgramError> {
+    let (tick_size, lot_size, min_order_size) = if instruction_data.len() == 24 {
+        let instruction_data = array_ref![instruction_data, 0, 24];
+        let (tick_size, lot_size, min_order_size) = array_refs![instruction_data, 8, 8, 8];
+        (
+            u64::from_le_bytes(*tick_size),
+            u64::from_le_bytes(*lot_size),
+            u64::from_le_bytes(*min_order_size),
+        )
+    } else {
+        msg!(
+            "Invalid data - expected 24 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if tick_size == 0 || lot_size == 0 {
+        msg!("Tick size and lot size should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok((tick_size, lot_size, min_order_size))
+}
+
+fn create_market<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (tick_size, lot_size, min_order_size) = unpack_market_rules(instruction_data)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let authority = next_account_info(account_info_iter)?; // 1 - market authority (admin)
+    if !authority.is_signer {
+        msg!("Market authority must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_mint_info = next_account_info(account_info_iter)?; // 2 - token mint
+    spl_token::check_program_account(token_mint_info.owner)?;
+    let token_mint = SPLMint::unpack(&token_mint_info.data.borrow())?;
+
+    let price_mint_info = next_account_info(account_info_iter)?; // 3 - price mint
+    spl_token::check_program_account(price_mint_info.owner)?;
+    let price_mint = SPLMint::unpack(&price_mint_info.data.borrow())?;
+
+    let market_account = next_account_info(account_info_iter)?; // 4 - market account
+    let (expected_market, bump_seed) = get_market_address(
+        program_id,
+        token_mint_info.key,
+        price_mint_info.key,
+    );
+    if expected_market != *market_account.key {
+        msg!(
+            "Market not match. Expected {:?}",
+            expected_market,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(market_account) {
+        msg!("Market {:?} already exists", market_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::MarketExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 5 - system account
+    if !system_program::check_id(system_account.key) {
+        msg!("System program not match. Got {:?}", system_account.key,);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // market rules apply to every order of the pair, so only admin can register them
+    let config_account = next_account_info(account_info_iter)?; // 6 - config
+    check_admin_and_get_config(program_id, authority, config_account)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        authority,
+        market_account,
+        Market::LEN,
         &[
-            buyer_buy_token_wallet.clone(),
-            seller_buy_token_wallet.clone(),
-            buyer.clone(),
+            b"Market",
+            &token_mint_info.key.to_bytes(),
+            &price_mint_info.key.to_bytes(),
+            &[bump_seed],
         ],
-        &[],
     )?;
 
-    order.remains_to_fill -= sell_token_amount;
+    let market = Market {
+        authority: *authority.key,
+        token_mint: *token_mint_info.key,
+        price_mint: *price_mint_info.key,
+        token_decimals: token_mint.decimals,
+        price_decimals: price_mint.decimals,
+        tick_size,
+        lot_size,
+        min_order_size,
+    };
+
+    Market::pack(market, market_account.data.borrow_mut().deref_mut())
+}
+
+fn update_market<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (tick_size, lot_size, min_order_size) = unpack_market_rules(instruction_data)?;
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    let market_account = next_account_info(account_info_iter)?; // 2 - market account
+    let config_account = next_account_info(account_info_iter)?; // 3 - config
+    check_admin_and_get_config(program_id, admin, config_account)?;
+
+    if is_program_account_missing(market_account) || *market_account.owner != *program_id {
+        msg!("Market {:?} does not exist", market_account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let mut market = Market::unpack(&market_account.data.borrow())?;
+    let (expected_market, _) = get_market_address(program_id, &market.token_mint, &market.price_mint);
+    if expected_market != *market_account.key {
+        msg!(
+            "Market not match. Expected {:?}",
+            expected_market,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    market.tick_size = tick_size;
+    market.lot_size = lot_size;
+    market.min_order_size = min_order_size;
+
+    Market::pack(market, market_account.data.borrow_mut().deref_mut())
+}
+
+// Lists public order which is not in the order book of its pair: it did not fit into full book, or it
+// is a reverse order of a flip order refilled after creation. Anyone can list an order
+fn list_order<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?; // 1 - payer (for order book account if missing)
+    if !payer.is_signer {
+        msg!("Payer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    let (mut order, _) = check_and_get_order_by_address(program_id, order_account)?;
+    if order.is_private {
+        msg!("Private orders are not listed in order book");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let order_book_account = next_account_info(account_info_iter)?; // 3 - order book
+    let order_book = check_and_get_order_book(
+        program_id,
+        &order.token_mint,
+        &order.price_mint,
+        order_book_account,
+    )?;
+
+    let market_account = next_account_info(account_info_iter)?; // 4 - market
+    let market = check_and_get_market(program_id, &order.token_mint, &order.price_mint, market_account)?
+        .ok_or_else(|| {
+            msg!("Orders of pairs without market are not listed in order book");
+            ProgramError::UninitializedAccount
+        })?;
+
+    if order.remains_to_fill < market.min_listing_amount() {
+        msg!("Order should have at least {:?} tokens to fill to be listed", market.min_listing_amount());
+        return Err(ProgramError::Custom(P2PSwapError::OrderBelowMinimumSize as u32));
+    }
+
+    let seller_epoch_account = next_account_info(account_info_iter)?; // 5 - seller epoch
+    if order.epoch < get_seller_epoch(program_id, &order.seller, seller_epoch_account)? {
+        msg!("Order is cancelled by seller");
+        return Err(ProgramError::Custom(P2PSwapError::OrderEpochExpired as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 6 - system account
+    check_system_program(system_account)?;
+
+    let mut order_book = match order_book {
+        Some(order_book) => order_book,
+        None => create_order_book_account(
+            system_account,
+            program_id,
+            payer,
+            &order.token_mint,
+            &order.price_mint,
+            order_book_account,
+        )?,
+    };
+
+    if order.is_listed {
+        msg!("Order is already listed");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    order_book.insert(OrderBookEntry {
+        order: *order_account.key,
+        sell_amount: order.sell_amount,
+        buy_amount: order.buy_amount,
+        remains_to_fill: order.remains_to_fill,
+    }).map_err(|err| {
+        msg!("Order is not listed in order book: {:?}", err);
+        ProgramError::Custom(err as u32)
+    })?;
+
+    OrderBook::pack(order_book, order_book_account.data.borrow_mut().deref_mut())?;
+
+    order.is_listed = true;
+    SwapSPLOrder::pack(order, order_account.data.borrow_mut().deref_mut())
+}
+
+fn migrate_order<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?; // 1 - payer (tops up rent of the grown order)
+    if !payer.is_signer {
+        msg!("Payer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    if *order_account.owner != *program_id {
+        msg!("Order is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if order_account.data_len() != SwapSPLOrder::LEGACY_LEN {
+        msg!("Order is not in legacy layout");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let order = SwapSPLOrder::unpack_legacy(&order_account.data.borrow())?;
+    if !order.is_initialized() {
+        msg!("Order is not initialized");
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let (expected_order_account, _) = get_order_address(program_id, &order.seller, order.order_index);
+    if expected_order_account != *order_account.key {
+        msg!(
+            "Order not match. Expected: {:?}",
+            expected_order_account,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 3 - system account
+    check_system_program(system_account)?;
+
+    let minimum_balance = Rent::get()?.minimum_balance(SwapSPLOrder::LEN);
+    let lamports_to_transfer = minimum_balance.saturating_sub(order_account.lamports());
+    if lamports_to_transfer > 0 {
+        invoke_signed(
+            &system_instruction::transfer(payer.key, order_account.key, lamports_to_transfer),
+            &[
+                (*payer).clone(),
+                order_account.clone(),
+                system_account.clone(),
+            ],
+            &[],
+        )?;
+    }
+
+    msg!("Migrating order to current layout");
+    order_account.realloc(SwapSPLOrder::LEN, true)?;
     SwapSPLOrder::pack(order, order_account.data.borrow_mut().deref_mut())
 }
 
-fn process_instruction<'a>(
+fn init_config<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    if !admin.is_signer {
+        msg!("Admin must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let config_account = next_account_info(account_info_iter)?; // 2 - config
+    let (expected_config, bump_seed) = get_config_address(program_id);
+    if expected_config != *config_account.key {
+        msg!(
+            "Config not match. Expected {:?}",
+            expected_config,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(config_account) {
+        msg!("Config {:?} already exists", config_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::ConfigExists as u32));
+    }
+
+    // Only upgrade authority of the program is allowed to become the first admin
+    let program_data = next_account_info(account_info_iter)?; // 3 - program data
+    let (expected_program_data, _) = Pubkey::find_program_address(
+        &[program_id.as_ref()],
+        &bpf_loader_upgradeable::id(),
+    );
+    if expected_program_data != *program_data.key {
+        msg!(
+            "Program data not match. Expected {:?}",
+            expected_program_data,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let upgrade_authority = {
+        let program_data = program_data.data.borrow();
+        if program_data.len() < bpf_loader_upgradeable::UpgradeableLoaderState::size_of_programdata_metadata() {
+            msg!("Program data is too short");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // UpgradeableLoaderState::ProgramData - 4 bytes tag, 8 bytes slot, Option<Pubkey>
+        let program_data = array_ref![program_data, 0, 45];
+        let (tag, _, authority_tag, authority) = array_refs![program_data, 4, 8, 1, 32];
+        if u32::from_le_bytes(*tag) != 3 || authority_tag[0] != 1 {
+            msg!("Program is not upgradeable");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Pubkey::new_from_array(*authority)
+    };
+
+    if upgrade_authority != *admin.key {
+        msg!("Admin not match. Expected {:?}", upgrade_authority);
+        return Err(ProgramError::Custom(P2PSwapError::NotAdmin as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 4 - system account
+    if !system_program::check_id(system_account.key) {
+        msg!("System program not match. Got {:?}", system_account.key,);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    create_program_account(
+        system_account,
+        program_id,
+        admin,
+        config_account,
+        Config::LEN,
+        &[b"Config", &[bump_seed]],
+    )?;
+
+    let config = Config {
+        admin: *admin.key,
+        is_paused: false,
+        mint_list_mode: MintListMode::Open,
+    };
+
+    Config::pack(config, config_account.data.borrow_mut().deref_mut())
+}
+
+fn set_paused<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let is_paused = match instruction_data {
+        [0] => false,
+        [1] => true,
+        _ => {
+            msg!(
+                "Invalid data - expected 1 byte flag - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    let config_account = next_account_info(account_info_iter)?; // 2 - config
+    let mut config = check_admin_and_get_config(program_id, admin, config_account)?;
+
+    msg!("Setting paused flag to {:?}", is_paused);
+    config.is_paused = is_paused;
+    Config::pack(config, config_account.data.borrow_mut().deref_mut())
+}
+
+fn set_admin<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let new_admin = if instruction_data.len() == 32 {
+        Pubkey::new_from_array(*array_ref![instruction_data, 0, 32])
+    } else {
+        msg!(
+            "Invalid data - expected 32 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if new_admin == Pubkey::default() {
+        msg!("New admin should not be default pubkey");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    let config_account = next_account_info(account_info_iter)?; // 2 - config
+    let mut config = check_admin_and_get_config(program_id, admin, config_account)?;
+
+    msg!("Changing admin to {:?}", new_admin);
+    config.admin = new_admin;
+    Config::pack(config, config_account.data.borrow_mut().deref_mut())
+}
+
+fn set_mint_list_mode<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let mint_list_mode = match instruction_data {
+        [mode] => MintListMode::from_u8(*mode).ok_or_else(|| {
+            msg!("Unknown mint list mode {:?}", mode);
+            ProgramError::InvalidInstructionData
+        })?,
+        _ => {
+            msg!(
+                "Invalid data - expected 1 byte mode - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    let config_account = next_account_info(account_info_iter)?; // 2 - config
+    let mut config = check_admin_and_get_config(program_id, admin, config_account)?;
+
+    msg!("Setting mint list mode to {:?}", mint_list_mode);
+    config.mint_list_mode = mint_list_mode;
+    Config::pack(config, config_account.data.borrow_mut().deref_mut())
+}
+
+fn set_mint_status<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // 0 - removes mint from lists
+    let status = match instruction_data {
+        [0] => None,
+        [status] => Some(MintStatus::from_u8(*status).ok_or_else(|| {
+            msg!("Unknown mint status {:?}", status);
+            ProgramError::InvalidInstructionData
+        })?),
+        _ => {
+            msg!(
+                "Invalid data - expected 1 byte status - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    let config_account = next_account_info(account_info_iter)?; // 2 - config
+    check_admin_and_get_config(program_id, admin, config_account)?;
+
+    let mint = next_account_info(account_info_iter)?; // 3 - mint
+    let mint_list_entry_account = next_account_info(account_info_iter)?; // 4 - mint list entry
+    let (expected_entry, bump_seed) = get_mint_list_entry_address(program_id, mint.key);
+    if expected_entry != *mint_list_entry_account.key {
+        msg!(
+            "Mint list entry not match. Expected {:?}",
+            expected_entry,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 5 - system account
+    if !system_program::check_id(system_account.key) {
+        msg!("System program not match. Got {:?}", system_account.key,);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    match status {
+        Some(status) => {
+            if is_program_account_missing(mint_list_entry_account) {
+                create_program_account(
+                    system_account,
+                    program_id,
+                    admin,
+                    mint_list_entry_account,
+                    MintListEntry::LEN,
+                    &[b"MintListEntry", &mint.key.to_bytes(), &[bump_seed]],
+                )?;
+            }
+
+            msg!("Setting status of mint {:?} to {:?}", mint.key, status);
+            let entry = MintListEntry {
+                mint: *mint.key,
+                status,
+            };
+
+            MintListEntry::pack(entry, mint_list_entry_account.data.borrow_mut().deref_mut())
+        },
+        None => {
+            if is_program_account_missing(mint_list_entry_account) {
+                return Ok(());
+            }
+
+            msg!("Removing mint {:?} from lists", mint.key);
+            close_program_account(mint_list_entry_account, admin)
+        },
+    }
+}
+
+fn set_arbitration_config<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // empty data - removes default arbitrator
+    let arbitration = match instruction_data.len() {
+        0 => None,
+        34 => {
+            let instruction_data = array_ref![instruction_data, 0, 34];
+            let (arbitrator, fee_bps) = array_refs![instruction_data, 32, 2];
+            Some(ArbitrationConfig {
+                arbitrator: Pubkey::new_from_array(*arbitrator),
+                fee_bps: u16::from_le_bytes(*fee_bps),
+            })
+        },
+        _ => {
+            msg!(
+                "Invalid data - expected 0 or 34 bytes - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+
+    if let Some(arbitration) = &arbitration {
+        check_arbitrator(&arbitration.arbitrator, arbitration.fee_bps)?;
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let admin = next_account_info(account_info_iter)?; // 1 - admin
+    let config_account = next_account_info(account_info_iter)?; // 2 - config
+    check_admin_and_get_config(program_id, admin, config_account)?;
+
+    let arbitration_account = next_account_info(account_info_iter)?; // 3 - arbitration config
+    let (expected_arbitration, bump_seed) = get_arbitration_config_address(program_id);
+    if expected_arbitration != *arbitration_account.key {
+        msg!(
+            "Arbitration config not match. Expected {:?}",
+            expected_arbitration,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 4 - system account
+    check_system_program(system_account)?;
+
+    match arbitration {
+        Some(arbitration) => {
+            if is_program_account_missing(arbitration_account) {
+                create_program_account(
+                    system_account,
+                    program_id,
+                    admin,
+                    arbitration_account,
+                    ArbitrationConfig::LEN,
+                    &[b"ArbitrationConfig", &[bump_seed]],
+                )?;
+            }
+
+            msg!("Setting default arbitrator to {:?}", arbitration.arbitrator);
+            ArbitrationConfig::pack(arbitration, arbitration_account.data.borrow_mut().deref_mut())
+        },
+        None => {
+            if is_program_account_missing(arbitration_account) {
+                return Ok(());
+            }
+
+            msg!("Removing default arbitrator");
+            close_program_account(arbitration_account, admin)
+        },
+    }
+}
+
+fn set_order_status<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    status: OrderStatus,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    let (mut order, _) = check_and_get_order(program_id, seller, order_account)?;
+
+    // 3, 4 - operator and its delegate (if seller does not sign)
+    check_seller_or_operator(program_id, seller, account_info_iter, PERMISSION_PAUSE)?;
+
+    msg!("Setting order status to {:?}", status);
+    order.status = status;
+    SwapSPLOrder::pack(order, order_account.data.borrow_mut().deref_mut())
+}
+
+fn create_proposal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (sell_amount, buy_amount) = if instruction_data.len() == 16 {
+        let instruction_data = array_ref![instruction_data, 0, 16];
+        let (sell_amount, buy_amount) = array_refs![instruction_data, 8, 8];
+        (
+            u64::from_le_bytes(*sell_amount),
+            u64::from_le_bytes(*buy_amount),
+        )
+    } else {
+        msg!(
+            "Invalid data - expected 16 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if sell_amount == 0 || buy_amount == 0 {
+        msg!("Proposal amounts should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer = next_account_info(account_info_iter)?; // 1 - buyer
+    if !buyer.is_signer {
+        msg!("Buyer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let buyer_price_token_wallet = next_account_info(account_info_iter)?; // 2 - buyer price token wallet
+    let order_account = next_account_info(account_info_iter)?; // 3 - order
+    let (order, _) = check_and_get_order_by_address(program_id, order_account)?;
+
+    if order.remains_to_fill < sell_amount {
+        msg!("Order has not enough tokens");
+        return Err(ProgramError::Custom(P2PSwapError::NotEnoughTokensInOrder as u32));
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - buyer escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - buyer escrow wallet
+    check_escrow_wallet(program_id, buyer.key, &order.price_mint, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    let proposal_account = next_account_info(account_info_iter)?; // 7 - proposal
+    let (expected_proposal, bump_seed) = get_proposal_address(program_id, order_account.key, buyer.key);
+    if expected_proposal != *proposal_account.key {
+        msg!(
+            "Proposal not match. Expected {:?}",
+            expected_proposal,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(proposal_account) {
+        msg!("Proposal {:?} already exists", proposal_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::ProposalExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 8 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 9 - config
+    check_not_paused(program_id, config_account)?;
+
+    msg!("Escrowing buyer payment");
+    transfer_from_signer(buyer_price_token_wallet, escrow_wallet, buyer, buy_amount)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        buyer,
+        proposal_account,
+        Proposal::LEN,
+        &[
+            b"Proposal",
+            &order_account.key.to_bytes(),
+            &buyer.key.to_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let proposal = Proposal {
+        order: *order_account.key,
+        buyer: *buyer.key,
+        price_mint: order.price_mint,
+        sell_amount,
+        buy_amount,
+    };
+
+    Proposal::pack(proposal, proposal_account.data.borrow_mut().deref_mut())
+}
+
+fn check_and_get_proposal(
+    program_id: &Pubkey,
+    proposal_account: &AccountInfo,
+) -> Result<Proposal, ProgramError> {
+    let proposal = Proposal::unpack(&proposal_account.data.borrow())?;
+    let (expected_proposal, _) = get_proposal_address(program_id, &proposal.order, &proposal.buyer);
+    if expected_proposal != *proposal_account.key {
+        msg!(
+            "Proposal not match. Expected {:?}",
+            expected_proposal,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(proposal)
+}
+
+fn withdraw_proposal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer = next_account_info(account_info_iter)?; // 1 - buyer
+    if !buyer.is_signer {
+        msg!("Buyer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let proposal_account = next_account_info(account_info_iter)?; // 2 - proposal
+    let proposal = check_and_get_proposal(program_id, proposal_account)?;
+    if proposal.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", proposal.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 3 - buyer escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 4 - buyer escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        buyer.key,
+        &proposal.price_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let buyer_price_token_wallet = next_account_info(account_info_iter)?; // 5 - buyer price token wallet
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    msg!("Returning escrowed payment to buyer");
+    transfer_from_escrow(
+        escrow_wallet,
+        buyer_price_token_wallet,
+        escrow_authority,
+        buyer.key,
+        escrow_seed,
+        proposal.buy_amount,
+    )?;
+
+    close_program_account(proposal_account, buyer)
+}
+
+fn accept_proposal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    let (mut order, _) = check_and_get_order(program_id, seller, order_account)?;
+
+    let proposal_account = next_account_info(account_info_iter)?; // 3 - proposal
+    let proposal = check_and_get_proposal(program_id, proposal_account)?;
+    if proposal.order != *order_account.key {
+        msg!("Proposal is made for another order: {:?}", proposal.order);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if order.remains_to_fill < proposal.sell_amount {
+        msg!("Order has not enough tokens");
+        return Err(ProgramError::Custom(P2PSwapError::NotEnoughTokensInOrder as u32));
+    }
+
+    let buyer = next_account_info(account_info_iter)?; // 4 - buyer
+    if proposal.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", proposal.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let order_wallet_authority = next_account_info(account_info_iter)?; // 5 - order wallet authority
+    let order_wallet_account = next_account_info(account_info_iter)?; // 6 - order wallet
+    let (_, order_wallet_seed) = check_and_get_order_wallet(
+        program_id,
+        &order,
+        order_wallet_authority,
+        order_wallet_account,
+    )?;
+
+    let buyer_sell_token_wallet = next_account_info(account_info_iter)?; // 7 - buyer sell token wallet
+    check_associated_wallet(buyer.key, &order.token_mint, buyer_sell_token_wallet)?;
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 8 - buyer escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 9 - buyer escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        buyer.key,
+        &proposal.price_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let seller_price_token_wallet = next_account_info(account_info_iter)?; // 10 - seller price token (payout) wallet
+    if order.get_payout_wallet() != *seller_price_token_wallet.key {
+        msg!("Seller payout wallet not match. Expected: {:?}", order.get_payout_wallet());
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_program = next_account_info(account_info_iter)?; // 11 - token program
+    check_token_program(token_program)?;
+
+    let order_book_account = next_account_info(account_info_iter)?; // 12 - order book
+    let order_book = check_and_get_order_book(
+        program_id,
+        &order.token_mint,
+        &order.price_mint,
+        order_book_account,
+    )?;
+
+    let config_account = next_account_info(account_info_iter)?; // 13 - config
+    check_not_paused(program_id, config_account)?;
+
+    // accepted proposal fills the order, so the same order rules apply as to FillOrder
+    let market_account = next_account_info(account_info_iter)?; // 14 - market
+    let market = check_and_get_market(
+        program_id,
+        &order.token_mint,
+        &order.price_mint,
+        market_account,
+    )?;
+
+    let seller_epoch_account = next_account_info(account_info_iter)?; // 15 - seller epoch
+    let seller_epoch = get_seller_epoch(program_id, seller.key, seller_epoch_account)?;
+
+    let buyer_gate_wallet = next_account_info(account_info_iter)?; // 16 - buyer gate wallet (system program if not gated)
+    check_order_fillable(&order, market.as_ref(), proposal.sell_amount, seller_epoch, buyer.key, buyer_gate_wallet)?;
+
+    let payout_split_account = next_account_info(account_info_iter)?; // 17 - payout split
+    let payout_split = check_and_get_payout_split(program_id, order_account.key, payout_split_account)?;
+    // 18.. - payout split recipient wallets
+    let payout_transfers = get_payout_transfers(
+        payout_split,
+        seller_price_token_wallet,
+        account_info_iter,
+        proposal.buy_amount,
+    )?;
+
+    msg!("Transfering from order to buyer");
+    transfer_from_escrow(
+        order_wallet_account,
+        buyer_sell_token_wallet,
+        order_wallet_authority,
+        seller.key,
+        order_wallet_seed,
+        proposal.sell_amount,
+    )?;
+
+    msg!("Transfering escrowed payment to seller");
+    for (payout_wallet, amount) in payout_transfers {
+        transfer_from_escrow(
+            escrow_wallet,
+            payout_wallet,
+            escrow_authority,
+            buyer.key,
+            escrow_seed,
+            amount,
+        )?;
+    }
+
+    // optional seller and buyer reputations after payout split recipients
+    record_trade_in_reputations(program_id, seller.key, buyer.key, account_info_iter)?;
+
+    order.remains_to_fill -= proposal.sell_amount;
+    if let Some(mut order_book) = order_book {
+        order_book.update(order_account.key, order.remains_to_fill);
+        order.is_listed = order_book.contains(order_account.key);
+        OrderBook::pack(order_book, order_book_account.data.borrow_mut().deref_mut())?;
+    }
+
+    close_program_account(proposal_account, buyer)?;
+    SwapSPLOrder::pack(order, order_account.data.borrow_mut().deref_mut())
+}
+
+fn create_deal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (amount_a, amount_b, deadline, nonce) = if instruction_data.len() == 32 {
+        let instruction_data = array_ref![instruction_data, 0, 32];
+        let (amount_a, amount_b, deadline, nonce) = array_refs![instruction_data, 8, 8, 8, 8];
+        (
+            u64::from_le_bytes(*amount_a),
+            u64::from_le_bytes(*amount_b),
+            i64::from_le_bytes(*deadline),
+            u64::from_le_bytes(*nonce),
+        )
+    } else {
+        msg!(
+            "Invalid data - expected 32 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if amount_a == 0 || amount_b == 0 {
+        msg!("Deal amounts should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if deadline <= Clock::get()?.unix_timestamp {
+        msg!("Deadline {:?} is in the past", deadline);
+        return Err(ProgramError::Custom(P2PSwapError::DealExpired as u32));
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let party_a = next_account_info(account_info_iter)?; // 1 - party A
+    if !party_a.is_signer {
+        msg!("Party A must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let party_b = next_account_info(account_info_iter)?; // 2 - party B
+    if party_a.key == party_b.key {
+        msg!("Parties of the deal should be different");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let mint_a = next_account_info(account_info_iter)?; // 3 - mint A
+    spl_token::check_program_account(mint_a.owner)?;
+    let mint_b = next_account_info(account_info_iter)?; // 4 - mint B
+    spl_token::check_program_account(mint_b.owner)?;
+
+    let deal_account = next_account_info(account_info_iter)?; // 5 - deal
+    let (expected_deal, bump_seed) = get_deal_address(program_id, party_a.key, nonce);
+    if expected_deal != *deal_account.key {
+        msg!(
+            "Deal not match. Expected {:?}",
+            expected_deal,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(deal_account) {
+        msg!("Deal {:?} already exists", deal_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::DealExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 6 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 7 - config
+    check_not_paused(program_id, config_account)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        party_a,
+        deal_account,
+        Deal::LEN,
+        &[b"Deal", &party_a.key.to_bytes(), &nonce.to_le_bytes(), &[bump_seed]],
+    )?;
+
+    let deal = Deal {
+        party_a: *party_a.key,
+        party_b: *party_b.key,
+        mint_a: *mint_a.key,
+        mint_b: *mint_b.key,
+        amount_a,
+        amount_b,
+        deadline,
+        nonce,
+        a_deposited: false,
+        b_deposited: false,
+    };
+
+    Deal::pack(deal, deal_account.data.borrow_mut().deref_mut())
+}
+
+fn check_and_get_deal(
+    program_id: &Pubkey,
+    deal_account: &AccountInfo,
+) -> Result<Deal, ProgramError> {
+    let deal = Deal::unpack(&deal_account.data.borrow())?;
+    let (expected_deal, _) = get_deal_address(program_id, &deal.party_a, deal.nonce);
+    if expected_deal != *deal_account.key {
+        msg!(
+            "Deal not match. Expected {:?}",
+            expected_deal,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(deal)
+}
+
+fn deposit_deal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let depositor = next_account_info(account_info_iter)?; // 1 - depositor
+    if !depositor.is_signer {
+        msg!("Depositor must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let deal_account = next_account_info(account_info_iter)?; // 2 - deal
+    let mut deal = check_and_get_deal(program_id, deal_account)?;
+
+    if Clock::get()?.unix_timestamp >= deal.deadline {
+        msg!("Deal is expired");
+        return Err(ProgramError::Custom(P2PSwapError::DealExpired as u32));
+    }
+
+    let (mint, amount, already_deposited) = if *depositor.key == deal.party_a {
+        (deal.mint_a, deal.amount_a, deal.a_deposited)
+    } else if *depositor.key == deal.party_b {
+        (deal.mint_b, deal.amount_b, deal.b_deposited)
+    } else {
+        msg!("Depositor is not a party of the deal");
+        return Err(ProgramError::InvalidAccountData);
+    };
+
+    if already_deposited {
+        msg!("Deposit is already made");
+        return Err(ProgramError::Custom(P2PSwapError::AlreadyDeposited as u32));
+    }
+
+    let depositor_wallet = next_account_info(account_info_iter)?; // 3 - depositor token wallet
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - depositor escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - depositor escrow wallet
+    check_escrow_wallet(program_id, depositor.key, &mint, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    transfer_from_signer(depositor_wallet, escrow_wallet, depositor, amount)?;
+
+    if *depositor.key == deal.party_a {
+        deal.a_deposited = true;
+    } else {
+        deal.b_deposited = true;
+    }
+
+    Deal::pack(deal, deal_account.data.borrow_mut().deref_mut())
+}
+
+fn settle_deal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let party_a = next_account_info(account_info_iter)?; // 1 - party A
+    let party_b = next_account_info(account_info_iter)?; // 2 - party B
+    let deal_account = next_account_info(account_info_iter)?; // 3 - deal
+    let deal = check_and_get_deal(program_id, deal_account)?;
+    if deal.party_a != *party_a.key || deal.party_b != *party_b.key {
+        msg!("Parties not match. Expected {:?} and {:?}", deal.party_a, deal.party_b);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !deal.a_deposited || !deal.b_deposited {
+        msg!("Deal is not funded by both parties");
+        return Err(ProgramError::Custom(P2PSwapError::DealNotFunded as u32));
+    }
+
+    let escrow_authority_a = next_account_info(account_info_iter)?; // 4 - party A escrow authority
+    let escrow_wallet_a = next_account_info(account_info_iter)?; // 5 - party A escrow wallet
+    let escrow_seed_a = check_escrow_wallet(
+        program_id,
+        party_a.key,
+        &deal.mint_a,
+        escrow_authority_a,
+        escrow_wallet_a,
+    )?;
+
+    let escrow_authority_b = next_account_info(account_info_iter)?; // 6 - party B escrow authority
+    let escrow_wallet_b = next_account_info(account_info_iter)?; // 7 - party B escrow wallet
+    let escrow_seed_b = check_escrow_wallet(
+        program_id,
+        party_b.key,
+        &deal.mint_b,
+        escrow_authority_b,
+        escrow_wallet_b,
+    )?;
+
+    let party_a_wallet = next_account_info(account_info_iter)?; // 8 - party A wallet for mint B
+    check_associated_wallet(party_a.key, &deal.mint_b, party_a_wallet)?;
+
+    let party_b_wallet = next_account_info(account_info_iter)?; // 9 - party B wallet for mint A
+    check_associated_wallet(party_b.key, &deal.mint_a, party_b_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 10 - token program
+    check_token_program(token_program)?;
+
+    msg!("Transfering from party A to party B");
+    transfer_from_escrow(
+        escrow_wallet_a,
+        party_b_wallet,
+        escrow_authority_a,
+        party_a.key,
+        escrow_seed_a,
+        deal.amount_a,
+    )?;
+
+    msg!("Transfering from party B to party A");
+    transfer_from_escrow(
+        escrow_wallet_b,
+        party_a_wallet,
+        escrow_authority_b,
+        party_b.key,
+        escrow_seed_b,
+        deal.amount_b,
+    )?;
+
+    // 11, 12 - optional party A and party B reputations
+    record_trade_in_reputations(program_id, party_a.key, party_b.key, account_info_iter)?;
+
+    close_program_account(deal_account, party_a)
+}
+
+fn reclaim_deal<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let party = next_account_info(account_info_iter)?; // 1 - party
+    if !party.is_signer {
+        msg!("Party must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let deal_account = next_account_info(account_info_iter)?; // 2 - deal
+    let mut deal = check_and_get_deal(program_id, deal_account)?;
+
+    if Clock::get()?.unix_timestamp < deal.deadline {
+        msg!("Deal is not expired yet");
+        return Err(ProgramError::Custom(P2PSwapError::DealNotExpired as u32));
+    }
+
+    if deal.a_deposited && deal.b_deposited {
+        msg!("Deal is funded by both parties and can only be settled");
+        return Err(ProgramError::Custom(P2PSwapError::DealFunded as u32));
+    }
+
+    let (mint, amount, deposited) = if *party.key == deal.party_a {
+        (deal.mint_a, deal.amount_a, deal.a_deposited)
+    } else if *party.key == deal.party_b {
+        (deal.mint_b, deal.amount_b, deal.b_deposited)
+    } else {
+        msg!("Signer is not a party of the deal");
+        return Err(ProgramError::InvalidAccountData);
+    };
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 3 - party escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 4 - party escrow wallet
+    let escrow_seed = check_escrow_wallet(program_id, party.key, &mint, escrow_authority, escrow_wallet)?;
+
+    let party_wallet = next_account_info(account_info_iter)?; // 5 - party token wallet
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    let party_a = next_account_info(account_info_iter)?; // 7 - party A (receives rent of the deal)
+    if deal.party_a != *party_a.key {
+        msg!("Party A not match. Expected {:?}", deal.party_a);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if deposited {
+        msg!("Returning deposit");
+        transfer_from_escrow(
+            escrow_wallet,
+            party_wallet,
+            escrow_authority,
+            party.key,
+            escrow_seed,
+            amount,
+        )?;
+
+        if *party.key == deal.party_a {
+            deal.a_deposited = false;
+        } else {
+            deal.b_deposited = false;
+        }
+    }
+
+    if !deal.a_deposited && !deal.b_deposited {
+        return close_program_account(deal_account, party_a);
+    }
+
+    Deal::pack(deal, deal_account.data.borrow_mut().deref_mut())
+}
+
+fn set_payout_split<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if instruction_data.len() % 2 != 0 {
+        msg!(
+            "Invalid data - expected 2 bytes per recipient - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    let (order, _) = check_and_get_order(program_id, seller, order_account)?;
+
+    let payout_split_account = next_account_info(account_info_iter)?; // 3 - payout split
+    let payout_split = check_and_get_payout_split(program_id, order_account.key, payout_split_account)?;
+
+    let system_account = next_account_info(account_info_iter)?; // 4 - system account
+    check_system_program(system_account)?;
+
+    let flip_account = next_account_info(account_info_iter)?; // 5 - flip
+    let (expected_flip, _) = get_flip_address(program_id, order_account.key);
+    if expected_flip != *flip_account.key {
+        msg!(
+            "Flip not match. Expected {:?}",
+            expected_flip,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !instruction_data.is_empty() && !is_program_account_missing(flip_account) {
+        msg!("Flip order can't have payout split");
+        return Err(ProgramError::Custom(P2PSwapError::FlipPayoutConflict as u32));
+    }
+
+    let mut recipients = Vec::new();
+    for bps in instruction_data.chunks_exact(2) {
+        let recipient_wallet = next_account_info(account_info_iter)?; // 6.. - recipient wallets
+        spl_token::check_program_account(recipient_wallet.owner)?;
+        let recipient_wallet_data = SPLAccount::unpack(&recipient_wallet.data.borrow())?;
+        if recipient_wallet_data.mint != order.price_mint {
+            msg!("Recipient wallet mint not match. Expected {:?}", order.price_mint);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        recipients.push(PayoutRecipient {
+            wallet: *recipient_wallet.key,
+            bps: u16::from_le_bytes([bps[0], bps[1]]),
+        });
+    }
+
+    PayoutSplit::check_recipients(&recipients).map_err(|err| {
+        msg!("Invalid payout split: {:?}", err);
+        ProgramError::Custom(err as u32)
+    })?;
+
+    if recipients.is_empty() {
+        if payout_split.is_some() {
+            msg!("Removing payout split");
+            close_program_account(payout_split_account, seller)?;
+        }
+
+        return Ok(());
+    }
+
+    if payout_split.is_none() {
+        let (_, bump_seed) = get_payout_split_address(program_id, order_account.key);
+        create_program_account(
+            system_account,
+            program_id,
+            seller,
+            payout_split_account,
+            PayoutSplit::LEN,
+            &[b"PayoutSplit", &order_account.key.to_bytes(), &[bump_seed]],
+        )?;
+    }
+
+    let payout_split = PayoutSplit {
+        order: *order_account.key,
+        recipients,
+    };
+
+    PayoutSplit::pack(payout_split, payout_split_account.data.borrow_mut().deref_mut())
+}
+
+fn set_flip<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // empty data removes flip
+    let spread_bps = match instruction_data.len() {
+        0 => None,
+        2 => Some(u16::from_le_bytes(*array_ref![instruction_data, 0, 2])),
+        _ => {
+            msg!(
+                "Invalid data - expected 0 or 2 bytes - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        },
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    let order_account = next_account_info(account_info_iter)?; // 2 - order
+    let (order, _) = check_and_get_order(program_id, seller, order_account)?;
+
+    let reverse_order_account = next_account_info(account_info_iter)?; // 3 - reverse order
+    let flip_account = next_account_info(account_info_iter)?; // 4 - flip
+    let (expected_flip, bump_seed) = get_flip_address(program_id, order_account.key);
+    if expected_flip != *flip_account.key {
+        msg!(
+            "Flip not match. Expected {:?}",
+            expected_flip,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 5 - system account
+    check_system_program(system_account)?;
+
+    let payout_split_account = next_account_info(account_info_iter)?; // 6 - payout split
+    let payout_split = check_and_get_payout_split(program_id, order_account.key, payout_split_account)?;
+
+    // 7, 8 - operator and its delegate (if seller does not sign). Operator pays for the flip account
+    let payer = check_seller_or_operator(program_id, seller, account_info_iter, PERMISSION_AMEND)?;
+
+    let spread_bps = match spread_bps {
+        Some(spread_bps) => spread_bps,
+        None => {
+            if !is_program_account_missing(flip_account) {
+                msg!("Removing flip");
+                close_program_account(flip_account, seller)?;
+            }
+
+            return Ok(());
+        },
+    };
+
+    if spread_bps as u64 >= BPS_DENOMINATOR {
+        msg!("Spread should be less than {:?} bps", BPS_DENOMINATOR);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // flip reinvests the whole payment, so it can't be combined with payout wallet or split of the order
+    if order.payout_wallet != Pubkey::default() || payout_split.is_some() {
+        msg!("Order with payout wallet or payout split can't be a flip order");
+        return Err(ProgramError::Custom(P2PSwapError::FlipPayoutConflict as u32));
+    }
+
+    let (reverse_order, _) = check_and_get_order(program_id, seller, reverse_order_account)?;
+    if reverse_order.token_mint != order.price_mint || reverse_order.price_mint != order.token_mint {
+        msg!(
+            "Reverse order should sell {:?} for {:?}",
+            order.price_mint,
+            order.token_mint,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(flip_account) {
+        create_program_account(
+            system_account,
+            program_id,
+            payer,
+            flip_account,
+            Flip::LEN,
+            &[b"Flip", &order_account.key.to_bytes(), &[bump_seed]],
+        )?;
+    }
+
+    let flip = Flip {
+        order: *order_account.key,
+        reverse_order: *reverse_order_account.key,
+        spread_bps,
+    };
+
+    Flip::pack(flip, flip_account.data.borrow_mut().deref_mut())
+}
+
+fn create_rfq<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (amount, deadline, nonce) = if instruction_data.len() == 24 {
+        let instruction_data = array_ref![instruction_data, 0, 24];
+        let (amount, deadline, nonce) = array_refs![instruction_data, 8, 8, 8];
+        (
+            u64::from_le_bytes(*amount),
+            i64::from_le_bytes(*deadline),
+            u64::from_le_bytes(*nonce),
+        )
+    } else {
+        msg!(
+            "Invalid data - expected 24 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if amount == 0 {
+        msg!("RFQ amount should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if deadline <= Clock::get()?.unix_timestamp {
+        msg!("Deadline {:?} is in the past", deadline);
+        return Err(ProgramError::Custom(P2PSwapError::RfqExpired as u32));
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer = next_account_info(account_info_iter)?; // 1 - buyer
+    if !buyer.is_signer {
+        msg!("Buyer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let token_mint = next_account_info(account_info_iter)?; // 2 - token mint
+    spl_token::check_program_account(token_mint.owner)?;
+    let price_mint = next_account_info(account_info_iter)?; // 3 - price mint
+    spl_token::check_program_account(price_mint.owner)?;
+
+    let rfq_account = next_account_info(account_info_iter)?; // 4 - rfq
+    let (expected_rfq, bump_seed) = get_rfq_address(program_id, buyer.key, nonce);
+    if expected_rfq != *rfq_account.key {
+        msg!(
+            "RFQ not match. Expected {:?}",
+            expected_rfq,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(rfq_account) {
+        msg!("RFQ {:?} already exists", rfq_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::RfqExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 5 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 6 - config
+    check_not_paused(program_id, config_account)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        buyer,
+        rfq_account,
+        Rfq::LEN,
+        &[b"Rfq", &buyer.key.to_bytes(), &nonce.to_le_bytes(), &[bump_seed]],
+    )?;
+
+    let rfq = Rfq {
+        buyer: *buyer.key,
+        token_mint: *token_mint.key,
+        price_mint: *price_mint.key,
+        amount,
+        deadline,
+        nonce,
+        accepted_quote: Pubkey::default(),
+    };
+
+    Rfq::pack(rfq, rfq_account.data.borrow_mut().deref_mut())
+}
+
+fn check_and_get_rfq(
+    program_id: &Pubkey,
+    rfq_account: &AccountInfo,
+) -> Result<Rfq, ProgramError> {
+    let rfq = Rfq::unpack(&rfq_account.data.borrow())?;
+    let (expected_rfq, _) = get_rfq_address(program_id, &rfq.buyer, rfq.nonce);
+    if expected_rfq != *rfq_account.key {
+        msg!(
+            "RFQ not match. Expected {:?}",
+            expected_rfq,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(rfq)
+}
+
+// Quotes can be made and accepted only while RFQ is open
+fn check_rfq_open(rfq: &Rfq) -> ProgramResult {
+    if rfq.accepted_quote != Pubkey::default() {
+        msg!("RFQ is already settled with quote {:?}", rfq.accepted_quote);
+        return Err(ProgramError::Custom(P2PSwapError::RfqSettled as u32));
+    }
+
+    if Clock::get()?.unix_timestamp >= rfq.deadline {
+        msg!("RFQ is expired");
+        return Err(ProgramError::Custom(P2PSwapError::RfqExpired as u32));
+    }
+
+    Ok(())
+}
+
+fn check_and_get_quote(
+    program_id: &Pubkey,
+    quote_account: &AccountInfo,
+) -> Result<Quote, ProgramError> {
+    let quote = Quote::unpack(&quote_account.data.borrow())?;
+    let (expected_quote, _) = get_quote_address(program_id, &quote.rfq, &quote.seller);
+    if expected_quote != *quote_account.key {
+        msg!(
+            "Quote not match. Expected {:?}",
+            expected_quote,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(quote)
+}
+
+fn create_quote<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let price_amount = if instruction_data.len() == 8 {
+        let price_amount = array_ref![instruction_data, 0, 8];
+        u64::from_le_bytes(*price_amount)
+    } else {
+        msg!(
+            "Invalid data - expected 8 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if price_amount == 0 {
+        msg!("Quote price should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let seller_token_wallet = next_account_info(account_info_iter)?; // 2 - seller token wallet
+    let rfq_account = next_account_info(account_info_iter)?; // 3 - rfq
+    let rfq = check_and_get_rfq(program_id, rfq_account)?;
+    check_rfq_open(&rfq)?;
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - seller escrow wallet
+    check_escrow_wallet(program_id, seller.key, &rfq.token_mint, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    let quote_account = next_account_info(account_info_iter)?; // 7 - quote
+    let (expected_quote, bump_seed) = get_quote_address(program_id, rfq_account.key, seller.key);
+    if expected_quote != *quote_account.key {
+        msg!(
+            "Quote not match. Expected {:?}",
+            expected_quote,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(quote_account) {
+        msg!("Quote {:?} already exists", quote_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::QuoteExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 8 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 9 - config
+    check_not_paused(program_id, config_account)?;
+
+    msg!("Escrowing seller tokens");
+    transfer_from_signer(seller_token_wallet, escrow_wallet, seller, rfq.amount)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        seller,
+        quote_account,
+        Quote::LEN,
+        &[
+            b"Quote",
+            &rfq_account.key.to_bytes(),
+            &seller.key.to_bytes(),
+            &[bump_seed],
+        ],
+    )?;
+
+    let quote = Quote {
+        rfq: *rfq_account.key,
+        seller: *seller.key,
+        token_mint: rfq.token_mint,
+        price_mint: rfq.price_mint,
+        amount: rfq.amount,
+        price_amount,
+        rfq_deadline: rfq.deadline,
+    };
+
+    Quote::pack(quote, quote_account.data.borrow_mut().deref_mut())
+}
+
+fn accept_quote<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer = next_account_info(account_info_iter)?; // 1 - buyer
+    if !buyer.is_signer {
+        msg!("Buyer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let rfq_account = next_account_info(account_info_iter)?; // 2 - rfq
+    let mut rfq = check_and_get_rfq(program_id, rfq_account)?;
+    if rfq.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", rfq.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    check_rfq_open(&rfq)?;
+
+    let quote_account = next_account_info(account_info_iter)?; // 3 - quote
+    let quote = check_and_get_quote(program_id, quote_account)?;
+    if quote.rfq != *rfq_account.key
+        || quote.token_mint != rfq.token_mint
+        || quote.price_mint != rfq.price_mint
+        || quote.amount != rfq.amount
+        || quote.rfq_deadline != rfq.deadline {
+        msg!("Quote is made for another RFQ: {:?}", quote.rfq);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let seller = next_account_info(account_info_iter)?; // 4 - seller
+    if quote.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", quote.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 5 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 6 - seller escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &quote.token_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let buyer_token_wallet = next_account_info(account_info_iter)?; // 7 - buyer token wallet
+    check_associated_wallet(buyer.key, &quote.token_mint, buyer_token_wallet)?;
+
+    let buyer_price_token_wallet = next_account_info(account_info_iter)?; // 8 - buyer price token wallet
+
+    let seller_price_token_wallet = next_account_info(account_info_iter)?; // 9 - seller price token wallet
+    check_associated_wallet(seller.key, &quote.price_mint, seller_price_token_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 10 - token program
+    check_token_program(token_program)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 11 - config
+    check_not_paused(program_id, config_account)?;
+
+    msg!("Transfering escrowed tokens to buyer");
+    transfer_from_escrow(
+        escrow_wallet,
+        buyer_token_wallet,
+        escrow_authority,
+        seller.key,
+        escrow_seed,
+        quote.amount,
+    )?;
+
+    msg!("Transfering payment to seller");
+    transfer_from_signer(buyer_price_token_wallet, seller_price_token_wallet, buyer, quote.price_amount)?;
+
+    // 12, 13 - optional seller and buyer reputations
+    record_trade_in_reputations(program_id, seller.key, buyer.key, account_info_iter)?;
+
+    rfq.accepted_quote = *quote_account.key;
+    Rfq::pack(rfq, rfq_account.data.borrow_mut().deref_mut())?;
+
+    close_program_account(quote_account, seller)
+}
+
+fn reclaim_quote<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let quote_account = next_account_info(account_info_iter)?; // 2 - quote
+    let quote = check_and_get_quote(program_id, quote_account)?;
+    if quote.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", quote.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let rfq_account = next_account_info(account_info_iter)?; // 3 - rfq
+    if quote.rfq != *rfq_account.key {
+        msg!("RFQ not match. Expected: {:?}", quote.rfq);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // quote is locked while RFQ it was made for is open. Closed RFQ is missing, RFQ recreated at the same
+    // address has another deadline, anyway quote is never locked after the deadline it was made for
+    if !is_program_account_missing(rfq_account) && Clock::get()?.unix_timestamp < quote.rfq_deadline {
+        let rfq = check_and_get_rfq(program_id, rfq_account)?;
+        if rfq.accepted_quote == Pubkey::default() && rfq.deadline == quote.rfq_deadline {
+            msg!("RFQ is still open");
+            return Err(ProgramError::Custom(P2PSwapError::RfqActive as u32));
+        }
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - seller escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &quote.token_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let seller_token_wallet = next_account_info(account_info_iter)?; // 6 - seller token wallet
+    let token_program = next_account_info(account_info_iter)?; // 7 - token program
+    check_token_program(token_program)?;
+
+    msg!("Returning escrowed tokens");
+    transfer_from_escrow(
+        escrow_wallet,
+        seller_token_wallet,
+        escrow_authority,
+        seller.key,
+        escrow_seed,
+        quote.amount,
+    )?;
+
+    close_program_account(quote_account, seller)
+}
+
+fn close_rfq<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer = next_account_info(account_info_iter)?; // 1 - buyer
+    if !buyer.is_signer {
+        msg!("Buyer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let rfq_account = next_account_info(account_info_iter)?; // 2 - rfq
+    let rfq = check_and_get_rfq(program_id, rfq_account)?;
+    if rfq.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", rfq.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // closing open RFQ cancels it - all quotes become reclaimable
+    close_program_account(rfq_account, buyer)
+}
+
+fn create_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (amount, min_bid, min_increment, end_slot, extension_slots, nonce) = if instruction_data.len() == 48 {
+        let instruction_data = array_ref![instruction_data, 0, 48];
+        let (amount, min_bid, min_increment, end_slot, extension_slots, nonce) =
+            array_refs![instruction_data, 8, 8, 8, 8, 8, 8];
+        (
+            u64::from_le_bytes(*amount),
+            u64::from_le_bytes(*min_bid),
+            u64::from_le_bytes(*min_increment),
+            u64::from_le_bytes(*end_slot),
+            u64::from_le_bytes(*extension_slots),
+            u64::from_le_bytes(*nonce),
+        )
+    } else {
+        msg!(
+            "Invalid data - expected 48 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if amount == 0 || min_bid == 0 || min_increment == 0 {
+        msg!("Auction amount, minimum bid and increment should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if end_slot <= Clock::get()?.slot {
+        msg!("End slot {:?} is in the past", end_slot);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionEnded as u32));
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let seller_token_wallet = next_account_info(account_info_iter)?; // 2 - seller token wallet
+    let token_mint = next_account_info(account_info_iter)?; // 3 - token mint
+    spl_token::check_program_account(token_mint.owner)?;
+    let price_mint = next_account_info(account_info_iter)?; // 4 - price mint
+    spl_token::check_program_account(price_mint.owner)?;
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 5 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 6 - seller escrow wallet
+    check_escrow_wallet(program_id, seller.key, token_mint.key, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 7 - token program
+    check_token_program(token_program)?;
+
+    let auction_account = next_account_info(account_info_iter)?; // 8 - auction
+    let (expected_auction, bump_seed) = get_auction_address(program_id, seller.key, nonce);
+    if expected_auction != *auction_account.key {
+        msg!(
+            "Auction not match. Expected {:?}",
+            expected_auction,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(auction_account) {
+        msg!("Auction {:?} already exists", auction_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 9 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 10 - config
+    check_not_paused(program_id, config_account)?;
+
+    msg!("Escrowing seller tokens");
+    transfer_from_signer(seller_token_wallet, escrow_wallet, seller, amount)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        seller,
+        auction_account,
+        Auction::LEN,
+        &[b"Auction", &seller.key.to_bytes(), &nonce.to_le_bytes(), &[bump_seed]],
+    )?;
+
+    let auction = Auction {
+        seller: *seller.key,
+        token_mint: *token_mint.key,
+        price_mint: *price_mint.key,
+        amount,
+        min_bid,
+        min_increment,
+        end_slot,
+        extension_slots,
+        nonce,
+        highest_bidder: Pubkey::default(),
+        highest_bid: 0,
+    };
+
+    Auction::pack(auction, auction_account.data.borrow_mut().deref_mut())
+}
+
+fn check_and_get_auction(
+    program_id: &Pubkey,
+    auction_account: &AccountInfo,
+) -> Result<Auction, ProgramError> {
+    let auction = Auction::unpack(&auction_account.data.borrow())?;
+    let (expected_auction, _) = get_auction_address(program_id, &auction.seller, auction.nonce);
+    if expected_auction != *auction_account.key {
+        msg!(
+            "Auction not match. Expected {:?}",
+            expected_auction,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(auction)
+}
+
+fn place_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let bid = if instruction_data.len() == 8 {
+        let bid = array_ref![instruction_data, 0, 8];
+        u64::from_le_bytes(*bid)
+    } else {
+        msg!(
+            "Invalid data - expected 8 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bidder = next_account_info(account_info_iter)?; // 1 - bidder
+    if !bidder.is_signer {
+        msg!("Bidder must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let auction_account = next_account_info(account_info_iter)?; // 2 - auction
+    let mut auction = check_and_get_auction(program_id, auction_account)?;
+
+    let slot = Clock::get()?.slot;
+    if slot >= auction.end_slot {
+        msg!("Auction ended at slot {:?}", auction.end_slot);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionEnded as u32));
+    }
+
+    let min_bid = auction.next_min_bid()
+        .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+    if bid < min_bid {
+        msg!("Bid should be at least {:?}", min_bid);
+        return Err(ProgramError::Custom(P2PSwapError::BidTooLow as u32));
+    }
+
+    let bidder_price_token_wallet = next_account_info(account_info_iter)?; // 3 - bidder price token wallet
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - bidder escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - bidder escrow wallet
+    check_escrow_wallet(program_id, bidder.key, &auction.price_mint, escrow_authority, escrow_wallet)?;
+
+    // 6..8 - previous bid refund accounts (ignored when there are no bids yet)
+    let previous_escrow_authority = next_account_info(account_info_iter)?; // 6 - previous bidder escrow authority
+    let previous_escrow_wallet = next_account_info(account_info_iter)?; // 7 - previous bidder escrow wallet
+    let previous_bidder_wallet = next_account_info(account_info_iter)?; // 8 - previous bidder price token wallet
+
+    let token_program = next_account_info(account_info_iter)?; // 9 - token program
+    check_token_program(token_program)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 10 - config
+    check_not_paused(program_id, config_account)?;
+
+    // previous bidder can close its wallet to block outbidding, so bidder recreates it if needed
+    let previous_bidder = next_account_info(account_info_iter)?; // 11 - previous bidder (ignored when there are no bids yet)
+    let price_mint = next_account_info(account_info_iter)?; // 12 - price mint
+    if auction.price_mint != *price_mint.key {
+        msg!("Price mint not match. Expected: {:?}", auction.price_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 13 - system program
+    check_system_program(system_account)?;
+
+    let associated_token_program = next_account_info(account_info_iter)?; // 14 - associated token program
+    check_associated_token_program(associated_token_program)?;
+
+    msg!("Escrowing bid");
+    transfer_from_signer(bidder_price_token_wallet, escrow_wallet, bidder, bid)?;
+
+    if auction.highest_bidder != Pubkey::default() {
+        let previous_escrow_seed = check_escrow_wallet(
+            program_id,
+            &auction.highest_bidder,
+            &auction.price_mint,
+            previous_escrow_authority,
+            previous_escrow_wallet,
+        )?;
+
+        if auction.highest_bidder != *previous_bidder.key {
+            msg!("Previous bidder not match. Expected: {:?}", auction.highest_bidder);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        check_associated_wallet(&auction.highest_bidder, &auction.price_mint, previous_bidder_wallet)?;
+        create_associated_wallet_if_missing(
+            bidder,
+            previous_bidder,
+            price_mint,
+            previous_bidder_wallet,
+            system_account,
+            token_program,
+            associated_token_program,
+        )?;
+
+        msg!("Refunding previous bid");
+        transfer_from_escrow(
+            previous_escrow_wallet,
+            previous_bidder_wallet,
+            previous_escrow_authority,
+            &auction.highest_bidder,
+            previous_escrow_seed,
+            auction.highest_bid,
+        )?;
+    }
+
+    auction.end_slot = auction.extended_end_slot(slot);
+    auction.highest_bidder = *bidder.key;
+    auction.highest_bid = bid;
+
+    Auction::pack(auction, auction_account.data.borrow_mut().deref_mut())
+}
+
+fn settle_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    let auction_account = next_account_info(account_info_iter)?; // 2 - auction
+    let auction = check_and_get_auction(program_id, auction_account)?;
+    if auction.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", auction.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if Clock::get()?.slot < auction.end_slot {
+        msg!("Auction ends at slot {:?}", auction.end_slot);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionNotEnded as u32));
+    }
+
+    let seller_escrow_authority = next_account_info(account_info_iter)?; // 3 - seller escrow authority
+    let seller_escrow_wallet = next_account_info(account_info_iter)?; // 4 - seller escrow wallet
+    let seller_escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &auction.token_mint,
+        seller_escrow_authority,
+        seller_escrow_wallet,
+    )?;
+
+    // 5..6 - winner accounts (ignored when there are no bids)
+    let winner_escrow_authority = next_account_info(account_info_iter)?; // 5 - winner escrow authority
+    let winner_escrow_wallet = next_account_info(account_info_iter)?; // 6 - winner escrow wallet
+
+    // winner wallet is seller's token wallet when there are no bids
+    let winner = if auction.highest_bidder == Pubkey::default() {
+        auction.seller
+    } else {
+        auction.highest_bidder
+    };
+    let winner_token_wallet = next_account_info(account_info_iter)?; // 7 - winner token wallet
+    check_associated_wallet(&winner, &auction.token_mint, winner_token_wallet)?;
+
+    let seller_price_token_wallet = next_account_info(account_info_iter)?; // 8 - seller price token wallet
+    check_associated_wallet(seller.key, &auction.price_mint, seller_price_token_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 9 - token program
+    check_token_program(token_program)?;
+
+    // winner or seller can close its wallet to block settlement, so caller recreates missing wallets
+    let payer = next_account_info(account_info_iter)?; // 10 - payer for missing wallets
+    if !payer.is_signer {
+        msg!("Payer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let winner_account = next_account_info(account_info_iter)?; // 11 - winner (seller when there are no bids)
+    if winner != *winner_account.key {
+        msg!("Winner not match. Expected: {:?}", winner);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_mint = next_account_info(account_info_iter)?; // 12 - token mint
+    if auction.token_mint != *token_mint.key {
+        msg!("Token mint not match. Expected: {:?}", auction.token_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let price_mint = next_account_info(account_info_iter)?; // 13 - price mint
+    if auction.price_mint != *price_mint.key {
+        msg!("Price mint not match. Expected: {:?}", auction.price_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 14 - system program
+    check_system_program(system_account)?;
+
+    let associated_token_program = next_account_info(account_info_iter)?; // 15 - associated token program
+    check_associated_token_program(associated_token_program)?;
+
+    create_associated_wallet_if_missing(
+        payer,
+        winner_account,
+        token_mint,
+        winner_token_wallet,
+        system_account,
+        token_program,
+        associated_token_program,
+    )?;
+
+    if auction.highest_bidder != Pubkey::default() {
+        create_associated_wallet_if_missing(
+            payer,
+            seller,
+            price_mint,
+            seller_price_token_wallet,
+            system_account,
+            token_program,
+            associated_token_program,
+        )?;
+
+        let winner_escrow_seed = check_escrow_wallet(
+            program_id,
+            &auction.highest_bidder,
+            &auction.price_mint,
+            winner_escrow_authority,
+            winner_escrow_wallet,
+        )?;
+
+        msg!("Transfering winning bid to seller");
+        transfer_from_escrow(
+            winner_escrow_wallet,
+            seller_price_token_wallet,
+            winner_escrow_authority,
+            &auction.highest_bidder,
+            winner_escrow_seed,
+            auction.highest_bid,
+        )?;
+    }
+
+    msg!("Transfering escrowed tokens to {:?}", winner);
+    transfer_from_escrow(
+        seller_escrow_wallet,
+        winner_token_wallet,
+        seller_escrow_authority,
+        seller.key,
+        seller_escrow_seed,
+        auction.amount,
+    )?;
+
+    // 16, 17 - optional seller and winner reputations (nothing is recorded when there are no bids)
+    record_trade_in_reputations(program_id, seller.key, &winner, account_info_iter)?;
+
+    close_program_account(auction_account, seller)
+}
+
+fn create_sealed_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (amount, min_bid, min_deposit, commit_end_slot, reveal_end_slot, nonce, forfeit_unrevealed) =
+        if instruction_data.len() == 49 {
+            let instruction_data = array_ref![instruction_data, 0, 49];
+            let (amount, min_bid, min_deposit, commit_end_slot, reveal_end_slot, nonce, forfeit_unrevealed) =
+                array_refs![instruction_data, 8, 8, 8, 8, 8, 8, 1];
+            (
+                u64::from_le_bytes(*amount),
+                u64::from_le_bytes(*min_bid),
+                u64::from_le_bytes(*min_deposit),
+                u64::from_le_bytes(*commit_end_slot),
+                u64::from_le_bytes(*reveal_end_slot),
+                u64::from_le_bytes(*nonce),
+                match forfeit_unrevealed {
+                    [0] => false,
+                    [1] => true,
+                    _ => {
+                        msg!("Invalid forfeit flag {:?}. Expected 0 or 1", forfeit_unrevealed[0]);
+                        return Err(ProgramError::InvalidInstructionData);
+                    }
+                },
+            )
+        } else {
+            msg!(
+                "Invalid data - expected 49 bytes - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        };
+
+    if amount == 0 || min_bid == 0 {
+        msg!("Auction amount and minimum bid should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if commit_end_slot <= Clock::get()?.slot {
+        msg!("Commit end slot {:?} is in the past", commit_end_slot);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionEnded as u32));
+    }
+
+    if reveal_end_slot <= commit_end_slot {
+        msg!("Reveal end slot should be after commit end slot");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let seller_token_wallet = next_account_info(account_info_iter)?; // 2 - seller token wallet
+    let token_mint = next_account_info(account_info_iter)?; // 3 - token mint
+    spl_token::check_program_account(token_mint.owner)?;
+    let price_mint = next_account_info(account_info_iter)?; // 4 - price mint
+    spl_token::check_program_account(price_mint.owner)?;
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 5 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 6 - seller escrow wallet
+    check_escrow_wallet(program_id, seller.key, token_mint.key, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 7 - token program
+    check_token_program(token_program)?;
+
+    let auction_account = next_account_info(account_info_iter)?; // 8 - sealed auction
+    let (expected_auction, bump_seed) = get_sealed_auction_address(program_id, seller.key, nonce);
+    if expected_auction != *auction_account.key {
+        msg!(
+            "Auction not match. Expected {:?}",
+            expected_auction,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(auction_account) {
+        msg!("Auction {:?} already exists", auction_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 9 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 10 - config
+    check_not_paused(program_id, config_account)?;
+
+    msg!("Escrowing seller tokens");
+    transfer_from_signer(seller_token_wallet, escrow_wallet, seller, amount)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        seller,
+        auction_account,
+        SealedAuction::LEN,
+        &[b"SealedAuction", &seller.key.to_bytes(), &nonce.to_le_bytes(), &[bump_seed]],
+    )?;
+
+    let auction = SealedAuction {
+        seller: *seller.key,
+        token_mint: *token_mint.key,
+        price_mint: *price_mint.key,
+        amount,
+        min_bid,
+        min_deposit,
+        commit_end_slot,
+        reveal_end_slot,
+        forfeit_unrevealed,
+        nonce,
+        bid_count: 0,
+        highest_bidder: Pubkey::default(),
+        highest_bid: 0,
+        is_settled: false,
+    };
+
+    SealedAuction::pack(auction, auction_account.data.borrow_mut().deref_mut())
+}
+
+fn check_and_get_sealed_auction(
+    program_id: &Pubkey,
+    auction_account: &AccountInfo,
+) -> Result<SealedAuction, ProgramError> {
+    let auction = SealedAuction::unpack(&auction_account.data.borrow())?;
+    let (expected_auction, _) = get_sealed_auction_address(program_id, &auction.seller, auction.nonce);
+    if expected_auction != *auction_account.key {
+        msg!(
+            "Auction not match. Expected {:?}",
+            expected_auction,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(auction)
+}
+
+fn check_and_get_sealed_bid(
+    program_id: &Pubkey,
+    auction: &Pubkey,
+    sealed_bid_account: &AccountInfo,
+) -> Result<SealedBid, ProgramError> {
+    let sealed_bid = SealedBid::unpack(&sealed_bid_account.data.borrow())?;
+    let (expected_sealed_bid, _) = get_sealed_bid_address(program_id, auction, &sealed_bid.bidder);
+    if expected_sealed_bid != *sealed_bid_account.key || sealed_bid.auction != *auction {
+        msg!(
+            "Sealed bid not match. Expected {:?}",
+            expected_sealed_bid,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(sealed_bid)
+}
+
+fn commit_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (commitment, deposit) = if instruction_data.len() == 40 {
+        let instruction_data = array_ref![instruction_data, 0, 40];
+        let (commitment, deposit) = array_refs![instruction_data, 32, 8];
+        (*commitment, u64::from_le_bytes(*deposit))
+    } else {
+        msg!(
+            "Invalid data - expected 40 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bidder = next_account_info(account_info_iter)?; // 1 - bidder
+    if !bidder.is_signer {
+        msg!("Bidder must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let auction_account = next_account_info(account_info_iter)?; // 2 - sealed auction
+    let mut auction = check_and_get_sealed_auction(program_id, auction_account)?;
+
+    if Clock::get()?.slot >= auction.commit_end_slot {
+        msg!("Commit phase ended at slot {:?}", auction.commit_end_slot);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionEnded as u32));
+    }
+
+    if deposit < auction.min_deposit {
+        msg!("Deposit should be at least {:?}", auction.min_deposit);
+        return Err(ProgramError::Custom(P2PSwapError::BidTooLow as u32));
+    }
+
+    let bidder_price_token_wallet = next_account_info(account_info_iter)?; // 3 - bidder price token wallet
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - bidder escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - bidder escrow wallet
+    check_escrow_wallet(program_id, bidder.key, &auction.price_mint, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    let sealed_bid_account = next_account_info(account_info_iter)?; // 7 - sealed bid
+    let (expected_sealed_bid, bump_seed) =
+        get_sealed_bid_address(program_id, auction_account.key, bidder.key);
+    if expected_sealed_bid != *sealed_bid_account.key {
+        msg!(
+            "Sealed bid not match. Expected {:?}",
+            expected_sealed_bid,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(sealed_bid_account) {
+        msg!("Bidder already committed a bid");
+        return Err(ProgramError::Custom(P2PSwapError::BidExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 8 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 9 - config
+    check_not_paused(program_id, config_account)?;
+
+    msg!("Escrowing deposit");
+    transfer_from_signer(bidder_price_token_wallet, escrow_wallet, bidder, deposit)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        bidder,
+        sealed_bid_account,
+        SealedBid::LEN,
+        &[b"SealedBid", &auction_account.key.to_bytes(), &bidder.key.to_bytes(), &[bump_seed]],
+    )?;
+
+    let sealed_bid = SealedBid {
+        auction: *auction_account.key,
+        bidder: *bidder.key,
+        commitment,
+        deposit,
+        is_revealed: false,
+        bid: 0,
+    };
+    SealedBid::pack(sealed_bid, sealed_bid_account.data.borrow_mut().deref_mut())?;
+
+    auction.bid_count = auction.bid_count.checked_add(1)
+        .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+    SealedAuction::pack(auction, auction_account.data.borrow_mut().deref_mut())
+}
+
+fn reveal_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (bid, salt) = if instruction_data.len() == 40 {
+        let instruction_data = array_ref![instruction_data, 0, 40];
+        let (bid, salt) = array_refs![instruction_data, 8, 32];
+        (u64::from_le_bytes(*bid), salt)
+    } else {
+        msg!(
+            "Invalid data - expected 40 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let bidder = next_account_info(account_info_iter)?; // 1 - bidder
+    if !bidder.is_signer {
+        msg!("Bidder must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let auction_account = next_account_info(account_info_iter)?; // 2 - sealed auction
+    let mut auction = check_and_get_sealed_auction(program_id, auction_account)?;
+
+    let slot = Clock::get()?.slot;
+    if slot < auction.commit_end_slot || slot >= auction.reveal_end_slot {
+        msg!(
+            "Bids are revealed from slot {:?} until slot {:?}",
+            auction.commit_end_slot,
+            auction.reveal_end_slot,
+        );
+        return Err(ProgramError::Custom(P2PSwapError::NotRevealPhase as u32));
+    }
+
+    let sealed_bid_account = next_account_info(account_info_iter)?; // 3 - sealed bid
+    let mut sealed_bid = check_and_get_sealed_bid(program_id, auction_account.key, sealed_bid_account)?;
+    if sealed_bid.bidder != *bidder.key {
+        msg!("Bidder not match. Expected: {:?}", sealed_bid.bidder);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if sealed_bid.is_revealed {
+        msg!("Bid is already revealed");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if get_bid_commitment(bidder.key, bid, salt) != sealed_bid.commitment {
+        msg!("Bid and salt don't match the commitment");
+        return Err(ProgramError::Custom(P2PSwapError::CommitmentMismatch as u32));
+    }
+
+    if bid < auction.min_bid {
+        msg!("Bid should be at least {:?}", auction.min_bid);
+        return Err(ProgramError::Custom(P2PSwapError::BidTooLow as u32));
+    }
+
+    if bid > sealed_bid.deposit {
+        msg!("Bid exceeds deposit {:?}", sealed_bid.deposit);
+        return Err(ProgramError::Custom(P2PSwapError::BidExceedsDeposit as u32));
+    }
+
+    sealed_bid.is_revealed = true;
+    sealed_bid.bid = bid;
+    SealedBid::pack(sealed_bid, sealed_bid_account.data.borrow_mut().deref_mut())?;
+
+    // on equal bids the earlier revealed wins
+    if bid > auction.highest_bid {
+        auction.highest_bidder = *bidder.key;
+        auction.highest_bid = bid;
+        SealedAuction::pack(auction, auction_account.data.borrow_mut().deref_mut())?;
+    }
+
+    Ok(())
+}
+
+fn settle_sealed_auction<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    let auction_account = next_account_info(account_info_iter)?; // 2 - sealed auction
+    let mut auction = check_and_get_sealed_auction(program_id, auction_account)?;
+    if auction.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", auction.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if auction.is_settled {
+        msg!("Auction is already settled");
+        return Err(ProgramError::Custom(P2PSwapError::AuctionSettled as u32));
+    }
+
+    if Clock::get()?.slot < auction.reveal_end_slot {
+        msg!("Reveal phase ends at slot {:?}", auction.reveal_end_slot);
+        return Err(ProgramError::Custom(P2PSwapError::AuctionNotEnded as u32));
+    }
+
+    let seller_escrow_authority = next_account_info(account_info_iter)?; // 3 - seller escrow authority
+    let seller_escrow_wallet = next_account_info(account_info_iter)?; // 4 - seller escrow wallet
+    let seller_escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &auction.token_mint,
+        seller_escrow_authority,
+        seller_escrow_wallet,
+    )?;
+
+    // 5..7 - winner accounts (ignored when there are no revealed bids)
+    let winner_sealed_bid_account = next_account_info(account_info_iter)?; // 5 - winner sealed bid
+    let winner_escrow_authority = next_account_info(account_info_iter)?; // 6 - winner escrow authority
+    let winner_escrow_wallet = next_account_info(account_info_iter)?; // 7 - winner escrow wallet
+
+    // winner wallet is seller's token wallet when there are no revealed bids
+    let winner = if auction.highest_bidder == Pubkey::default() {
+        auction.seller
+    } else {
+        auction.highest_bidder
+    };
+    let winner_token_wallet = next_account_info(account_info_iter)?; // 8 - winner token wallet
+    check_associated_wallet(&winner, &auction.token_mint, winner_token_wallet)?;
+
+    let seller_price_token_wallet = next_account_info(account_info_iter)?; // 9 - seller price token wallet
+    check_associated_wallet(seller.key, &auction.price_mint, seller_price_token_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 10 - token program
+    check_token_program(token_program)?;
+
+    // winner or seller can close its wallet to block settlement, so caller recreates missing wallets
+    let payer = next_account_info(account_info_iter)?; // 11 - payer for missing wallets
+    if !payer.is_signer {
+        msg!("Payer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let winner_account = next_account_info(account_info_iter)?; // 12 - winner (seller when there are no revealed bids)
+    if winner != *winner_account.key {
+        msg!("Winner not match. Expected: {:?}", winner);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_mint = next_account_info(account_info_iter)?; // 13 - token mint
+    if auction.token_mint != *token_mint.key {
+        msg!("Token mint not match. Expected: {:?}", auction.token_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let price_mint = next_account_info(account_info_iter)?; // 14 - price mint
+    if auction.price_mint != *price_mint.key {
+        msg!("Price mint not match. Expected: {:?}", auction.price_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 15 - system program
+    check_system_program(system_account)?;
+
+    let associated_token_program = next_account_info(account_info_iter)?; // 16 - associated token program
+    check_associated_token_program(associated_token_program)?;
+
+    create_associated_wallet_if_missing(
+        payer,
+        winner_account,
+        token_mint,
+        winner_token_wallet,
+        system_account,
+        token_program,
+        associated_token_program,
+    )?;
+
+    if auction.highest_bidder != Pubkey::default() {
+        create_associated_wallet_if_missing(
+            payer,
+            seller,
+            price_mint,
+            seller_price_token_wallet,
+            system_account,
+            token_program,
+            associated_token_program,
+        )?;
+
+        let mut winner_sealed_bid =
+            check_and_get_sealed_bid(program_id, auction_account.key, winner_sealed_bid_account)?;
+        if winner_sealed_bid.bidder != auction.highest_bidder {
+            msg!("Sealed bid of the winner {:?} expected", auction.highest_bidder);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let winner_escrow_seed = check_escrow_wallet(
+            program_id,
+            &auction.highest_bidder,
+            &auction.price_mint,
+            winner_escrow_authority,
+            winner_escrow_wallet,
+        )?;
+
+        msg!("Transfering winning bid to seller");
+        transfer_from_escrow(
+            winner_escrow_wallet,
+            seller_price_token_wallet,
+            winner_escrow_authority,
+            &auction.highest_bidder,
+            winner_escrow_seed,
+            auction.highest_bid,
+        )?;
+
+        // the rest of the deposit is refunded on withdrawal
+        winner_sealed_bid.deposit = winner_sealed_bid.deposit.checked_sub(auction.highest_bid)
+            .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+        SealedBid::pack(winner_sealed_bid, winner_sealed_bid_account.data.borrow_mut().deref_mut())?;
+    }
+
+    msg!("Transfering escrowed tokens to {:?}", winner);
+    transfer_from_escrow(
+        seller_escrow_wallet,
+        winner_token_wallet,
+        seller_escrow_authority,
+        seller.key,
+        seller_escrow_seed,
+        auction.amount,
+    )?;
+
+    // 17, 18 - optional seller and winner reputations (nothing is recorded when there are no revealed bids)
+    record_trade_in_reputations(program_id, seller.key, &winner, account_info_iter)?;
+
+    if auction.bid_count == 0 {
+        return close_program_account(auction_account, seller);
+    }
+
+    auction.is_settled = true;
+    SealedAuction::pack(auction, auction_account.data.borrow_mut().deref_mut())
+}
+
+fn withdraw_sealed_bid<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let bidder = next_account_info(account_info_iter)?; // 1 - bidder
+    let seller = next_account_info(account_info_iter)?; // 2 - seller
+    let auction_account = next_account_info(account_info_iter)?; // 3 - sealed auction
+    let mut auction = check_and_get_sealed_auction(program_id, auction_account)?;
+    if auction.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", auction.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !auction.is_settled {
+        msg!("Auction is not settled yet");
+        return Err(ProgramError::Custom(P2PSwapError::AuctionNotSettled as u32));
+    }
+
+    let sealed_bid_account = next_account_info(account_info_iter)?; // 4 - sealed bid
+    let sealed_bid = check_and_get_sealed_bid(program_id, auction_account.key, sealed_bid_account)?;
+    if sealed_bid.bidder != *bidder.key {
+        msg!("Bidder not match. Expected: {:?}", sealed_bid.bidder);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 5 - bidder escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 6 - bidder escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        bidder.key,
+        &auction.price_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let bidder_price_token_wallet = next_account_info(account_info_iter)?; // 7 - bidder price token wallet
+    let seller_price_token_wallet = next_account_info(account_info_iter)?; // 8 - seller price token wallet
+
+    let token_program = next_account_info(account_info_iter)?; // 9 - token program
+    check_token_program(token_program)?;
+
+    let (destination, owner) = if sealed_bid.is_revealed || !auction.forfeit_unrevealed {
+        (bidder_price_token_wallet, bidder.key)
+    } else {
+        msg!("Bid was not revealed. Deposit is forfeited to seller");
+        (seller_price_token_wallet, seller.key)
+    };
+    check_associated_wallet(owner, &auction.price_mint, destination)?;
+
+    if sealed_bid.deposit > 0 {
+        transfer_from_escrow(
+            escrow_wallet,
+            destination,
+            escrow_authority,
+            bidder.key,
+            escrow_seed,
+            sealed_bid.deposit,
+        )?;
+    }
+
+    close_program_account(sealed_bid_account, bidder)?;
+
+    auction.bid_count = auction.bid_count.saturating_sub(1);
+    if auction.bid_count == 0 {
+        return close_program_account(auction_account, seller);
+    }
+
+    SealedAuction::pack(auction, auction_account.data.borrow_mut().deref_mut())
+}
+
+fn create_fiat_trade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (amount, fiat_amount, fiat_currency, payment_deadline, nonce, arbitration_fee_bps) =
+        if instruction_data.len() == 37 {
+        let instruction_data = array_ref![instruction_data, 0, 37];
+        let (amount, fiat_amount, fiat_currency, payment_deadline, nonce, arbitration_fee_bps) =
+            array_refs![instruction_data, 8, 8, 3, 8, 8, 2];
+        (
+            u64::from_le_bytes(*amount),
+            u64::from_le_bytes(*fiat_amount),
+            *fiat_currency,
+            i64::from_le_bytes(*payment_deadline),
+            u64::from_le_bytes(*nonce),
+            u16::from_le_bytes(*arbitration_fee_bps),
+        )
+    } else {
+        msg!(
+            "Invalid data - expected 37 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    if amount == 0 {
+        msg!("Trade amount should be greater than 0");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if payment_deadline <= Clock::get()?.unix_timestamp {
+        msg!("Payment deadline {:?} is in the past", payment_deadline);
+        return Err(ProgramError::Custom(P2PSwapError::PaymentWindowExpired as u32));
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let buyer = next_account_info(account_info_iter)?; // 2 - buyer
+    if seller.key == buyer.key {
+        msg!("Buyer and seller should be different");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_mint = next_account_info(account_info_iter)?; // 3 - token mint
+    spl_token::check_program_account(token_mint.owner)?;
+
+    let seller_token_wallet = next_account_info(account_info_iter)?; // 4 - seller token wallet
+    let escrow_authority = next_account_info(account_info_iter)?; // 5 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 6 - seller escrow wallet
+    check_escrow_wallet(program_id, seller.key, token_mint.key, escrow_authority, escrow_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 7 - token program
+    check_token_program(token_program)?;
+
+    let trade_account = next_account_info(account_info_iter)?; // 8 - fiat trade
+    let (expected_trade, bump_seed) = get_fiat_trade_address(program_id, seller.key, nonce);
+    if expected_trade != *trade_account.key {
+        msg!(
+            "Fiat trade not match. Expected {:?}",
+            expected_trade,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(trade_account) {
+        msg!("Fiat trade {:?} already exists", trade_account.key);
+        return Err(ProgramError::Custom(P2PSwapError::FiatTradeExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 9 - system account
+    check_system_program(system_account)?;
+
+    let config_account = next_account_info(account_info_iter)?; // 10 - config
+    check_not_paused(program_id, config_account)?;
+
+    // System program here means default arbitrator from arbitration config. Every trade needs an arbitrator,
+    // otherwise buyer marking payment as sent without paying would lock seller tokens forever
+    let arbitrator = next_account_info(account_info_iter)?; // 11 - arbitrator
+    let arbitration_account = next_account_info(account_info_iter)?; // 12 - arbitration config
+    let (arbitrator, arbitration_fee_bps) = if system_program::check_id(arbitrator.key) {
+        match check_and_get_arbitration_config(program_id, arbitration_account)? {
+            Some(arbitration) => (arbitration.arbitrator, arbitration.fee_bps),
+            None => {
+                msg!("No arbitrator specified and no default arbitrator is set");
+                return Err(ProgramError::Custom(P2PSwapError::NoArbitrator as u32));
+            }
+        }
+    } else {
+        check_arbitrator(arbitrator.key, arbitration_fee_bps)?;
+        (*arbitrator.key, arbitration_fee_bps)
+    };
+
+    if arbitrator == *seller.key || arbitrator == *buyer.key {
+        msg!("Arbitrator should not be a party of the trade");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    msg!("Escrowing seller tokens");
+    transfer_from_signer(seller_token_wallet, escrow_wallet, seller, amount)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        seller,
+        trade_account,
+        FiatTrade::LEN,
+        &[b"FiatTrade", &seller.key.to_bytes(), &nonce.to_le_bytes(), &[bump_seed]],
+    )?;
+
+    let trade = FiatTrade {
+        seller: *seller.key,
+        buyer: *buyer.key,
+        token_mint: *token_mint.key,
+        amount,
+        fiat_amount,
+        fiat_currency,
+        payment_deadline,
+        nonce,
+        status: FiatTradeStatus::Open,
+        arbitrator,
+        arbitration_fee_bps,
+        dispute_deadline: 0,
+    };
+
+    FiatTrade::pack(trade, trade_account.data.borrow_mut().deref_mut())?;
+    emit_fiat_trade_event(FiatTradeEvent::Created, trade_account.key, seller.key, &[]);
+    Ok(())
+}
+
+fn check_arbitrator(arbitrator: &Pubkey, fee_bps: u16) -> ProgramResult {
+    if *arbitrator == Pubkey::default() {
+        msg!("Arbitrator should not be default pubkey");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    if fee_bps as u64 >= BPS_DENOMINATOR {
+        msg!("Arbitration fee should be less than {:?} bps", BPS_DENOMINATOR);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    Ok(())
+}
+
+fn check_and_get_arbitration_config(
+    program_id: &Pubkey,
+    arbitration_account: &AccountInfo,
+) -> Result<Option<ArbitrationConfig>, ProgramError> {
+    let (expected_arbitration, _) = get_arbitration_config_address(program_id);
+    if expected_arbitration != *arbitration_account.key {
+        msg!(
+            "Arbitration config not match. Expected {:?}",
+            expected_arbitration,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if is_program_account_missing(arbitration_account) {
+        return Ok(None);
+    }
+
+    if *arbitration_account.owner != *program_id {
+        msg!("Arbitration config is not owned by program");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(Some(ArbitrationConfig::unpack(&arbitration_account.data.borrow())?))
+}
+
+fn emit_fiat_trade_event(event: FiatTradeEvent, trade: &Pubkey, actor: &Pubkey, data: &[&[u8]]) {
+    let event = [event as u8];
+    let mut fields: Vec<&[u8]> = vec![&event, trade.as_ref(), actor.as_ref()];
+    fields.extend_from_slice(data);
+    sol_log_data(&fields);
+}
+
+fn check_not_disputed(trade: &FiatTrade) -> ProgramResult {
+    if trade.status == FiatTradeStatus::Disputed {
+        msg!("Trade is disputed. Waiting for arbitrator {:?}", trade.arbitrator);
+        return Err(ProgramError::Custom(P2PSwapError::TradeDisputed as u32));
+    }
+
+    Ok(())
+}
+
+fn check_and_get_fiat_trade(
+    program_id: &Pubkey,
+    trade_account: &AccountInfo,
+) -> Result<FiatTrade, ProgramError> {
+    let trade = FiatTrade::unpack(&trade_account.data.borrow())?;
+    let (expected_trade, _) = get_fiat_trade_address(program_id, &trade.seller, trade.nonce);
+    if expected_trade != *trade_account.key {
+        msg!(
+            "Fiat trade not match. Expected {:?}",
+            expected_trade,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    Ok(trade)
+}
+
+fn mark_fiat_sent<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let buyer = next_account_info(account_info_iter)?; // 1 - buyer
+    if !buyer.is_signer {
+        msg!("Buyer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let trade_account = next_account_info(account_info_iter)?; // 2 - fiat trade
+    let mut trade = check_and_get_fiat_trade(program_id, trade_account)?;
+    if trade.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", trade.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_not_disputed(&trade)?;
+    if trade.status != FiatTradeStatus::Open {
+        msg!("Payment is already marked as sent");
+        return Err(ProgramError::Custom(P2PSwapError::PaymentAlreadySent as u32));
+    }
+
+    if Clock::get()?.unix_timestamp >= trade.payment_deadline {
+        msg!("Payment window expired at {:?}", trade.payment_deadline);
+        return Err(ProgramError::Custom(P2PSwapError::PaymentWindowExpired as u32));
+    }
+
+    trade.status = FiatTradeStatus::PaymentSent;
+    FiatTrade::pack(trade, trade_account.data.borrow_mut().deref_mut())?;
+    emit_fiat_trade_event(FiatTradeEvent::PaymentSent, trade_account.key, buyer.key, &[]);
+    Ok(())
+}
+
+fn release_fiat_trade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let trade_account = next_account_info(account_info_iter)?; // 2 - fiat trade
+    let trade = check_and_get_fiat_trade(program_id, trade_account)?;
+    if trade.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", trade.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_not_disputed(&trade)?;
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 3 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 4 - seller escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &trade.token_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let buyer_token_wallet = next_account_info(account_info_iter)?; // 5 - buyer token wallet
+    check_associated_wallet(&trade.buyer, &trade.token_mint, buyer_token_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 6 - token program
+    check_token_program(token_program)?;
+
+    // buyer can close its wallet to block release, so seller recreates it if needed
+    let buyer = next_account_info(account_info_iter)?; // 7 - buyer
+    if trade.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", trade.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let token_mint = next_account_info(account_info_iter)?; // 8 - token mint
+    if trade.token_mint != *token_mint.key {
+        msg!("Token mint not match. Expected: {:?}", trade.token_mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 9 - system program
+    check_system_program(system_account)?;
+
+    let associated_token_program = next_account_info(account_info_iter)?; // 10 - associated token program
+    check_associated_token_program(associated_token_program)?;
+
+    create_associated_wallet_if_missing(
+        seller,
+        buyer,
+        token_mint,
+        buyer_token_wallet,
+        system_account,
+        token_program,
+        associated_token_program,
+    )?;
+
+    msg!("Releasing escrowed tokens to buyer");
+    transfer_from_escrow(
+        escrow_wallet,
+        buyer_token_wallet,
+        escrow_authority,
+        seller.key,
+        escrow_seed,
+        trade.amount,
+    )?;
+
+    // 11, 12 - optional seller and buyer reputations
+    record_trade_in_reputations(program_id, seller.key, buyer.key, account_info_iter)?;
+
+    emit_fiat_trade_event(FiatTradeEvent::Released, trade_account.key, seller.key, &[]);
+    close_program_account(trade_account, seller)
+}
+
+// Buyer can cancel the trade at any moment (e.g. when fiat payment is not going to be sent).
+// Seller can cancel the trade only if buyer did not mark payment as sent in the payment window
+fn cancel_fiat_trade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?; // 1 - caller (buyer or seller)
+    if !caller.is_signer {
+        msg!("Caller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let seller = next_account_info(account_info_iter)?; // 2 - seller
+    let trade_account = next_account_info(account_info_iter)?; // 3 - fiat trade
+    let trade = check_and_get_fiat_trade(program_id, trade_account)?;
+    if trade.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", trade.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    check_not_disputed(&trade)?;
+
+    if *caller.key == trade.seller {
+        if trade.status == FiatTradeStatus::PaymentSent {
+            msg!("Buyer marked payment as sent. Release tokens, ask buyer to cancel the trade or raise a dispute");
+            return Err(ProgramError::Custom(P2PSwapError::PaymentAlreadySent as u32));
+        }
+
+        if Clock::get()?.unix_timestamp < trade.payment_deadline {
+            msg!("Payment window is open until {:?}", trade.payment_deadline);
+            return Err(ProgramError::Custom(P2PSwapError::PaymentWindowActive as u32));
+        }
+    } else if *caller.key != trade.buyer {
+        msg!("Only buyer or seller can cancel the trade");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - seller escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &trade.token_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let seller_token_wallet = next_account_info(account_info_iter)?; // 6 - seller token wallet
+    check_associated_wallet(seller.key, &trade.token_mint, seller_token_wallet)?;
+
+    let token_program = next_account_info(account_info_iter)?; // 7 - token program
+    check_token_program(token_program)?;
+
+    msg!("Returning escrowed tokens to seller");
+    transfer_from_escrow(
+        escrow_wallet,
+        seller_token_wallet,
+        escrow_authority,
+        seller.key,
+        escrow_seed,
+        trade.amount,
+    )?;
+
+    emit_fiat_trade_event(FiatTradeEvent::Cancelled, trade_account.key, caller.key, &[]);
+    close_program_account(trade_account, seller)
+}
+
+// Once buyer marked payment as sent, either party can freeze the trade until arbitrator decides
+// who gets escrowed tokens
+fn raise_dispute<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let caller = next_account_info(account_info_iter)?; // 1 - caller (buyer or seller)
+    if !caller.is_signer {
+        msg!("Caller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let trade_account = next_account_info(account_info_iter)?; // 2 - fiat trade
+    let mut trade = check_and_get_fiat_trade(program_id, trade_account)?;
+    if *caller.key != trade.seller && *caller.key != trade.buyer {
+        msg!("Only buyer or seller can raise a dispute");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if trade.arbitrator == Pubkey::default() {
+        msg!("Trade has no arbitrator");
+        return Err(ProgramError::Custom(P2PSwapError::NoArbitrator as u32));
+    }
+
+    check_not_disputed(&trade)?;
+    if trade.status != FiatTradeStatus::PaymentSent {
+        msg!("Dispute can be raised only after buyer marked payment as sent");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    msg!("Raising dispute. Arbitrator: {:?}", trade.arbitrator);
+    trade.status = FiatTradeStatus::Disputed;
+    trade.dispute_deadline = Clock::get()?.unix_timestamp
+        .checked_add(DISPUTE_RESOLUTION_PERIOD)
+        .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+    FiatTrade::pack(trade, trade_account.data.borrow_mut().deref_mut())?;
+    emit_fiat_trade_event(FiatTradeEvent::DisputeRaised, trade_account.key, caller.key, &[]);
+    Ok(())
+}
+
+// Arbitrator awards escrowed tokens to one of the parties and may take a fee
+// up to arbitration_fee_bps of the trade. Once dispute deadline passes, default arbitrator
+// from arbitration config can resolve the dispute instead of unresponsive arbitrator of the trade
+fn resolve_dispute<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (to_buyer, fee_bps) = if instruction_data.len() == 3 {
+        let instruction_data = array_ref![instruction_data, 0, 3];
+        let (winner, fee_bps) = array_refs![instruction_data, 1, 2];
+        let to_buyer = match winner[0] {
+            0 => false,
+            1 => true,
+            _ => {
+                msg!("Unknown winner {:?}. Expected 0 - seller or 1 - buyer", winner[0]);
+                return Err(ProgramError::InvalidInstructionData);
+            }
+        };
+        (to_buyer, u16::from_le_bytes(*fee_bps))
+    } else {
+        msg!(
+            "Invalid data - expected 3 bytes - {:?}",
+            instruction_data,
+        );
+        return Err(ProgramError::InvalidInstructionData);
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let arbitrator = next_account_info(account_info_iter)?; // 1 - arbitrator
+    if !arbitrator.is_signer {
+        msg!("Arbitrator must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let seller = next_account_info(account_info_iter)?; // 2 - seller
+    let trade_account = next_account_info(account_info_iter)?; // 3 - fiat trade
+    let trade = check_and_get_fiat_trade(program_id, trade_account)?;
+    if trade.seller != *seller.key {
+        msg!("Seller not match. Expected: {:?}", trade.seller);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if trade.status != FiatTradeStatus::Disputed {
+        msg!("Trade is not disputed");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if fee_bps > trade.arbitration_fee_bps {
+        msg!("Arbitration fee should not exceed {:?} bps", trade.arbitration_fee_bps);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    let escrow_authority = next_account_info(account_info_iter)?; // 4 - seller escrow authority
+    let escrow_wallet = next_account_info(account_info_iter)?; // 5 - seller escrow wallet
+    let escrow_seed = check_escrow_wallet(
+        program_id,
+        seller.key,
+        &trade.token_mint,
+        escrow_authority,
+        escrow_wallet,
+    )?;
+
+    let winner = if to_buyer { trade.buyer } else { trade.seller };
+    let winner_token_wallet = next_account_info(account_info_iter)?; // 6 - winner token wallet
+    check_associated_wallet(&winner, &trade.token_mint, winner_token_wallet)?;
+
+    let arbitrator_token_wallet = next_account_info(account_info_iter)?; // 7 - arbitrator token wallet
+    let token_program = next_account_info(account_info_iter)?; // 8 - token program
+    check_token_program(token_program)?;
+
+    let loser = if to_buyer { trade.seller } else { trade.buyer };
+    let loser_reputation_account = next_account_info(account_info_iter)?; // 9 - loser reputation
+
+    if trade.arbitrator != *arbitrator.key {
+        // 10 - arbitration config (only when default arbitrator resolves the dispute)
+        let is_default_arbitrator = match account_info_iter.next() {
+            Some(arbitration_account) => {
+                check_and_get_arbitration_config(program_id, arbitration_account)?
+                    .is_some_and(|arbitration| arbitration.arbitrator == *arbitrator.key)
+            },
+            None => false,
+        };
+
+        if !is_default_arbitrator {
+            msg!("Arbitrator not match. Expected: {:?}", trade.arbitrator);
+            return Err(ProgramError::Custom(P2PSwapError::NotArbitrator as u32));
+        }
+
+        if Clock::get()?.unix_timestamp < trade.dispute_deadline {
+            msg!("Arbitrator {:?} can resolve the dispute until {:?}", trade.arbitrator, trade.dispute_deadline);
+            return Err(ProgramError::Custom(P2PSwapError::NotArbitrator as u32));
+        }
+    }
+
+    if let Some(mut loser_reputation) = check_and_get_reputation(program_id, &loser, loser_reputation_account)? {
+        loser_reputation.disputes_lost = loser_reputation.disputes_lost.saturating_add(1);
+        Reputation::pack(loser_reputation, loser_reputation_account.data.borrow_mut().deref_mut())?;
+    }
+
+    let fee = (trade.amount as u128 * fee_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+    if fee > 0 {
+        check_associated_wallet(arbitrator.key, &trade.token_mint, arbitrator_token_wallet)?;
+
+        msg!("Paying arbitration fee {:?}", fee);
+        transfer_from_escrow(
+            escrow_wallet,
+            arbitrator_token_wallet,
+            escrow_authority,
+            seller.key,
+            escrow_seed,
+            fee,
+        )?;
+    }
+
+    msg!("Awarding escrowed tokens to {:?}", winner);
+    transfer_from_escrow(
+        escrow_wallet,
+        winner_token_wallet,
+        escrow_authority,
+        seller.key,
+        escrow_seed,
+        trade.amount - fee,
+    )?;
+
+    emit_fiat_trade_event(
+        FiatTradeEvent::DisputeResolved,
+        trade_account.key,
+        arbitrator.key,
+        &[winner.as_ref(), &fee.to_le_bytes()],
+    );
+    close_program_account(trade_account, seller)
+}
+
+fn create_reputation<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let payer = next_account_info(account_info_iter)?; // 1 - payer
+    if !payer.is_signer {
+        msg!("Payer must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let wallet = next_account_info(account_info_iter)?; // 2 - wallet
+    let reputation_account = next_account_info(account_info_iter)?; // 3 - reputation
+    let (expected_reputation, bump_seed) = get_reputation_address(program_id, wallet.key);
+    if expected_reputation != *reputation_account.key {
+        msg!(
+            "Reputation not match. Expected {:?}",
+            expected_reputation,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if !is_program_account_missing(reputation_account) {
+        msg!("Reputation of {:?} already exists", wallet.key);
+        return Err(ProgramError::Custom(P2PSwapError::ReputationExists as u32));
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 4 - system account
+    check_system_program(system_account)?;
+
+    create_program_account(
+        system_account,
+        program_id,
+        payer,
+        reputation_account,
+        Reputation::LEN,
+        &[b"Reputation", &wallet.key.to_bytes(), &[bump_seed]],
+    )?;
+
+    let reputation = Reputation {
+        wallet: *wallet.key,
+        trade_count: 0,
+        revoke_count: 0,
+        disputes_lost: 0,
+        first_trade_slot: 0,
+        rating_sum: 0,
+        rating_count: 0,
+    };
+
+    Reputation::pack(reputation, reputation_account.data.borrow_mut().deref_mut())
+}
+
+// Seller and buyer of a receipt rate each other once. Receipt is closed when both did
+fn rate_trade<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let rating = match instruction_data {
+        [rating @ 1..=5] => *rating,
+        _ => {
+            msg!(
+                "Invalid data - expected 1 byte rating from 1 to 5 - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let rater = next_account_info(account_info_iter)?; // 1 - rater (seller or buyer)
+    if !rater.is_signer {
+        msg!("Rater must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let buyer = next_account_info(account_info_iter)?; // 2 - buyer
+    let trade_receipt_account = next_account_info(account_info_iter)?; // 3 - trade receipt
+    let mut receipt = TradeReceipt::unpack(&trade_receipt_account.data.borrow())?;
+    let (expected_receipt, _) = get_trade_receipt_address(program_id, &receipt.order, &receipt.buyer);
+    if expected_receipt != *trade_receipt_account.key {
+        msg!(
+            "Trade receipt not match. Expected {:?}",
+            expected_receipt,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if receipt.buyer != *buyer.key {
+        msg!("Buyer not match. Expected: {:?}", receipt.buyer);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (counterparty, is_rated) = if *rater.key == receipt.seller {
+        (receipt.buyer, &mut receipt.is_rated_by_seller)
+    } else if *rater.key == receipt.buyer {
+        (receipt.seller, &mut receipt.is_rated_by_buyer)
+    } else {
+        msg!("Only seller or buyer can rate the trade");
+        return Err(ProgramError::InvalidAccountData);
+    };
+
+    if *is_rated {
+        msg!("Trade is already rated by {:?}", rater.key);
+        return Err(ProgramError::Custom(P2PSwapError::AlreadyRated as u32));
+    }
+    *is_rated = true;
+
+    let reputation_account = next_account_info(account_info_iter)?; // 4 - counterparty reputation
+    let mut reputation = check_and_get_reputation(program_id, &counterparty, reputation_account)?
+        .ok_or_else(|| {
+            msg!("Reputation of {:?} not found", counterparty);
+            ProgramError::UninitializedAccount
+        })?;
+
+    msg!("Rating {:?} with {:?}", counterparty, rating);
+    reputation.record_rating(rating);
+    Reputation::pack(reputation, reputation_account.data.borrow_mut().deref_mut())?;
+
+    if receipt.is_rated_by_seller && receipt.is_rated_by_buyer {
+        close_program_account(trade_receipt_account, buyer)
+    } else {
+        TradeReceipt::pack(receipt, trade_receipt_account.data.borrow_mut().deref_mut())
+    }
+}
+
+fn set_delegate<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // 0 - removes operator
+    let permissions = match instruction_data {
+        [permissions] if *permissions & !PERMISSION_ALL == 0 => *permissions,
+        _ => {
+            msg!(
+                "Invalid data - expected 1 byte permissions - {:?}",
+                instruction_data,
+            );
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    if !seller.is_signer {
+        msg!("Seller must be signer");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    let operator = next_account_info(account_info_iter)?; // 2 - operator
+    if operator.key == seller.key {
+        msg!("Seller can't be its own operator");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let delegate_account = next_account_info(account_info_iter)?; // 3 - delegate
+    let (expected_delegate, bump_seed) = get_delegate_address(program_id, seller.key, operator.key);
+    if expected_delegate != *delegate_account.key {
+        msg!(
+            "Delegate not match. Expected {:?}",
+            expected_delegate,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 4 - system account
+    check_system_program(system_account)?;
+
+    if permissions == 0 {
+        if !is_program_account_missing(delegate_account) {
+            msg!("Removing operator {:?}", operator.key);
+            close_program_account(delegate_account, seller)?;
+        }
+
+        return Ok(());
+    }
+
+    if is_program_account_missing(delegate_account) {
+        create_program_account(
+            system_account,
+            program_id,
+            seller,
+            delegate_account,
+            Delegate::LEN,
+            &[b"Delegate", &seller.key.to_bytes(), &operator.key.to_bytes(), &[bump_seed]],
+        )?;
+    }
+
+    msg!("Setting permissions of operator {:?} to {:?}", operator.key, permissions);
+    let delegate = Delegate {
+        seller: *seller.key,
+        operator: *operator.key,
+        permissions,
+    };
+
+    Delegate::pack(delegate, delegate_account.data.borrow_mut().deref_mut())
+}
+
+// Cancels all orders of the seller created before the bump
+fn bump_epoch<'a>(
+    program_id: &'a Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    _instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let seller = next_account_info(account_info_iter)?; // 1 - seller
+    let seller_epoch_account = next_account_info(account_info_iter)?; // 2 - seller epoch
+    let (expected_seller_epoch, bump_seed) = get_seller_epoch_address(program_id, seller.key);
+    if expected_seller_epoch != *seller_epoch_account.key {
+        msg!(
+            "Seller epoch not match. Expected {:?}",
+            expected_seller_epoch,
+        );
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let system_account = next_account_info(account_info_iter)?; // 3 - system account
+    check_system_program(system_account)?;
+
+    // 4, 5 - operator and its delegate (if seller does not sign). Operator pays for the epoch account
+    let payer = check_seller_or_operator(program_id, seller, account_info_iter, PERMISSION_REVOKE)?;
+
+    let mut seller_epoch = if is_program_account_missing(seller_epoch_account) {
+        create_program_account(
+            system_account,
+            program_id,
+            payer,
+            seller_epoch_account,
+            SellerEpoch::LEN,
+            &[b"SellerEpoch", &seller.key.to_bytes(), &[bump_seed]],
+        )?;
+
+        SellerEpoch {
+            seller: *seller.key,
+            epoch: 0,
+            bumped_at_slot: 0,
+        }
+    } else {
+        SellerEpoch::unpack(&seller_epoch_account.data.borrow())?
+    };
+
+    seller_epoch.epoch = seller_epoch.epoch
+        .checked_add(1)
+        .ok_or(ProgramError::Custom(P2PSwapError::IntOverflowError as u32))?;
+    seller_epoch.bumped_at_slot = Clock::get()?.slot;
+
+    msg!("Seller epoch bumped to {:?}. All previous orders are cancelled", seller_epoch.epoch);
+    SellerEpoch::pack(seller_epoch, seller_epoch_account.data.borrow_mut().deref_mut())
+}
+
+pub fn process_instruction<'a>(
     program_id: &'a Pubkey,
     accounts: &'a [AccountInfo<'a>],
     instruction_data: &[u8],
@@ -627,5 +5447,50 @@ fn process_instruction<'a>(
         P2PSwapInstructions::CreatePrivateOrder => create_private_order(program_id, accounts, instruction),
         P2PSwapInstructions::RevokeOrder => revoke_order(program_id, accounts, instruction),
         P2PSwapInstructions::FillOrder => fill_order(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateMarket => create_market(program_id, accounts, instruction),
+        P2PSwapInstructions::InitConfig => init_config(program_id, accounts, instruction),
+        P2PSwapInstructions::SetPaused => set_paused(program_id, accounts, instruction),
+        P2PSwapInstructions::SetAdmin => set_admin(program_id, accounts, instruction),
+        P2PSwapInstructions::SetMintListMode => set_mint_list_mode(program_id, accounts, instruction),
+        P2PSwapInstructions::SetMintStatus => set_mint_status(program_id, accounts, instruction),
+        P2PSwapInstructions::PauseOrder => set_order_status(program_id, accounts, OrderStatus::Paused),
+        P2PSwapInstructions::ResumeOrder => set_order_status(program_id, accounts, OrderStatus::Active),
+        P2PSwapInstructions::CreateProposal => create_proposal(program_id, accounts, instruction),
+        P2PSwapInstructions::WithdrawProposal => withdraw_proposal(program_id, accounts, instruction),
+        P2PSwapInstructions::AcceptProposal => accept_proposal(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateDeal => create_deal(program_id, accounts, instruction),
+        P2PSwapInstructions::DepositDeal => deposit_deal(program_id, accounts, instruction),
+        P2PSwapInstructions::SettleDeal => settle_deal(program_id, accounts, instruction),
+        P2PSwapInstructions::ReclaimDeal => reclaim_deal(program_id, accounts, instruction),
+        P2PSwapInstructions::FillOrderExactSpend => fill_order_exact_spend(program_id, accounts, instruction),
+        P2PSwapInstructions::SetPayoutSplit => set_payout_split(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateRfq => create_rfq(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateQuote => create_quote(program_id, accounts, instruction),
+        P2PSwapInstructions::AcceptQuote => accept_quote(program_id, accounts, instruction),
+        P2PSwapInstructions::ReclaimQuote => reclaim_quote(program_id, accounts, instruction),
+        P2PSwapInstructions::CloseRfq => close_rfq(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateAuction => create_auction(program_id, accounts, instruction),
+        P2PSwapInstructions::PlaceBid => place_bid(program_id, accounts, instruction),
+        P2PSwapInstructions::SettleAuction => settle_auction(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateSealedAuction => create_sealed_auction(program_id, accounts, instruction),
+        P2PSwapInstructions::CommitBid => commit_bid(program_id, accounts, instruction),
+        P2PSwapInstructions::RevealBid => reveal_bid(program_id, accounts, instruction),
+        P2PSwapInstructions::SettleSealedAuction => settle_sealed_auction(program_id, accounts, instruction),
+        P2PSwapInstructions::WithdrawSealedBid => withdraw_sealed_bid(program_id, accounts, instruction),
+        P2PSwapInstructions::SetFlip => set_flip(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateFiatTrade => create_fiat_trade(program_id, accounts, instruction),
+        P2PSwapInstructions::MarkFiatSent => mark_fiat_sent(program_id, accounts, instruction),
+        P2PSwapInstructions::ReleaseFiatTrade => release_fiat_trade(program_id, accounts, instruction),
+        P2PSwapInstructions::CancelFiatTrade => cancel_fiat_trade(program_id, accounts, instruction),
+        P2PSwapInstructions::SetArbitrationConfig => set_arbitration_config(program_id, accounts, instruction),
+        P2PSwapInstructions::RaiseDispute => raise_dispute(program_id, accounts, instruction),
+        P2PSwapInstructions::ResolveDispute => resolve_dispute(program_id, accounts, instruction),
+        P2PSwapInstructions::CreateReputation => create_reputation(program_id, accounts, instruction),
+        P2PSwapInstructions::RateTrade => rate_trade(program_id, accounts, instruction),
+        P2PSwapInstructions::SetDelegate => set_delegate(program_id, accounts, instruction),
+        P2PSwapInstructions::BumpEpoch => bump_epoch(program_id, accounts, instruction),
+        P2PSwapInstructions::UpdateMarket => update_market(program_id, accounts, instruction),
+        P2PSwapInstructions::ListOrder => list_order(program_id, accounts, instruction),
+        P2PSwapInstructions::MigrateOrder => migrate_order(program_id, accounts, instruction),
     }
 }
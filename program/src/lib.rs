@@ -4,6 +4,7 @@ use {
 
 #[cfg(not(feature="no-entrypoint"))]
 use solana_program::{
+    keccak,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
@@ -11,6 +12,7 @@ use solana_program::{
 
 #[cfg(feature="no-entrypoint")]
 use solana_sdk::{
+    keccak,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
@@ -18,7 +20,8 @@ use solana_sdk::{
 
 //extern crate core;
 
-#[cfg(not(feature="no-entrypoint"))]
+// Instruction processor is always built (tests run it with solana-program-test), only the
+// program entrypoint symbol is excluded by no-entrypoint feature
 #[macro_use]
 pub mod entrypoint;
 
@@ -30,8 +33,8 @@ use spl_associated_token_account::get_associated_token_address;
 #[repr(C)]
 #[derive(Debug)]
 pub struct SwapSPLOrder {
-    // creation slot
-    pub creation_slot: u64,
+    // Sequential index of the order handed out by seller's order counter (used as order seed)
+    pub order_index: u64,
     // Who is going to sell tokens
     pub seller: Pubkey,
     // How much tokens are allowed to be sold from order wallet within this order
@@ -51,6 +54,220 @@ pub struct SwapSPLOrder {
     pub remains_to_fill: u64,
     // Is this order private (key signed by seller required to unlock order)
     pub is_private: bool,
+    // Paused orders can not be filled until seller resumes them
+    pub status: OrderStatus,
+    // Order can not be filled before this moment
+    pub start_at: StartAt,
+    // Token account of price_mint which receives payments. Pubkey::default() means
+    // associated token account of seller
+    pub payout_wallet: Pubkey,
+    // Epoch of the seller at order creation. Order can't be filled once seller bumps its epoch
+    pub epoch: u64,
+    // Only holders of this token can fill the order. Pubkey::default() if order is not gated
+    pub gate_mint: Pubkey,
+    // Minimum balance of gate_mint buyer should hold
+    pub gate_min_balance: u64,
+    // Is this order listed in order book of its pair
+    pub is_listed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartAt {
+    Immediately,
+    Slot(u64),
+    UnixTimestamp(i64),
+}
+
+impl StartAt {
+    pub const LEN: usize = 9;
+
+    pub fn unpack(src: &[u8; 9]) -> Result<Self, ProgramError> {
+        let (kind, value) = array_refs![src, 1, 8];
+        match kind {
+            [0] => Ok(StartAt::Immediately),
+            [1] => Ok(StartAt::Slot(u64::from_le_bytes(*value))),
+            [2] => Ok(StartAt::UnixTimestamp(i64::from_le_bytes(*value))),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    pub fn pack(&self, dst: &mut [u8; 9]) {
+        let (kind_dst, value_dst) = mut_array_refs![dst, 1, 8];
+        match self {
+            StartAt::Immediately => {
+                kind_dst[0] = 0;
+                *value_dst = [0; 8];
+            },
+            StartAt::Slot(slot) => {
+                kind_dst[0] = 1;
+                *value_dst = slot.to_le_bytes();
+            },
+            StartAt::UnixTimestamp(unix_timestamp) => {
+                kind_dst[0] = 2;
+                *value_dst = unix_timestamp.to_le_bytes();
+            },
+        }
+    }
+
+    pub fn is_started(&self, slot: u64, unix_timestamp: i64) -> bool {
+        match *self {
+            StartAt::Immediately => true,
+            StartAt::Slot(start_slot) => slot >= start_slot,
+            StartAt::UnixTimestamp(start_timestamp) => unix_timestamp >= start_timestamp,
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderStatus {
+    Active = 0,
+    Paused = 1,
+}
+
+impl OrderStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(OrderStatus::Active),
+            1 => Some(OrderStatus::Paused),
+            _ => None,
+        }
+    }
+}
+
+// Rounding policy of order fills:
+// - payment for a fill is rounded up, so seller never receives less than order price
+// - a fill should be at least min_sell_amount unless it takes all the rest of the order (so dust left
+//   after partial fills can always be bought out)
+impl SwapSPLOrder {
+    // Size of orders created before order counter and the fields after is_private were added.
+    // Such orders can only be converted with MigrateOrder
+    pub const LEGACY_LEN: usize = 169;
+
+    // Reads order in the legacy layout. Its creation slot was the order seed, so it becomes order
+    // index and the order keeps its address. New fields get values of a plain active order
+    pub fn unpack_legacy(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEGACY_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let src = array_ref![src, 0, 169];
+        let (creation_slot, seller, sell_amount,
+            order_wallet, token_mint, price_mint, buy_amount,
+            min_sell_amount, remains_to_fill, is_private) =
+            array_refs![src, 8, 32, 8, 32, 32, 32, 8, 8, 8, 1];
+
+        let is_private = match is_private {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(SwapSPLOrder {
+            order_index: u64::from_le_bytes(*creation_slot),
+            seller: Pubkey::new_from_array(*seller),
+            sell_amount: u64::from_le_bytes(*sell_amount),
+            order_wallet: Pubkey::new_from_array(*order_wallet),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            buy_amount: u64::from_le_bytes(*buy_amount),
+            min_sell_amount: u64::from_le_bytes(*min_sell_amount),
+            remains_to_fill: u64::from_le_bytes(*remains_to_fill),
+            is_private,
+            status: OrderStatus::Active,
+            start_at: StartAt::Immediately,
+            payout_wallet: Pubkey::default(),
+            epoch: 0,
+            gate_mint: Pubkey::default(),
+            gate_min_balance: 0,
+            is_listed: false,
+        })
+    }
+
+    // Amount of price token to pay for given amount of order token (rounded up)
+    pub fn price_for_amount(&self, sell_token_amount: u64) -> Result<u64, P2PSwapError> {
+        let price = (sell_token_amount as u128)
+            .checked_mul(self.buy_amount as u128)
+            .ok_or(P2PSwapError::IntOverflowError)?;
+        let sell_amount = self.sell_amount as u128;
+        if sell_amount == 0 {
+            return Err(P2PSwapError::IntOverflowError);
+        }
+
+        let price = price / sell_amount + u128::from(price % sell_amount != 0);
+        u64::try_from(price).map_err(|_| P2PSwapError::IntOverflowError)
+    }
+
+    // Maximum amount of order token which can be bought for given amount of price token (rounded down).
+    // Payment for this amount (see price_for_amount) never exceeds price_token_amount.
+    pub fn amount_for_price(&self, price_token_amount: u64) -> Result<u64, P2PSwapError> {
+        let amount = (price_token_amount as u128)
+            .checked_mul(self.sell_amount as u128)
+            .ok_or(P2PSwapError::IntOverflowError)?
+            .checked_div(self.buy_amount as u128)
+            .ok_or(P2PSwapError::IntOverflowError)?;
+
+        Ok(u64::try_from(amount).unwrap_or(u64::MAX))
+    }
+
+    // Token account which should receive payments for the order
+    pub fn get_payout_wallet(&self) -> Pubkey {
+        if self.payout_wallet == Pubkey::default() {
+            get_associated_token_address(&self.seller, &self.price_mint)
+        } else {
+            self.payout_wallet
+        }
+    }
+
+    // Amount of order token the reverse order of a flip order asks for price tokens collected by a fill:
+    // the order price improved by spread for seller (rounded up)
+    pub fn flip_buy_amount(&self, price_token_amount: u64, spread_bps: u16) -> Result<u64, P2PSwapError> {
+        let amount = (price_token_amount as u128)
+            .checked_mul(self.sell_amount as u128)
+            .ok_or(P2PSwapError::IntOverflowError)?
+            .checked_mul(BPS_DENOMINATOR as u128)
+            .ok_or(P2PSwapError::IntOverflowError)?;
+        let divisor = (self.buy_amount as u128)
+            .checked_mul((BPS_DENOMINATOR as u128).saturating_sub(spread_bps as u128))
+            .ok_or(P2PSwapError::IntOverflowError)?;
+        if divisor == 0 {
+            return Err(P2PSwapError::IntOverflowError);
+        }
+
+        let amount = amount / divisor + u128::from(amount % divisor != 0);
+        u64::try_from(amount).map_err(|_| P2PSwapError::IntOverflowError)
+    }
+
+    // Adds sell_token_amount to be sold for buy_token_amount. Order is rebased to what remains to fill,
+    // so the price of already filled part does not affect the price of the rest
+    pub fn refill(&mut self, sell_token_amount: u64, buy_token_amount: u64) -> Result<(), P2PSwapError> {
+        let remains_price = if self.remains_to_fill == 0 {
+            0
+        } else {
+            self.price_for_amount(self.remains_to_fill)?
+        };
+
+        self.sell_amount = self.remains_to_fill
+            .checked_add(sell_token_amount)
+            .ok_or(P2PSwapError::IntOverflowError)?;
+        self.buy_amount = remains_price
+            .checked_add(buy_token_amount)
+            .ok_or(P2PSwapError::IntOverflowError)?;
+        self.remains_to_fill = self.sell_amount;
+        Ok(())
+    }
+
+    pub fn check_fill_amount(&self, sell_token_amount: u64) -> Result<(), P2PSwapError> {
+        if self.remains_to_fill < sell_token_amount {
+            return Err(P2PSwapError::NotEnoughTokensInOrder);
+        }
+
+        if sell_token_amount < self.min_sell_amount && sell_token_amount != self.remains_to_fill {
+            return Err(P2PSwapError::BuyAmountBelowMinimum);
+        }
+
+        Ok(())
+    }
 }
 
 impl Sealed for SwapSPLOrder {}
@@ -83,16 +300,157 @@ pub fn get_order_address(program_id: &Pubkey, seller: &Pubkey, order_seed: u64)
     )
 }
 
+pub fn get_order_counter_address(program_id: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"OrderCounter", &seller.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_market_address(program_id: &Pubkey, token_mint: &Pubkey, price_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Market", &token_mint.to_bytes(), &price_mint.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_order_book_address(program_id: &Pubkey, token_mint: &Pubkey, price_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"OrderBook", &token_mint.to_bytes(), &price_mint.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"Config"], program_id)
+}
+
+pub fn get_mint_list_entry_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"MintListEntry", &mint.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_proposal_address(program_id: &Pubkey, order: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Proposal", &order.to_bytes(), &buyer.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_payout_split_address(program_id: &Pubkey, order: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"PayoutSplit", &order.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_rfq_address(program_id: &Pubkey, buyer: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Rfq", &buyer.to_bytes(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_quote_address(program_id: &Pubkey, rfq: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Quote", &rfq.to_bytes(), &seller.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_auction_address(program_id: &Pubkey, seller: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Auction", &seller.to_bytes(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_sealed_auction_address(program_id: &Pubkey, seller: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"SealedAuction", &seller.to_bytes(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_sealed_bid_address(program_id: &Pubkey, auction: &Pubkey, bidder: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"SealedBid", &auction.to_bytes(), &bidder.to_bytes()],
+        program_id,
+    )
+}
+
+// Hash committed by bidder of sealed-bid auction. Bidder is hashed in so nobody can copy
+// someone else's commitment and reveal it after the owner
+pub fn get_bid_commitment(bidder: &Pubkey, bid: u64, salt: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&bidder.to_bytes(), &bid.to_le_bytes(), salt]).to_bytes()
+}
+
+pub fn get_reputation_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Reputation", &wallet.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_trade_receipt_address(program_id: &Pubkey, order: &Pubkey, buyer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"TradeReceipt", &order.to_bytes(), &buyer.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_delegate_address(program_id: &Pubkey, seller: &Pubkey, operator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Delegate", &seller.to_bytes(), &operator.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_seller_epoch_address(program_id: &Pubkey, seller: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"SellerEpoch", &seller.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_flip_address(program_id: &Pubkey, order: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Flip", &order.to_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_arbitration_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"ArbitrationConfig"], program_id)
+}
+
+pub fn get_fiat_trade_address(program_id: &Pubkey, seller: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"FiatTrade", &seller.to_bytes(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+pub fn get_deal_address(program_id: &Pubkey, party_a: &Pubkey, nonce: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Deal", &party_a.to_bytes(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
 impl Pack for SwapSPLOrder {
-    const LEN: usize = 169;
+    const LEN: usize = 260;
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, 169];
-        let (creation_slot, seller, sell_amount,
+        let src = array_ref![src, 0, 260];
+        let (order_index, seller, sell_amount,
             order_wallet, token_mint, price_mint, buy_amount,
-            min_sell_amount, remains_to_fill, is_private) =
-            array_refs![src, 8, 32, 8, 32, 32, 32, 8, 8, 8, 1];
+            min_sell_amount, remains_to_fill, is_private, status, start_at, payout_wallet, epoch,
+            gate_mint, gate_min_balance, is_listed) =
+            array_refs![src, 8, 32, 8, 32, 32, 32, 8, 8, 8, 1, 1, StartAt::LEN, 32, 8, 32, 8, 1];
 
-        let creation_slot = u64::from_le_bytes(*creation_slot);
+        let order_index = u64::from_le_bytes(*order_index);
         let sell_amount = u64::from_le_bytes(*sell_amount);
         let buy_amount = u64::from_le_bytes(*buy_amount);
         let min_sell_amount = u64::from_le_bytes(*min_sell_amount);
@@ -102,9 +460,17 @@ impl Pack for SwapSPLOrder {
             [1] => true,
             _ => return Err(ProgramError::InvalidAccountData),
         };
+        let status = OrderStatus::from_u8(status[0])
+            .ok_or(ProgramError::InvalidAccountData)?;
+        let start_at = StartAt::unpack(start_at)?;
+        let is_listed = match is_listed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
 
         Ok(SwapSPLOrder {
-            creation_slot,
+            order_index,
             seller: Pubkey::new_from_array(*seller),
             sell_amount,
             order_wallet: Pubkey::new_from_array(*order_wallet),
@@ -114,13 +480,20 @@ impl Pack for SwapSPLOrder {
             min_sell_amount,
             remains_to_fill,
             is_private,
+            status,
+            start_at,
+            payout_wallet: Pubkey::new_from_array(*payout_wallet),
+            epoch: u64::from_le_bytes(*epoch),
+            gate_mint: Pubkey::new_from_array(*gate_mint),
+            gate_min_balance: u64::from_le_bytes(*gate_min_balance),
+            is_listed,
         })
     }
 
     fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, 169];
+        let dst = array_mut_ref![dst, 0, 260];
         let (
-            creation_slot_dst,
+            order_index_dst,
             seller_dst,
             sell_amount_dst,
             order_wallet_dst,
@@ -130,9 +503,16 @@ impl Pack for SwapSPLOrder {
             min_sell_mount_dst,
             remains_to_fill_dst,
             is_private_dst,
-        ) = mut_array_refs![dst, 8, 32, 8, 32, 32, 32, 8, 8, 8, 1];
+            status_dst,
+            start_at_dst,
+            payout_wallet_dst,
+            epoch_dst,
+            gate_mint_dst,
+            gate_min_balance_dst,
+            is_listed_dst,
+        ) = mut_array_refs![dst, 8, 32, 8, 32, 32, 32, 8, 8, 8, 1, 1, StartAt::LEN, 32, 8, 32, 8, 1];
         let &SwapSPLOrder {
-            creation_slot,
+            order_index,
             ref seller,
             sell_amount,
             ref order_wallet,
@@ -142,8 +522,15 @@ impl Pack for SwapSPLOrder {
             min_sell_amount,
             remains_to_fill,
             is_private,
+            status,
+            ref start_at,
+            ref payout_wallet,
+            epoch,
+            ref gate_mint,
+            gate_min_balance,
+            is_listed,
         } = self;
-        *creation_slot_dst = creation_slot.to_le_bytes();
+        *order_index_dst = order_index.to_le_bytes();
         seller_dst.copy_from_slice(seller.as_ref());
         *sell_amount_dst = sell_amount.to_le_bytes();
         order_wallet_dst.copy_from_slice(order_wallet.as_ref());
@@ -153,36 +540,1698 @@ impl Pack for SwapSPLOrder {
         *min_sell_mount_dst = min_sell_amount.to_le_bytes();
         *remains_to_fill_dst = remains_to_fill.to_le_bytes();
         is_private_dst[0] = is_private as u8;
+        status_dst[0] = status as u8;
+        start_at.pack(start_at_dst);
+        payout_wallet_dst.copy_from_slice(payout_wallet.as_ref());
+        *epoch_dst = epoch.to_le_bytes();
+        gate_mint_dst.copy_from_slice(gate_mint.as_ref());
+        *gate_min_balance_dst = gate_min_balance.to_le_bytes();
+        is_listed_dst[0] = is_listed as u8;
     }
 }
 
-#[repr(u8)]
-pub enum P2PSwapInstructions {
-    Undefined = 0,
-    CreatePublicOrder = 1,
-    CreatePrivateOrder = 2,
-    RevokeOrder = 3,
-    FillOrder = 4,
+#[repr(C)]
+#[derive(Debug)]
+pub struct OrderCounter {
+    // Seller this counter belongs to
+    pub seller: Pubkey,
+    // Index to be used as seed of the next order created by seller
+    pub next_order_index: u64,
 }
 
-impl P2PSwapInstructions {
-    pub fn from_u8(value: u8) -> Self {
-        match value {
-            1 => P2PSwapInstructions::CreatePublicOrder,
-            2 => P2PSwapInstructions::CreatePrivateOrder,
-            3 => P2PSwapInstructions::RevokeOrder,
-            4 => P2PSwapInstructions::FillOrder,
-            _ => P2PSwapInstructions::Undefined,
+impl Sealed for OrderCounter {}
+
+impl IsInitialized for OrderCounter {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Pack for OrderCounter {
+    const LEN: usize = 40;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 40];
+        let (seller, next_order_index) = array_refs![src, 32, 8];
+
+        Ok(OrderCounter {
+            seller: Pubkey::new_from_array(*seller),
+            next_order_index: u64::from_le_bytes(*next_order_index),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 40];
+        let (seller_dst, next_order_index_dst) = mut_array_refs![dst, 32, 8];
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        *next_order_index_dst = self.next_order_index.to_le_bytes();
+    }
+}
+
+// Bumping the epoch cancels all orders the seller created before at once
+#[repr(C)]
+#[derive(Debug)]
+pub struct SellerEpoch {
+    pub seller: Pubkey,
+    // Epoch copied into new orders of the seller
+    pub epoch: u64,
+    // Slot of the last bump
+    pub bumped_at_slot: u64,
+}
+
+impl Sealed for SellerEpoch {}
+
+impl IsInitialized for SellerEpoch {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Pack for SellerEpoch {
+    const LEN: usize = 48;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 48];
+        let (seller, epoch, bumped_at_slot) = array_refs![src, 32, 8, 8];
+
+        Ok(SellerEpoch {
+            seller: Pubkey::new_from_array(*seller),
+            epoch: u64::from_le_bytes(*epoch),
+            bumped_at_slot: u64::from_le_bytes(*bumped_at_slot),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 48];
+        let (seller_dst, epoch_dst, bumped_at_slot_dst) = mut_array_refs![dst, 32, 8, 8];
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        *epoch_dst = self.epoch.to_le_bytes();
+        *bumped_at_slot_dst = self.bumped_at_slot.to_le_bytes();
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Market {
+    // Admin who created this market
+    pub authority: Pubkey,
+    // Token to sell
+    pub token_mint: Pubkey,
+    // Token for payment
+    pub price_mint: Pubkey,
+    // Decimals of token_mint
+    pub token_decimals: u8,
+    // Decimals of price_mint
+    pub price_decimals: u8,
+    // Price of a single lot (in price_mint smallest portions) should be a multiple of tick size
+    pub tick_size: u64,
+    // Order and fill amounts (in token_mint smallest portions) should be a multiple of lot size
+    pub lot_size: u64,
+    // Minimum sell amount of an order
+    pub min_order_size: u64,
+}
+
+impl Sealed for Market {}
+
+impl IsInitialized for Market {
+    fn is_initialized(&self) -> bool {
+        self.lot_size != 0
+    }
+}
+
+impl Market {
+    pub fn check_order(&self, sell_amount: u64, buy_amount: u64, min_sell_amount: u64) -> Result<(), P2PSwapError> {
+        if sell_amount < self.min_order_size {
+            return Err(P2PSwapError::OrderBelowMinimumSize);
+        }
+
+        if sell_amount.checked_rem(self.lot_size) != Some(0)
+            || min_sell_amount.checked_rem(self.lot_size) != Some(0) {
+            return Err(P2PSwapError::InvalidLotSize);
+        }
+
+        let lot_price = (buy_amount as u128) * (self.lot_size as u128);
+        if lot_price.checked_rem(sell_amount as u128) != Some(0)
+            || (lot_price / (sell_amount as u128)).checked_rem(self.tick_size as u128) != Some(0) {
+            return Err(P2PSwapError::InvalidTickSize);
+        }
+
+        Ok(())
+    }
+
+    pub fn round_down_to_lot(&self, sell_token_amount: u64) -> u64 {
+        sell_token_amount - sell_token_amount.checked_rem(self.lot_size).unwrap_or(0)
+    }
+
+    // Orders with less tokens to fill are not listed in order book of the pair
+    pub fn min_listing_amount(&self) -> u64 {
+        self.min_order_size.max(self.lot_size)
+    }
+
+    pub fn check_fill(&self, sell_token_amount: u64) -> Result<(), P2PSwapError> {
+        if sell_token_amount.checked_rem(self.lot_size) != Some(0) {
+            return Err(P2PSwapError::InvalidLotSize);
         }
+
+        Ok(())
     }
 }
 
-enum P2PSwapError {
+impl Pack for Market {
+    const LEN: usize = 122;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 122];
+        let (authority, token_mint, price_mint, token_decimals,
+            price_decimals, tick_size, lot_size, min_order_size) =
+            array_refs![src, 32, 32, 32, 1, 1, 8, 8, 8];
+
+        Ok(Market {
+            authority: Pubkey::new_from_array(*authority),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            token_decimals: token_decimals[0],
+            price_decimals: price_decimals[0],
+            tick_size: u64::from_le_bytes(*tick_size),
+            lot_size: u64::from_le_bytes(*lot_size),
+            min_order_size: u64::from_le_bytes(*min_order_size),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 122];
+        let (
+            authority_dst,
+            token_mint_dst,
+            price_mint_dst,
+            token_decimals_dst,
+            price_decimals_dst,
+            tick_size_dst,
+            lot_size_dst,
+            min_order_size_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 1, 1, 8, 8, 8];
+        authority_dst.copy_from_slice(self.authority.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        token_decimals_dst[0] = self.token_decimals;
+        price_decimals_dst[0] = self.price_decimals;
+        *tick_size_dst = self.tick_size.to_le_bytes();
+        *lot_size_dst = self.lot_size.to_le_bytes();
+        *min_order_size_dst = self.min_order_size.to_le_bytes();
+    }
+}
+
+// Maximum number of open orders listed in order book of a single token pair
+pub const MAX_ORDER_BOOK_ENTRIES: usize = 128;
+const ORDER_BOOK_ENTRY_LEN: usize = 56;
+const ORDER_BOOK_HEADER_LEN: usize = 66;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBookEntry {
+    // Address of the order
+    pub order: Pubkey,
+    // Same as in order - price is buy_amount / sell_amount
+    pub sell_amount: u64,
+    // Same as in order - price is buy_amount / sell_amount
+    pub buy_amount: u64,
+    // How much tokens are still to be sold
+    pub remains_to_fill: u64,
+}
+
+impl OrderBookEntry {
+    // Compares prices of two entries without rounding
+    pub fn price_cmp(&self, other: &OrderBookEntry) -> std::cmp::Ordering {
+        let left = (self.buy_amount as u128) * (other.sell_amount as u128);
+        let right = (other.buy_amount as u128) * (self.sell_amount as u128);
+        left.cmp(&right)
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct OrderBook {
+    // Token to sell
+    pub token_mint: Pubkey,
+    // Token for payment
+    pub price_mint: Pubkey,
+    // Open public orders sorted by price (lowest first). Orders with the same price are
+    // sorted by creation time
+    pub entries: Vec<OrderBookEntry>,
+}
+
+impl Sealed for OrderBook {}
+
+impl IsInitialized for OrderBook {
+    fn is_initialized(&self) -> bool {
+        self.token_mint != Pubkey::default()
+    }
+}
+
+impl OrderBook {
+    // Listed orders are never evicted: full book does not accept new orders (OrderBookFull)
+    pub fn insert(&mut self, entry: OrderBookEntry) -> Result<(), P2PSwapError> {
+        if self.entries.len() >= MAX_ORDER_BOOK_ENTRIES {
+            return Err(P2PSwapError::OrderBookFull);
+        }
+
+        let position = self.entries
+            .iter()
+            .position(|existing| existing.price_cmp(&entry) == std::cmp::Ordering::Greater)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(position, entry);
+        Ok(())
+    }
+
+    pub fn contains(&self, order: &Pubkey) -> bool {
+        self.entries.iter().any(|entry| entry.order == *order)
+    }
+
+    // Re-inserts the order after its price has changed
+    pub fn replace(&mut self, entry: OrderBookEntry) -> Result<(), P2PSwapError> {
+        self.entries.retain(|existing| existing.order != entry.order);
+        self.insert(entry)
+    }
+
+    // Sets new remaining amount of the order or removes it from book if nothing remains to fill
+    pub fn update(&mut self, order: &Pubkey, remains_to_fill: u64) {
+        if let Some(position) = self.entries.iter().position(|entry| entry.order == *order) {
+            if remains_to_fill == 0 {
+                self.entries.remove(position);
+            } else {
+                self.entries[position].remains_to_fill = remains_to_fill;
+            }
+        }
+    }
+}
+
+impl Pack for OrderBook {
+    const LEN: usize = ORDER_BOOK_HEADER_LEN + MAX_ORDER_BOOK_ENTRIES * ORDER_BOOK_ENTRY_LEN;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (header, body) = src.split_at(ORDER_BOOK_HEADER_LEN);
+        let header = array_ref![header, 0, ORDER_BOOK_HEADER_LEN];
+        let (token_mint, price_mint, entries_count) = array_refs![header, 32, 32, 2];
+
+        let entries_count = u16::from_le_bytes(*entries_count) as usize;
+        if entries_count > MAX_ORDER_BOOK_ENTRIES {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let entries = body
+            .chunks_exact(ORDER_BOOK_ENTRY_LEN)
+            .take(entries_count)
+            .map(|entry| {
+                let entry = array_ref![entry, 0, ORDER_BOOK_ENTRY_LEN];
+                let (order, sell_amount, buy_amount, remains_to_fill) =
+                    array_refs![entry, 32, 8, 8, 8];
+                OrderBookEntry {
+                    order: Pubkey::new_from_array(*order),
+                    sell_amount: u64::from_le_bytes(*sell_amount),
+                    buy_amount: u64::from_le_bytes(*buy_amount),
+                    remains_to_fill: u64::from_le_bytes(*remains_to_fill),
+                }
+            })
+            .collect();
+
+        Ok(OrderBook {
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            entries,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let (header, body) = dst.split_at_mut(ORDER_BOOK_HEADER_LEN);
+        let header = array_mut_ref![header, 0, ORDER_BOOK_HEADER_LEN];
+        let (token_mint_dst, price_mint_dst, entries_count_dst) = mut_array_refs![header, 32, 32, 2];
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        *entries_count_dst = (self.entries.len() as u16).to_le_bytes();
+
+        body.fill(0);
+        for (entry, entry_dst) in self.entries.iter().zip(body.chunks_exact_mut(ORDER_BOOK_ENTRY_LEN)) {
+            let entry_dst = array_mut_ref![entry_dst, 0, ORDER_BOOK_ENTRY_LEN];
+            let (order_dst, sell_amount_dst, buy_amount_dst, remains_to_fill_dst) =
+                mut_array_refs![entry_dst, 32, 8, 8, 8];
+            order_dst.copy_from_slice(entry.order.as_ref());
+            *sell_amount_dst = entry.sell_amount.to_le_bytes();
+            *buy_amount_dst = entry.buy_amount.to_le_bytes();
+            *remains_to_fill_dst = entry.remains_to_fill.to_le_bytes();
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MintListMode {
+    // Any mint can be used in orders
+    Open = 0,
+    // Mints marked as denied can not be used in orders
+    Denylist = 1,
+    // Only mints marked as allowed can be used in orders
+    Allowlist = 2,
+}
+
+impl MintListMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(MintListMode::Open),
+            1 => Some(MintListMode::Denylist),
+            2 => Some(MintListMode::Allowlist),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Config {
+    // Who is allowed to manage this deployment of p2p-swap
+    pub admin: Pubkey,
+    // Creation and filling of orders are blocked while program is paused
+    pub is_paused: bool,
+    // How mint list entries are applied to token_mint and price_mint of new orders
+    pub mint_list_mode: MintListMode,
+}
+
+impl Sealed for Config {}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.admin != Pubkey::default()
+    }
+}
+
+impl Pack for Config {
+    const LEN: usize = 34;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 34];
+        let (admin, is_paused, mint_list_mode) = array_refs![src, 32, 1, 1];
+        let is_paused = match is_paused {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let mint_list_mode = MintListMode::from_u8(mint_list_mode[0])
+            .ok_or(ProgramError::InvalidAccountData)?;
+
+        Ok(Config {
+            admin: Pubkey::new_from_array(*admin),
+            is_paused,
+            mint_list_mode,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 34];
+        let (admin_dst, is_paused_dst, mint_list_mode_dst) = mut_array_refs![dst, 32, 1, 1];
+        admin_dst.copy_from_slice(self.admin.as_ref());
+        is_paused_dst[0] = self.is_paused as u8;
+        mint_list_mode_dst[0] = self.mint_list_mode as u8;
+    }
+}
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MintStatus {
+    Allowed = 1,
+    Denied = 2,
+}
+
+impl MintStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(MintStatus::Allowed),
+            2 => Some(MintStatus::Denied),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct MintListEntry {
+    // Token mint this entry is about
+    pub mint: Pubkey,
+    // Whether mint is allowed or denied by admin
+    pub status: MintStatus,
+}
+
+impl Sealed for MintListEntry {}
+
+impl IsInitialized for MintListEntry {
+    fn is_initialized(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+}
+
+impl Pack for MintListEntry {
+    const LEN: usize = 33;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 33];
+        let (mint, status) = array_refs![src, 32, 1];
+
+        Ok(MintListEntry {
+            mint: Pubkey::new_from_array(*mint),
+            status: MintStatus::from_u8(status[0]).ok_or(ProgramError::InvalidAccountData)?,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 33];
+        let (mint_dst, status_dst) = mut_array_refs![dst, 32, 1];
+        mint_dst.copy_from_slice(self.mint.as_ref());
+        status_dst[0] = self.status as u8;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Proposal {
+    // Order this proposal is made for
+    pub order: Pubkey,
+    // Who proposes different terms. Buyer's payment is escrowed in the order wallet of the buyer
+    // (ATA of buyer's order wallet authority for price_mint)
+    pub buyer: Pubkey,
+    // Token for payment (same as in order)
+    pub price_mint: Pubkey,
+    // How much order tokens buyer wants to get
+    pub sell_amount: u64,
+    // How much price-tokens buyer pays (escrowed)
+    pub buy_amount: u64,
+}
+
+impl Sealed for Proposal {}
+
+impl IsInitialized for Proposal {
+    fn is_initialized(&self) -> bool {
+        self.buyer != Pubkey::default()
+    }
+}
+
+impl Pack for Proposal {
+    const LEN: usize = 112;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 112];
+        let (order, buyer, price_mint, sell_amount, buy_amount) = array_refs![src, 32, 32, 32, 8, 8];
+
+        Ok(Proposal {
+            order: Pubkey::new_from_array(*order),
+            buyer: Pubkey::new_from_array(*buyer),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            sell_amount: u64::from_le_bytes(*sell_amount),
+            buy_amount: u64::from_le_bytes(*buy_amount),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 112];
+        let (order_dst, buyer_dst, price_mint_dst, sell_amount_dst, buy_amount_dst) =
+            mut_array_refs![dst, 32, 32, 32, 8, 8];
+        order_dst.copy_from_slice(self.order.as_ref());
+        buyer_dst.copy_from_slice(self.buyer.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        *sell_amount_dst = self.sell_amount.to_le_bytes();
+        *buy_amount_dst = self.buy_amount.to_le_bytes();
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Deal {
+    // Who created the deal
+    pub party_a: Pubkey,
+    // Counterparty of the deal
+    pub party_b: Pubkey,
+    // Token party_a gives
+    pub mint_a: Pubkey,
+    // Token party_b gives
+    pub mint_b: Pubkey,
+    // How much of mint_a party_a deposits
+    pub amount_a: u64,
+    // How much of mint_b party_b deposits
+    pub amount_b: u64,
+    // Unix timestamp. Deposits are not accepted after deadline, deposited tokens can be reclaimed
+    // after deadline if counterparty never funded the deal
+    pub deadline: i64,
+    // Seed of the deal address chosen by party_a
+    pub nonce: u64,
+    // Deposits are stored in order wallets of the parties
+    // (ATA of party's order wallet authority for deposited mint)
+    pub a_deposited: bool,
+    pub b_deposited: bool,
+}
+
+impl Sealed for Deal {}
+
+impl IsInitialized for Deal {
+    fn is_initialized(&self) -> bool {
+        self.party_a != Pubkey::default()
+    }
+}
+
+impl Pack for Deal {
+    const LEN: usize = 162;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 162];
+        let (party_a, party_b, mint_a, mint_b, amount_a, amount_b,
+            deadline, nonce, a_deposited, b_deposited) =
+            array_refs![src, 32, 32, 32, 32, 8, 8, 8, 8, 1, 1];
+
+        let unpack_bool = |value: &[u8; 1]| match value {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(Deal {
+            party_a: Pubkey::new_from_array(*party_a),
+            party_b: Pubkey::new_from_array(*party_b),
+            mint_a: Pubkey::new_from_array(*mint_a),
+            mint_b: Pubkey::new_from_array(*mint_b),
+            amount_a: u64::from_le_bytes(*amount_a),
+            amount_b: u64::from_le_bytes(*amount_b),
+            deadline: i64::from_le_bytes(*deadline),
+            nonce: u64::from_le_bytes(*nonce),
+            a_deposited: unpack_bool(a_deposited)?,
+            b_deposited: unpack_bool(b_deposited)?,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 162];
+        let (
+            party_a_dst,
+            party_b_dst,
+            mint_a_dst,
+            mint_b_dst,
+            amount_a_dst,
+            amount_b_dst,
+            deadline_dst,
+            nonce_dst,
+            a_deposited_dst,
+            b_deposited_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 32, 8, 8, 8, 8, 1, 1];
+        party_a_dst.copy_from_slice(self.party_a.as_ref());
+        party_b_dst.copy_from_slice(self.party_b.as_ref());
+        mint_a_dst.copy_from_slice(self.mint_a.as_ref());
+        mint_b_dst.copy_from_slice(self.mint_b.as_ref());
+        *amount_a_dst = self.amount_a.to_le_bytes();
+        *amount_b_dst = self.amount_b.to_le_bytes();
+        *deadline_dst = self.deadline.to_le_bytes();
+        *nonce_dst = self.nonce.to_le_bytes();
+        a_deposited_dst[0] = self.a_deposited as u8;
+        b_deposited_dst[0] = self.b_deposited as u8;
+    }
+}
+
+pub const MAX_PAYOUT_RECIPIENTS: usize = 5;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+const PAYOUT_RECIPIENT_LEN: usize = 34;
+const PAYOUT_SPLIT_HEADER_LEN: usize = 33;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoutRecipient {
+    // Token account of price_mint receiving the share
+    pub wallet: Pubkey,
+    // Share of every payment in basis points
+    pub bps: u16,
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct PayoutSplit {
+    // Order this split belongs to
+    pub order: Pubkey,
+    // Partners receiving shares of payments. The rest of every payment goes to payout wallet
+    // of the order
+    pub recipients: Vec<PayoutRecipient>,
+}
+
+impl Sealed for PayoutSplit {}
+
+impl IsInitialized for PayoutSplit {
+    fn is_initialized(&self) -> bool {
+        self.order != Pubkey::default()
+    }
+}
+
+impl PayoutSplit {
+    pub fn check_recipients(recipients: &[PayoutRecipient]) -> Result<(), P2PSwapError> {
+        if recipients.len() > MAX_PAYOUT_RECIPIENTS
+            || recipients.iter().any(|recipient| recipient.bps == 0) {
+            return Err(P2PSwapError::InvalidPayoutSplit);
+        }
+
+        let total_bps: u64 = recipients.iter().map(|recipient| recipient.bps as u64).sum();
+        if total_bps > BPS_DENOMINATOR {
+            return Err(P2PSwapError::InvalidPayoutSplit);
+        }
+
+        Ok(())
+    }
+
+    // Shares of recipients (rounded down) in the same order as recipients. Whatever remains after
+    // the shares belongs to payout wallet of the order
+    pub fn shares(&self, amount: u64) -> Vec<u64> {
+        self.recipients
+            .iter()
+            .map(|recipient| ((amount as u128) * (recipient.bps as u128) / (BPS_DENOMINATOR as u128)) as u64)
+            .collect()
+    }
+}
+
+impl Pack for PayoutSplit {
+    const LEN: usize = PAYOUT_SPLIT_HEADER_LEN + MAX_PAYOUT_RECIPIENTS * PAYOUT_RECIPIENT_LEN;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let (header, body) = src.split_at(PAYOUT_SPLIT_HEADER_LEN);
+        let header = array_ref![header, 0, PAYOUT_SPLIT_HEADER_LEN];
+        let (order, recipients_count) = array_refs![header, 32, 1];
+
+        let recipients_count = recipients_count[0] as usize;
+        if recipients_count > MAX_PAYOUT_RECIPIENTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let recipients = body
+            .chunks_exact(PAYOUT_RECIPIENT_LEN)
+            .take(recipients_count)
+            .map(|recipient| {
+                let recipient = array_ref![recipient, 0, PAYOUT_RECIPIENT_LEN];
+                let (wallet, bps) = array_refs![recipient, 32, 2];
+                PayoutRecipient {
+                    wallet: Pubkey::new_from_array(*wallet),
+                    bps: u16::from_le_bytes(*bps),
+                }
+            })
+            .collect();
+
+        Ok(PayoutSplit {
+            order: Pubkey::new_from_array(*order),
+            recipients,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let (header, body) = dst.split_at_mut(PAYOUT_SPLIT_HEADER_LEN);
+        let header = array_mut_ref![header, 0, PAYOUT_SPLIT_HEADER_LEN];
+        let (order_dst, recipients_count_dst) = mut_array_refs![header, 32, 1];
+        order_dst.copy_from_slice(self.order.as_ref());
+        recipients_count_dst[0] = self.recipients.len() as u8;
+
+        body.fill(0);
+        for (recipient, recipient_dst) in self.recipients.iter().zip(body.chunks_exact_mut(PAYOUT_RECIPIENT_LEN)) {
+            let recipient_dst = array_mut_ref![recipient_dst, 0, PAYOUT_RECIPIENT_LEN];
+            let (wallet_dst, bps_dst) = mut_array_refs![recipient_dst, 32, 2];
+            wallet_dst.copy_from_slice(recipient.wallet.as_ref());
+            *bps_dst = recipient.bps.to_le_bytes();
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Rfq {
+    // Who requests quotes (and pays for the tokens)
+    pub buyer: Pubkey,
+    // Token buyer wants to get
+    pub token_mint: Pubkey,
+    // Token for payment
+    pub price_mint: Pubkey,
+    // How much tokens buyer wants to get
+    pub amount: u64,
+    // Quotes can be made and accepted only before this unix timestamp
+    pub deadline: i64,
+    // Seed of the RFQ address chosen by buyer
+    pub nonce: u64,
+    // Quote accepted by buyer. Pubkey::default() until the RFQ is settled
+    pub accepted_quote: Pubkey,
+}
+
+impl Sealed for Rfq {}
+
+impl IsInitialized for Rfq {
+    fn is_initialized(&self) -> bool {
+        self.buyer != Pubkey::default()
+    }
+}
+
+impl Pack for Rfq {
+    const LEN: usize = 152;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 152];
+        let (buyer, token_mint, price_mint, amount, deadline, nonce, accepted_quote) =
+            array_refs![src, 32, 32, 32, 8, 8, 8, 32];
+
+        Ok(Rfq {
+            buyer: Pubkey::new_from_array(*buyer),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            amount: u64::from_le_bytes(*amount),
+            deadline: i64::from_le_bytes(*deadline),
+            nonce: u64::from_le_bytes(*nonce),
+            accepted_quote: Pubkey::new_from_array(*accepted_quote),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 152];
+        let (buyer_dst, token_mint_dst, price_mint_dst, amount_dst, deadline_dst, nonce_dst, accepted_quote_dst) =
+            mut_array_refs![dst, 32, 32, 32, 8, 8, 8, 32];
+        buyer_dst.copy_from_slice(self.buyer.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        *amount_dst = self.amount.to_le_bytes();
+        *deadline_dst = self.deadline.to_le_bytes();
+        *nonce_dst = self.nonce.to_le_bytes();
+        accepted_quote_dst.copy_from_slice(self.accepted_quote.as_ref());
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Quote {
+    // RFQ this quote responds to
+    pub rfq: Pubkey,
+    // Who offers the tokens. Seller's tokens are escrowed in the order wallet of the seller
+    // (ATA of seller's order wallet authority for token_mint)
+    pub seller: Pubkey,
+    // Token offered (same as in RFQ)
+    pub token_mint: Pubkey,
+    // Token for payment (same as in RFQ)
+    pub price_mint: Pubkey,
+    // How much tokens are escrowed (same as in RFQ)
+    pub amount: u64,
+    // How much price-tokens seller wants to get for the whole amount
+    pub price_amount: u64,
+    // Deadline of the RFQ at quote creation. Quote can be reclaimed after it even if buyer recreates
+    // the RFQ at the same address
+    pub rfq_deadline: i64,
+}
+
+impl Sealed for Quote {}
+
+impl IsInitialized for Quote {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Pack for Quote {
+    const LEN: usize = 152;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 152];
+        let (rfq, seller, token_mint, price_mint, amount, price_amount, rfq_deadline) =
+            array_refs![src, 32, 32, 32, 32, 8, 8, 8];
+
+        Ok(Quote {
+            rfq: Pubkey::new_from_array(*rfq),
+            seller: Pubkey::new_from_array(*seller),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            amount: u64::from_le_bytes(*amount),
+            price_amount: u64::from_le_bytes(*price_amount),
+            rfq_deadline: i64::from_le_bytes(*rfq_deadline),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 152];
+        let (rfq_dst, seller_dst, token_mint_dst, price_mint_dst, amount_dst, price_amount_dst, rfq_deadline_dst) =
+            mut_array_refs![dst, 32, 32, 32, 32, 8, 8, 8];
+        rfq_dst.copy_from_slice(self.rfq.as_ref());
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        *amount_dst = self.amount.to_le_bytes();
+        *price_amount_dst = self.price_amount.to_le_bytes();
+        *rfq_deadline_dst = self.rfq_deadline.to_le_bytes();
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Auction {
+    // Who sells tokens. Tokens are escrowed in the order wallet of the seller
+    pub seller: Pubkey,
+    // Token to sell
+    pub token_mint: Pubkey,
+    // Token for bids
+    pub price_mint: Pubkey,
+    // How much tokens are sold
+    pub amount: u64,
+    // Minimum first bid
+    pub min_bid: u64,
+    // Every next bid should exceed the highest bid at least by this amount
+    pub min_increment: u64,
+    // Bids are accepted before this slot
+    pub end_slot: u64,
+    // Bid placed less than extension_slots before the end moves the end to extension_slots after the bid
+    pub extension_slots: u64,
+    // Seed of the auction address chosen by seller
+    pub nonce: u64,
+    // Bidder of the highest bid (escrowed in bidder's order wallet). Pubkey::default() if there are no bids
+    pub highest_bidder: Pubkey,
+    // The highest bid
+    pub highest_bid: u64,
+}
+
+impl Sealed for Auction {}
+
+impl IsInitialized for Auction {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Auction {
+    // Minimum amount of the next bid
+    pub fn next_min_bid(&self) -> Option<u64> {
+        if self.highest_bidder == Pubkey::default() {
+            Some(self.min_bid)
+        } else {
+            self.highest_bid.checked_add(self.min_increment)
+        }
+    }
+
+    // End slot after a bid placed at given slot (anti-sniping extension)
+    pub fn extended_end_slot(&self, slot: u64) -> u64 {
+        self.end_slot.max(slot.saturating_add(self.extension_slots))
+    }
+}
+
+impl Pack for Auction {
+    const LEN: usize = 184;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 184];
+        let (seller, token_mint, price_mint, amount, min_bid, min_increment,
+            end_slot, extension_slots, nonce, highest_bidder, highest_bid) =
+            array_refs![src, 32, 32, 32, 8, 8, 8, 8, 8, 8, 32, 8];
+
+        Ok(Auction {
+            seller: Pubkey::new_from_array(*seller),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            amount: u64::from_le_bytes(*amount),
+            min_bid: u64::from_le_bytes(*min_bid),
+            min_increment: u64::from_le_bytes(*min_increment),
+            end_slot: u64::from_le_bytes(*end_slot),
+            extension_slots: u64::from_le_bytes(*extension_slots),
+            nonce: u64::from_le_bytes(*nonce),
+            highest_bidder: Pubkey::new_from_array(*highest_bidder),
+            highest_bid: u64::from_le_bytes(*highest_bid),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 184];
+        let (
+            seller_dst,
+            token_mint_dst,
+            price_mint_dst,
+            amount_dst,
+            min_bid_dst,
+            min_increment_dst,
+            end_slot_dst,
+            extension_slots_dst,
+            nonce_dst,
+            highest_bidder_dst,
+            highest_bid_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 8, 8, 8, 8, 8, 8, 32, 8];
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        *amount_dst = self.amount.to_le_bytes();
+        *min_bid_dst = self.min_bid.to_le_bytes();
+        *min_increment_dst = self.min_increment.to_le_bytes();
+        *end_slot_dst = self.end_slot.to_le_bytes();
+        *extension_slots_dst = self.extension_slots.to_le_bytes();
+        *nonce_dst = self.nonce.to_le_bytes();
+        highest_bidder_dst.copy_from_slice(self.highest_bidder.as_ref());
+        *highest_bid_dst = self.highest_bid.to_le_bytes();
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct SealedAuction {
+    // Who sells tokens. Tokens are escrowed in the order wallet of the seller
+    pub seller: Pubkey,
+    // Token to sell
+    pub token_mint: Pubkey,
+    // Token for bids
+    pub price_mint: Pubkey,
+    // How much tokens are sold
+    pub amount: u64,
+    // Minimum bid to be accepted on reveal
+    pub min_bid: u64,
+    // Minimum deposit escrowed with a commitment. Revealed bid can't exceed the deposit
+    pub min_deposit: u64,
+    // Commitments are accepted before this slot
+    pub commit_end_slot: u64,
+    // Bids are revealed from commit_end_slot until this slot
+    pub reveal_end_slot: u64,
+    // Deposits of bids which were not revealed go to seller (otherwise they are refunded)
+    pub forfeit_unrevealed: bool,
+    // Seed of the auction address chosen by seller
+    pub nonce: u64,
+    // Number of sealed bids not withdrawn yet. Auction account is closed when it's settled and all bids are withdrawn
+    pub bid_count: u32,
+    // Bidder of the highest revealed bid. Pubkey::default() if there are no revealed bids
+    pub highest_bidder: Pubkey,
+    // The highest revealed bid
+    pub highest_bid: u64,
+    // Tokens and the highest bid are transferred
+    pub is_settled: bool,
+}
+
+impl Sealed for SealedAuction {}
+
+impl IsInitialized for SealedAuction {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Pack for SealedAuction {
+    const LEN: usize = 190;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 190];
+        let (seller, token_mint, price_mint, amount, min_bid, min_deposit, commit_end_slot, reveal_end_slot,
+            forfeit_unrevealed, nonce, bid_count, highest_bidder, highest_bid, is_settled) =
+            array_refs![src, 32, 32, 32, 8, 8, 8, 8, 8, 1, 8, 4, 32, 8, 1];
+        let forfeit_unrevealed = match forfeit_unrevealed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        let is_settled = match is_settled {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(SealedAuction {
+            seller: Pubkey::new_from_array(*seller),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            price_mint: Pubkey::new_from_array(*price_mint),
+            amount: u64::from_le_bytes(*amount),
+            min_bid: u64::from_le_bytes(*min_bid),
+            min_deposit: u64::from_le_bytes(*min_deposit),
+            commit_end_slot: u64::from_le_bytes(*commit_end_slot),
+            reveal_end_slot: u64::from_le_bytes(*reveal_end_slot),
+            forfeit_unrevealed,
+            nonce: u64::from_le_bytes(*nonce),
+            bid_count: u32::from_le_bytes(*bid_count),
+            highest_bidder: Pubkey::new_from_array(*highest_bidder),
+            highest_bid: u64::from_le_bytes(*highest_bid),
+            is_settled,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 190];
+        let (
+            seller_dst,
+            token_mint_dst,
+            price_mint_dst,
+            amount_dst,
+            min_bid_dst,
+            min_deposit_dst,
+            commit_end_slot_dst,
+            reveal_end_slot_dst,
+            forfeit_unrevealed_dst,
+            nonce_dst,
+            bid_count_dst,
+            highest_bidder_dst,
+            highest_bid_dst,
+            is_settled_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 8, 8, 8, 8, 8, 1, 8, 4, 32, 8, 1];
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        price_mint_dst.copy_from_slice(self.price_mint.as_ref());
+        *amount_dst = self.amount.to_le_bytes();
+        *min_bid_dst = self.min_bid.to_le_bytes();
+        *min_deposit_dst = self.min_deposit.to_le_bytes();
+        *commit_end_slot_dst = self.commit_end_slot.to_le_bytes();
+        *reveal_end_slot_dst = self.reveal_end_slot.to_le_bytes();
+        forfeit_unrevealed_dst[0] = self.forfeit_unrevealed as u8;
+        *nonce_dst = self.nonce.to_le_bytes();
+        *bid_count_dst = self.bid_count.to_le_bytes();
+        highest_bidder_dst.copy_from_slice(self.highest_bidder.as_ref());
+        *highest_bid_dst = self.highest_bid.to_le_bytes();
+        is_settled_dst[0] = self.is_settled as u8;
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct SealedBid {
+    // Sealed-bid auction
+    pub auction: Pubkey,
+    // Who bids. Deposit is escrowed in the order wallet of the bidder
+    pub bidder: Pubkey,
+    // get_bid_commitment(bidder, bid, salt)
+    pub commitment: [u8; 32],
+    // Escrowed amount of price token
+    pub deposit: u64,
+    // Bid is revealed
+    pub is_revealed: bool,
+    // Revealed bid (0 until revealed)
+    pub bid: u64,
+}
+
+impl Sealed for SealedBid {}
+
+impl IsInitialized for SealedBid {
+    fn is_initialized(&self) -> bool {
+        self.bidder != Pubkey::default()
+    }
+}
+
+impl Pack for SealedBid {
+    const LEN: usize = 113;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 113];
+        let (auction, bidder, commitment, deposit, is_revealed, bid) =
+            array_refs![src, 32, 32, 32, 8, 1, 8];
+        let is_revealed = match is_revealed {
+            [0] => false,
+            [1] => true,
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+
+        Ok(SealedBid {
+            auction: Pubkey::new_from_array(*auction),
+            bidder: Pubkey::new_from_array(*bidder),
+            commitment: *commitment,
+            deposit: u64::from_le_bytes(*deposit),
+            is_revealed,
+            bid: u64::from_le_bytes(*bid),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 113];
+        let (
+            auction_dst,
+            bidder_dst,
+            commitment_dst,
+            deposit_dst,
+            is_revealed_dst,
+            bid_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 8, 1, 8];
+        auction_dst.copy_from_slice(self.auction.as_ref());
+        bidder_dst.copy_from_slice(self.bidder.as_ref());
+        *commitment_dst = self.commitment;
+        *deposit_dst = self.deposit.to_le_bytes();
+        is_revealed_dst[0] = self.is_revealed as u8;
+        *bid_dst = self.bid.to_le_bytes();
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct Flip {
+    // Order which fills fund the reverse order
+    pub order: Pubkey,
+    // Order of the same seller which sells price token of the order for its token. Price tokens
+    // collected by fills of the order are added to this order instead of being paid out
+    pub reverse_order: Pubkey,
+    // Reverse order price is better for seller than the order price by this spread
+    pub spread_bps: u16,
+}
+
+impl Sealed for Flip {}
+
+impl IsInitialized for Flip {
+    fn is_initialized(&self) -> bool {
+        self.order != Pubkey::default()
+    }
+}
+
+impl Pack for Flip {
+    const LEN: usize = 66;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 66];
+        let (order, reverse_order, spread_bps) = array_refs![src, 32, 32, 2];
+
+        Ok(Flip {
+            order: Pubkey::new_from_array(*order),
+            reverse_order: Pubkey::new_from_array(*reverse_order),
+            spread_bps: u16::from_le_bytes(*spread_bps),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 66];
+        let (order_dst, reverse_order_dst, spread_bps_dst) = mut_array_refs![dst, 32, 32, 2];
+        order_dst.copy_from_slice(self.order.as_ref());
+        reverse_order_dst.copy_from_slice(self.reverse_order.as_ref());
+        *spread_bps_dst = self.spread_bps.to_le_bytes();
+    }
+}
+
+// How long arbitrator of the trade alone can resolve a dispute (seconds). After that default arbitrator
+// from arbitration config can resolve it too
+pub const DISPUTE_RESOLUTION_PERIOD: i64 = 14 * 24 * 60 * 60;
+
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FiatTradeStatus {
+    // Waiting for buyer to send fiat payment
+    Open = 0,
+    // Buyer marked fiat payment as sent, waiting for seller to release tokens
+    PaymentSent = 1,
+    // One of the parties raised a dispute. Only arbitrator can move escrowed tokens
+    Disputed = 2,
+}
+
+impl FiatTradeStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(FiatTradeStatus::Open),
+            1 => Some(FiatTradeStatus::PaymentSent),
+            2 => Some(FiatTradeStatus::Disputed),
+            _ => None,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct FiatTrade {
+    // Who sells tokens for fiat. Tokens are escrowed in the order wallet of the seller
+    pub seller: Pubkey,
+    // The only buyer who can get the tokens
+    pub buyer: Pubkey,
+    // Token to sell
+    pub token_mint: Pubkey,
+    // How much tokens are sold
+    pub amount: u64,
+    // Fiat payment expected by seller (in minor units of the currency, e.g. cents)
+    pub fiat_amount: u64,
+    // ISO 4217 code of the fiat currency
+    pub fiat_currency: [u8; 3],
+    // Buyer should mark payment as sent before this unix timestamp, otherwise seller can cancel the trade
+    pub payment_deadline: i64,
+    // Seed of the trade address chosen by seller
+    pub nonce: u64,
+    pub status: FiatTradeStatus,
+    // Who resolves disputes of the trade. Pubkey::default() if disputes are not possible
+    pub arbitrator: Pubkey,
+    // Maximum share of escrowed tokens arbitrator can take for resolving a dispute
+    pub arbitration_fee_bps: u16,
+    // If arbitrator did not resolve the dispute before this unix timestamp, default arbitrator can resolve it
+    // too. 0 if the trade is not disputed
+    pub dispute_deadline: i64,
+}
+
+impl Sealed for FiatTrade {}
+
+impl IsInitialized for FiatTrade {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Pack for FiatTrade {
+    const LEN: usize = 174;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 174];
+        let (seller, buyer, token_mint, amount, fiat_amount, fiat_currency, payment_deadline, nonce, status,
+            arbitrator, arbitration_fee_bps, dispute_deadline) =
+            array_refs![src, 32, 32, 32, 8, 8, 3, 8, 8, 1, 32, 2, 8];
+
+        Ok(FiatTrade {
+            seller: Pubkey::new_from_array(*seller),
+            buyer: Pubkey::new_from_array(*buyer),
+            token_mint: Pubkey::new_from_array(*token_mint),
+            amount: u64::from_le_bytes(*amount),
+            fiat_amount: u64::from_le_bytes(*fiat_amount),
+            fiat_currency: *fiat_currency,
+            payment_deadline: i64::from_le_bytes(*payment_deadline),
+            nonce: u64::from_le_bytes(*nonce),
+            status: FiatTradeStatus::from_u8(status[0]).ok_or(ProgramError::InvalidAccountData)?,
+            arbitrator: Pubkey::new_from_array(*arbitrator),
+            arbitration_fee_bps: u16::from_le_bytes(*arbitration_fee_bps),
+            dispute_deadline: i64::from_le_bytes(*dispute_deadline),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 174];
+        let (
+            seller_dst,
+            buyer_dst,
+            token_mint_dst,
+            amount_dst,
+            fiat_amount_dst,
+            fiat_currency_dst,
+            payment_deadline_dst,
+            nonce_dst,
+            status_dst,
+            arbitrator_dst,
+            arbitration_fee_bps_dst,
+            dispute_deadline_dst,
+        ) = mut_array_refs![dst, 32, 32, 32, 8, 8, 3, 8, 8, 1, 32, 2, 8];
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        buyer_dst.copy_from_slice(self.buyer.as_ref());
+        token_mint_dst.copy_from_slice(self.token_mint.as_ref());
+        *amount_dst = self.amount.to_le_bytes();
+        *fiat_amount_dst = self.fiat_amount.to_le_bytes();
+        *fiat_currency_dst = self.fiat_currency;
+        *payment_deadline_dst = self.payment_deadline.to_le_bytes();
+        *nonce_dst = self.nonce.to_le_bytes();
+        status_dst[0] = self.status as u8;
+        arbitrator_dst.copy_from_slice(self.arbitrator.as_ref());
+        *arbitration_fee_bps_dst = self.arbitration_fee_bps.to_le_bytes();
+        *dispute_deadline_dst = self.dispute_deadline.to_le_bytes();
+    }
+}
+
+// Events of fiat trades are logged with sol_log_data as [event, trade, actor] where actor is
+// the signer of the transition. DisputeResolved additionally has [winner, fee (u64)]
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FiatTradeEvent {
+    Created = 1,
+    PaymentSent = 2,
+    Released = 3,
+    Cancelled = 4,
+    DisputeRaised = 5,
+    DisputeResolved = 6,
+}
+
+impl FiatTradeEvent {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(FiatTradeEvent::Created),
+            2 => Some(FiatTradeEvent::PaymentSent),
+            3 => Some(FiatTradeEvent::Released),
+            4 => Some(FiatTradeEvent::Cancelled),
+            5 => Some(FiatTradeEvent::DisputeRaised),
+            6 => Some(FiatTradeEvent::DisputeResolved),
+            _ => None,
+        }
+    }
+}
+
+// Default arbitrator of new fiat trades, set by admin
+#[repr(C)]
+#[derive(Debug)]
+pub struct ArbitrationConfig {
+    pub arbitrator: Pubkey,
+    // Maximum share of escrowed tokens arbitrator can take for resolving a dispute
+    pub fee_bps: u16,
+}
+
+impl Sealed for ArbitrationConfig {}
+
+impl IsInitialized for ArbitrationConfig {
+    fn is_initialized(&self) -> bool {
+        self.arbitrator != Pubkey::default()
+    }
+}
+
+impl Pack for ArbitrationConfig {
+    const LEN: usize = 34;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 34];
+        let (arbitrator, fee_bps) = array_refs![src, 32, 2];
+
+        Ok(ArbitrationConfig {
+            arbitrator: Pubkey::new_from_array(*arbitrator),
+            fee_bps: u16::from_le_bytes(*fee_bps),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 34];
+        let (arbitrator_dst, fee_bps_dst) = mut_array_refs![dst, 32, 2];
+        arbitrator_dst.copy_from_slice(self.arbitrator.as_ref());
+        *fee_bps_dst = self.fee_bps.to_le_bytes();
+    }
+}
+
+// Trading history of a wallet. Once created it's updated by every fill, revoke and
+// dispute resolution involving the wallet
+#[repr(C)]
+#[derive(Debug)]
+pub struct Reputation {
+    pub wallet: Pubkey,
+    // Number of completed trades as seller or buyer. Volumes of different mints aren't comparable,
+    // so only trades are counted
+    pub trade_count: u64,
+    // How many times the wallet revoked its orders
+    pub revoke_count: u32,
+    pub disputes_lost: u32,
+    // Slot of the first trade recorded. 0 if there are no trades yet
+    pub first_trade_slot: u64,
+    // Sum and count of ratings left by counterparties (1..=5 each)
+    pub rating_sum: u64,
+    pub rating_count: u32,
+}
+
+impl Reputation {
+    pub fn record_trade(&mut self, slot: u64) {
+        if self.trade_count == 0 {
+            self.first_trade_slot = slot;
+        }
+        self.trade_count = self.trade_count.saturating_add(1);
+    }
+
+    pub fn record_rating(&mut self, rating: u8) {
+        self.rating_sum = self.rating_sum.saturating_add(rating as u64);
+        self.rating_count = self.rating_count.saturating_add(1);
+    }
+}
+
+impl Sealed for Reputation {}
+
+impl IsInitialized for Reputation {
+    fn is_initialized(&self) -> bool {
+        self.wallet != Pubkey::default()
+    }
+}
+
+impl Pack for Reputation {
+    const LEN: usize = 68;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 68];
+        let (wallet, trade_count, revoke_count, disputes_lost, first_trade_slot, rating_sum, rating_count) =
+            array_refs![src, 32, 8, 4, 4, 8, 8, 4];
+
+        Ok(Reputation {
+            wallet: Pubkey::new_from_array(*wallet),
+            trade_count: u64::from_le_bytes(*trade_count),
+            revoke_count: u32::from_le_bytes(*revoke_count),
+            disputes_lost: u32::from_le_bytes(*disputes_lost),
+            first_trade_slot: u64::from_le_bytes(*first_trade_slot),
+            rating_sum: u64::from_le_bytes(*rating_sum),
+            rating_count: u32::from_le_bytes(*rating_count),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 68];
+        let (
+            wallet_dst,
+            trade_count_dst,
+            revoke_count_dst,
+            disputes_lost_dst,
+            first_trade_slot_dst,
+            rating_sum_dst,
+            rating_count_dst,
+        ) = mut_array_refs![dst, 32, 8, 4, 4, 8, 8, 4];
+        wallet_dst.copy_from_slice(self.wallet.as_ref());
+        *trade_count_dst = self.trade_count.to_le_bytes();
+        *revoke_count_dst = self.revoke_count.to_le_bytes();
+        *disputes_lost_dst = self.disputes_lost.to_le_bytes();
+        *first_trade_slot_dst = self.first_trade_slot.to_le_bytes();
+        *rating_sum_dst = self.rating_sum.to_le_bytes();
+        *rating_count_dst = self.rating_count.to_le_bytes();
+    }
+}
+
+// Proof of a trade between two wallets with reputation. Each side can rate the other once.
+// Repeated fills of the order by the same buyer share the receipt
+#[repr(C)]
+#[derive(Debug)]
+pub struct TradeReceipt {
+    pub order: Pubkey,
+    pub seller: Pubkey,
+    // Buyer paid rent for the receipt and gets it back when both sides rated the trade
+    pub buyer: Pubkey,
+    // Filled amount in raw units of order token
+    pub volume: u64,
+    pub is_rated_by_seller: bool,
+    pub is_rated_by_buyer: bool,
+}
+
+impl Sealed for TradeReceipt {}
+
+impl IsInitialized for TradeReceipt {
+    fn is_initialized(&self) -> bool {
+        self.order != Pubkey::default()
+    }
+}
+
+impl Pack for TradeReceipt {
+    const LEN: usize = 106;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 106];
+        let (order, seller, buyer, volume, is_rated_by_seller, is_rated_by_buyer) =
+            array_refs![src, 32, 32, 32, 8, 1, 1];
+
+        Ok(TradeReceipt {
+            order: Pubkey::new_from_array(*order),
+            seller: Pubkey::new_from_array(*seller),
+            buyer: Pubkey::new_from_array(*buyer),
+            volume: u64::from_le_bytes(*volume),
+            is_rated_by_seller: match is_rated_by_seller {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+            is_rated_by_buyer: match is_rated_by_buyer {
+                [0] => false,
+                [1] => true,
+                _ => return Err(ProgramError::InvalidAccountData),
+            },
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 106];
+        let (order_dst, seller_dst, buyer_dst, volume_dst, is_rated_by_seller_dst, is_rated_by_buyer_dst) =
+            mut_array_refs![dst, 32, 32, 32, 8, 1, 1];
+        order_dst.copy_from_slice(self.order.as_ref());
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        buyer_dst.copy_from_slice(self.buyer.as_ref());
+        *volume_dst = self.volume.to_le_bytes();
+        is_rated_by_seller_dst[0] = self.is_rated_by_seller as u8;
+        is_rated_by_buyer_dst[0] = self.is_rated_by_buyer as u8;
+    }
+}
+
+// Operator permissions (bit flags of Delegate::permissions)
+pub const PERMISSION_AMEND: u8 = 1;
+pub const PERMISSION_PAUSE: u8 = 2;
+pub const PERMISSION_REVOKE: u8 = 4;
+pub const PERMISSION_ALL: u8 = PERMISSION_AMEND | PERMISSION_PAUSE | PERMISSION_REVOKE;
+
+// Operator key which manages orders on behalf of the seller. Tokens and payments of the orders
+// still always go to the seller
+#[repr(C)]
+#[derive(Debug)]
+pub struct Delegate {
+    pub seller: Pubkey,
+    pub operator: Pubkey,
+    // Combination of PERMISSION_* flags
+    pub permissions: u8,
+}
+
+impl Delegate {
+    pub fn has_permission(&self, permission: u8) -> bool {
+        self.permissions & permission == permission
+    }
+}
+
+impl Sealed for Delegate {}
+
+impl IsInitialized for Delegate {
+    fn is_initialized(&self) -> bool {
+        self.seller != Pubkey::default()
+    }
+}
+
+impl Pack for Delegate {
+    const LEN: usize = 65;
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, 65];
+        let (seller, operator, permissions) = array_refs![src, 32, 32, 1];
+
+        Ok(Delegate {
+            seller: Pubkey::new_from_array(*seller),
+            operator: Pubkey::new_from_array(*operator),
+            permissions: permissions[0],
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, 65];
+        let (seller_dst, operator_dst, permissions_dst) = mut_array_refs![dst, 32, 32, 1];
+        seller_dst.copy_from_slice(self.seller.as_ref());
+        operator_dst.copy_from_slice(self.operator.as_ref());
+        permissions_dst[0] = self.permissions;
+    }
+}
+
+#[repr(u8)]
+pub enum P2PSwapInstructions {
+    Undefined = 0,
+    CreatePublicOrder = 1,
+    CreatePrivateOrder = 2,
+    RevokeOrder = 3,
+    FillOrder = 4,
+    CreateMarket = 5,
+    InitConfig = 6,
+    SetPaused = 7,
+    SetAdmin = 8,
+    SetMintListMode = 9,
+    SetMintStatus = 10,
+    PauseOrder = 11,
+    ResumeOrder = 12,
+    CreateProposal = 13,
+    WithdrawProposal = 14,
+    AcceptProposal = 15,
+    CreateDeal = 16,
+    DepositDeal = 17,
+    SettleDeal = 18,
+    ReclaimDeal = 19,
+    FillOrderExactSpend = 20,
+    SetPayoutSplit = 21,
+    CreateRfq = 22,
+    CreateQuote = 23,
+    AcceptQuote = 24,
+    ReclaimQuote = 25,
+    CloseRfq = 26,
+    CreateAuction = 27,
+    PlaceBid = 28,
+    SettleAuction = 29,
+    CreateSealedAuction = 30,
+    CommitBid = 31,
+    RevealBid = 32,
+    SettleSealedAuction = 33,
+    WithdrawSealedBid = 34,
+    SetFlip = 35,
+    CreateFiatTrade = 36,
+    MarkFiatSent = 37,
+    ReleaseFiatTrade = 38,
+    CancelFiatTrade = 39,
+    SetArbitrationConfig = 40,
+    RaiseDispute = 41,
+    ResolveDispute = 42,
+    CreateReputation = 43,
+    RateTrade = 44,
+    SetDelegate = 45,
+    BumpEpoch = 46,
+    UpdateMarket = 47,
+    ListOrder = 48,
+    MigrateOrder = 49,
+}
+
+impl P2PSwapInstructions {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => P2PSwapInstructions::CreatePublicOrder,
+            2 => P2PSwapInstructions::CreatePrivateOrder,
+            3 => P2PSwapInstructions::RevokeOrder,
+            4 => P2PSwapInstructions::FillOrder,
+            5 => P2PSwapInstructions::CreateMarket,
+            6 => P2PSwapInstructions::InitConfig,
+            7 => P2PSwapInstructions::SetPaused,
+            8 => P2PSwapInstructions::SetAdmin,
+            9 => P2PSwapInstructions::SetMintListMode,
+            10 => P2PSwapInstructions::SetMintStatus,
+            11 => P2PSwapInstructions::PauseOrder,
+            12 => P2PSwapInstructions::ResumeOrder,
+            13 => P2PSwapInstructions::CreateProposal,
+            14 => P2PSwapInstructions::WithdrawProposal,
+            15 => P2PSwapInstructions::AcceptProposal,
+            16 => P2PSwapInstructions::CreateDeal,
+            17 => P2PSwapInstructions::DepositDeal,
+            18 => P2PSwapInstructions::SettleDeal,
+            19 => P2PSwapInstructions::ReclaimDeal,
+            20 => P2PSwapInstructions::FillOrderExactSpend,
+            21 => P2PSwapInstructions::SetPayoutSplit,
+            22 => P2PSwapInstructions::CreateRfq,
+            23 => P2PSwapInstructions::CreateQuote,
+            24 => P2PSwapInstructions::AcceptQuote,
+            25 => P2PSwapInstructions::ReclaimQuote,
+            26 => P2PSwapInstructions::CloseRfq,
+            27 => P2PSwapInstructions::CreateAuction,
+            28 => P2PSwapInstructions::PlaceBid,
+            29 => P2PSwapInstructions::SettleAuction,
+            30 => P2PSwapInstructions::CreateSealedAuction,
+            31 => P2PSwapInstructions::CommitBid,
+            32 => P2PSwapInstructions::RevealBid,
+            33 => P2PSwapInstructions::SettleSealedAuction,
+            34 => P2PSwapInstructions::WithdrawSealedBid,
+            35 => P2PSwapInstructions::SetFlip,
+            36 => P2PSwapInstructions::CreateFiatTrade,
+            37 => P2PSwapInstructions::MarkFiatSent,
+            38 => P2PSwapInstructions::ReleaseFiatTrade,
+            39 => P2PSwapInstructions::CancelFiatTrade,
+            40 => P2PSwapInstructions::SetArbitrationConfig,
+            41 => P2PSwapInstructions::RaiseDispute,
+            42 => P2PSwapInstructions::ResolveDispute,
+            43 => P2PSwapInstructions::CreateReputation,
+            44 => P2PSwapInstructions::RateTrade,
+            45 => P2PSwapInstructions::SetDelegate,
+            46 => P2PSwapInstructions::BumpEpoch,
+            47 => P2PSwapInstructions::UpdateMarket,
+            48 => P2PSwapInstructions::ListOrder,
+            49 => P2PSwapInstructions::MigrateOrder,
+            _ => P2PSwapInstructions::Undefined,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum P2PSwapError {
     OrderExists = 1,
-    CreationSlotToFar = 2,
     UnlockInstructionNotFound = 3,
     UnlockInstructionInvalid = 4,
     BuyAmountBelowMinimum = 5,
     NotEnoughTokensInOrder = 6,
     IntOverflowError = 7,
+    OrderIndexMismatch = 8,
+    MarketExists = 9,
+    InvalidLotSize = 10,
+    InvalidTickSize = 11,
+    OrderBelowMinimumSize = 12,
+    OrderBookFull = 13,
+    ProgramPaused = 14,
+    NotAdmin = 15,
+    ConfigExists = 16,
+    MintNotAllowed = 17,
+    OrderPaused = 18,
+    OrderNotStarted = 19,
+    ProposalExists = 20,
+    DealExists = 21,
+    DealExpired = 22,
+    DealNotExpired = 23,
+    DealNotFunded = 24,
+    AlreadyDeposited = 25,
+    DealFunded = 26,
+    ZeroPayment = 27,
+    InvalidPayoutSplit = 28,
+    RfqExists = 29,
+    RfqExpired = 30,
+    RfqActive = 31,
+    RfqSettled = 32,
+    QuoteExists = 33,
+    AuctionExists = 34,
+    AuctionEnded = 35,
+    AuctionNotEnded = 36,
+    BidTooLow = 37,
+    BidExists = 38,
+    NotRevealPhase = 39,
+    CommitmentMismatch = 40,
+    BidExceedsDeposit = 41,
+    AuctionSettled = 42,
+    AuctionNotSettled = 43,
+    FiatTradeExists = 44,
+    PaymentWindowExpired = 45,
+    PaymentWindowActive = 46,
+    PaymentAlreadySent = 47,
+    NoArbitrator = 48,
+    TradeDisputed = 49,
+    NotArbitrator = 50,
+    ReputationExists = 51,
+    AlreadyRated = 52,
+    OperatorNotAllowed = 53,
+    OrderEpochExpired = 54,
+    GateTokenRequired = 55,
+    FlipPayoutConflict = 56,
 }
\ No newline at end of file
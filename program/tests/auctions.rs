@@ -0,0 +1,376 @@
+mod common;
+
+use {
+    common::*,
+    p2p_swap::{
+        get_auction_address, get_bid_commitment, get_sealed_auction_address, get_sealed_bid_address, Auction,
+        P2PSwapError, P2PSwapInstructions, SealedAuction, SealedBid,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        instruction::AccountMeta,
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+    },
+    spl_associated_token_account::get_associated_token_address,
+};
+
+const NONCE: u64 = 1;
+
+async fn create_auction(
+    env: &mut TestEnv,
+    pair: &Pair,
+    amount: u64,
+    min_bid: u64,
+    min_increment: u64,
+    end_slot: u64,
+    extension_slots: u64,
+) -> Pubkey {
+    let seller = pair.seller.pubkey();
+    let (escrow_authority, escrow_wallet) = env.escrow(&seller, &pair.token_mint);
+    let (auction, _) = get_auction_address(&env.program_id, &seller, NONCE);
+    let accounts = vec![
+        AccountMeta::new(seller, true),
+        AccountMeta::new(get_associated_token_address(&seller, &pair.token_mint), false),
+        AccountMeta::new_readonly(pair.token_mint, false),
+        AccountMeta::new_readonly(pair.price_mint, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(auction, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    let data = [amount, min_bid, min_increment, end_slot, extension_slots, NONCE]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect::<Vec<u8>>();
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &pair.token_mint),
+        instruction(&env.program_id, P2PSwapInstructions::CreateAuction, &data, accounts),
+    ];
+    env.process(&instructions, &[&pair.seller]).await.unwrap();
+    auction
+}
+
+async fn place_bid(env: &mut TestEnv, bidder: &Keypair, auction_address: &Pubkey, bid: u64) -> Result<(), BanksClientError> {
+    let auction = env.get::<Auction>(auction_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&bidder.pubkey(), &auction.price_mint);
+    // refund accounts of the previous bidder are ignored for the first bid
+    let previous_bidder = if auction.highest_bidder == Pubkey::default() {
+        bidder.pubkey()
+    } else {
+        auction.highest_bidder
+    };
+    let (previous_escrow_authority, previous_escrow_wallet) = env.escrow(&previous_bidder, &auction.price_mint);
+    let accounts = vec![
+        AccountMeta::new(bidder.pubkey(), true),
+        AccountMeta::new(*auction_address, false),
+        AccountMeta::new(get_associated_token_address(&bidder.pubkey(), &auction.price_mint), false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(previous_escrow_authority, false),
+        AccountMeta::new(previous_escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&previous_bidder, &auction.price_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+        AccountMeta::new_readonly(previous_bidder, false),
+        AccountMeta::new_readonly(auction.price_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &auction.price_mint),
+        instruction(&env.program_id, P2PSwapInstructions::PlaceBid, &bid.to_le_bytes(), accounts),
+    ];
+    env.process(&instructions, &[bidder]).await
+}
+
+async fn settle_auction(env: &mut TestEnv, auction_address: &Pubkey) -> Result<(), BanksClientError> {
+    let auction = env.get::<Auction>(auction_address).await;
+    let winner = if auction.highest_bidder == Pubkey::default() {
+        auction.seller
+    } else {
+        auction.highest_bidder
+    };
+    let (seller_escrow_authority, seller_escrow_wallet) = env.escrow(&auction.seller, &auction.token_mint);
+    let (winner_escrow_authority, winner_escrow_wallet) = env.escrow(&winner, &auction.price_mint);
+    let mut accounts = vec![
+        AccountMeta::new(auction.seller, false),
+        AccountMeta::new(*auction_address, false),
+        AccountMeta::new_readonly(seller_escrow_authority, false),
+        AccountMeta::new(seller_escrow_wallet, false),
+        AccountMeta::new_readonly(winner_escrow_authority, false),
+        AccountMeta::new(winner_escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&winner, &auction.token_mint), false),
+        AccountMeta::new(get_associated_token_address(&auction.seller, &auction.price_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(env.payer(), true),
+        AccountMeta::new_readonly(winner, false),
+        AccountMeta::new_readonly(auction.token_mint, false),
+        AccountMeta::new_readonly(auction.price_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    accounts.extend(env.reputation_accounts(&auction.seller, &winner));
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::SettleAuction, &[], accounts);
+    env.process(&[instruction], &[]).await
+}
+
+async fn create_sealed_auction(
+    env: &mut TestEnv,
+    pair: &Pair,
+    amount: u64,
+    min_bid: u64,
+    min_deposit: u64,
+    commit_end_slot: u64,
+    reveal_end_slot: u64,
+) -> Pubkey {
+    let seller = pair.seller.pubkey();
+    let (escrow_authority, escrow_wallet) = env.escrow(&seller, &pair.token_mint);
+    let (auction, _) = get_sealed_auction_address(&env.program_id, &seller, NONCE);
+    let accounts = vec![
+        AccountMeta::new(seller, true),
+        AccountMeta::new(get_associated_token_address(&seller, &pair.token_mint), false),
+        AccountMeta::new_readonly(pair.token_mint, false),
+        AccountMeta::new_readonly(pair.price_mint, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(auction, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    // unrevealed deposits are forfeited to seller
+    let mut data = [amount, min_bid, min_deposit, commit_end_slot, reveal_end_slot, NONCE]
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect::<Vec<u8>>();
+    data.push(1);
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &pair.token_mint),
+        instruction(&env.program_id, P2PSwapInstructions::CreateSealedAuction, &data, accounts),
+    ];
+    env.process(&instructions, &[&pair.seller]).await.unwrap();
+    auction
+}
+
+fn salt(bidder: &Keypair) -> [u8; 32] {
+    bidder.pubkey().to_bytes()
+}
+
+async fn commit_bid(
+    env: &mut TestEnv,
+    bidder: &Keypair,
+    auction_address: &Pubkey,
+    bid: u64,
+    deposit: u64,
+) -> Result<(), BanksClientError> {
+    let auction = env.get::<SealedAuction>(auction_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&bidder.pubkey(), &auction.price_mint);
+    let accounts = vec![
+        AccountMeta::new(bidder.pubkey(), true),
+        AccountMeta::new(*auction_address, false),
+        AccountMeta::new(get_associated_token_address(&bidder.pubkey(), &auction.price_mint), false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(get_sealed_bid_address(&env.program_id, auction_address, &bidder.pubkey()).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    let commitment = get_bid_commitment(&bidder.pubkey(), bid, &salt(bidder));
+    let data = [&commitment[..], &deposit.to_le_bytes()].concat();
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &auction.price_mint),
+        instruction(&env.program_id, P2PSwapInstructions::CommitBid, &data, accounts),
+    ];
+    env.process(&instructions, &[bidder]).await
+}
+
+async fn reveal_bid(
+    env: &mut TestEnv,
+    bidder: &Keypair,
+    auction_address: &Pubkey,
+    bid: u64,
+    salt: &[u8; 32],
+) -> Result<(), BanksClientError> {
+    let accounts = vec![
+        AccountMeta::new(bidder.pubkey(), true),
+        AccountMeta::new(*auction_address, false),
+        AccountMeta::new(get_sealed_bid_address(&env.program_id, auction_address, &bidder.pubkey()).0, false),
+    ];
+    let data = [&bid.to_le_bytes()[..], salt].concat();
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::RevealBid, &data, accounts);
+    env.process(&[instruction], &[bidder]).await
+}
+
+async fn settle_sealed_auction(env: &mut TestEnv, auction_address: &Pubkey) -> Result<(), BanksClientError> {
+    let auction = env.get::<SealedAuction>(auction_address).await;
+    let winner = if auction.highest_bidder == Pubkey::default() {
+        auction.seller
+    } else {
+        auction.highest_bidder
+    };
+    let (seller_escrow_authority, seller_escrow_wallet) = env.escrow(&auction.seller, &auction.token_mint);
+    let (winner_escrow_authority, winner_escrow_wallet) = env.escrow(&winner, &auction.price_mint);
+    let mut accounts = vec![
+        AccountMeta::new(auction.seller, false),
+        AccountMeta::new(*auction_address, false),
+        AccountMeta::new_readonly(seller_escrow_authority, false),
+        AccountMeta::new(seller_escrow_wallet, false),
+        AccountMeta::new(get_sealed_bid_address(&env.program_id, auction_address, &winner).0, false),
+        AccountMeta::new_readonly(winner_escrow_authority, false),
+        AccountMeta::new(winner_escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&winner, &auction.token_mint), false),
+        AccountMeta::new(get_associated_token_address(&auction.seller, &auction.price_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(env.payer(), true),
+        AccountMeta::new_readonly(winner, false),
+        AccountMeta::new_readonly(auction.token_mint, false),
+        AccountMeta::new_readonly(auction.price_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    accounts.extend(env.reputation_accounts(&auction.seller, &winner));
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::SettleSealedAuction, &[], accounts);
+    env.process(&[instruction], &[]).await
+}
+
+async fn withdraw_sealed_bid(
+    env: &mut TestEnv,
+    bidder: &Keypair,
+    auction_address: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let auction = env.get::<SealedAuction>(auction_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&bidder.pubkey(), &auction.price_mint);
+    let accounts = vec![
+        AccountMeta::new(bidder.pubkey(), true),
+        AccountMeta::new(auction.seller, false),
+        AccountMeta::new(*auction_address, false),
+        AccountMeta::new(get_sealed_bid_address(&env.program_id, auction_address, &bidder.pubkey()).0, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&bidder.pubkey(), &auction.price_mint), false),
+        AccountMeta::new(get_associated_token_address(&auction.seller, &auction.price_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::WithdrawSealedBid, &[], accounts);
+    env.process(&[instruction], &[bidder]).await
+}
+
+#[tokio::test]
+async fn test_english_auction() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 1000).await;
+    let bidder = env.create_user().await;
+    env.fund_wallet(&bidder.pubkey(), &pair.price_mint, 1000).await;
+
+    let end_slot = env.clock().await.slot + 100;
+    let auction = create_auction(&mut env, &pair, 100, 50, 10, end_slot, 20).await;
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.token_mint).await, 900);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 100);
+
+    assert_error(place_bid(&mut env, &pair.buyer, &auction, 40).await, P2PSwapError::BidTooLow);
+    place_bid(&mut env, &pair.buyer, &auction, 50).await.unwrap();
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 950);
+    assert_eq!(env.escrow_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 50);
+
+    // next bid should exceed the highest one by min increment, outbid bidder is refunded
+    assert_error(place_bid(&mut env, &bidder, &auction, 55).await, P2PSwapError::BidTooLow);
+    place_bid(&mut env, &bidder, &auction, 60).await.unwrap();
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 1000);
+    assert_eq!(env.escrow_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 0);
+    assert_eq!(env.escrow_balance(&bidder.pubkey(), &pair.price_mint).await, 60);
+    assert_eq!(env.get::<Auction>(&auction).await.end_slot, end_slot);
+
+    assert_error(settle_auction(&mut env, &auction).await, P2PSwapError::AuctionNotEnded);
+
+    // bid close to the end extends the auction
+    let slot = env.clock().await.slot;
+    env.warp_slots(end_slot - slot - 5).await;
+    let slot = env.clock().await.slot;
+    place_bid(&mut env, &pair.buyer, &auction, 70).await.unwrap();
+    let state = env.get::<Auction>(&auction).await;
+    assert_eq!(state.end_slot, slot + 20);
+    assert_eq!(state.highest_bidder, pair.buyer.pubkey());
+    assert_eq!(state.highest_bid, 70);
+    assert_eq!(env.wallet_balance(&bidder.pubkey(), &pair.price_mint).await, 1000);
+    assert_eq!(env.escrow_balance(&bidder.pubkey(), &pair.price_mint).await, 0);
+
+    env.warp_slots(20).await;
+    assert_error(place_bid(&mut env, &bidder, &auction, 90).await, P2PSwapError::AuctionEnded);
+
+    settle_auction(&mut env, &auction).await.unwrap();
+    assert!(!env.exists(&auction).await);
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.token_mint).await, 100);
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.price_mint).await, 70);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 0);
+    assert_eq!(env.escrow_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 0);
+}
+
+#[tokio::test]
+async fn test_sealed_auction() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 1000).await;
+    let loser = env.create_user().await;
+    let silent = env.create_user().await;
+    env.fund_wallet(&loser.pubkey(), &pair.price_mint, 1000).await;
+    env.fund_wallet(&silent.pubkey(), &pair.price_mint, 1000).await;
+
+    let slot = env.clock().await.slot;
+    let auction = create_sealed_auction(&mut env, &pair, 100, 50, 80, slot + 50, slot + 100).await;
+
+    assert_error(commit_bid(&mut env, &loser, &auction, 60, 70).await, P2PSwapError::BidTooLow);
+    commit_bid(&mut env, &pair.buyer, &auction, 90, 100).await.unwrap();
+    commit_bid(&mut env, &loser, &auction, 60, 80).await.unwrap();
+    commit_bid(&mut env, &silent, &auction, 75, 80).await.unwrap();
+    assert_error(commit_bid(&mut env, &pair.buyer, &auction, 95, 100).await, P2PSwapError::BidExists);
+    assert_eq!(env.get::<SealedAuction>(&auction).await.bid_count, 3);
+    assert_eq!(env.escrow_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 100);
+    assert_eq!(env.wallet_balance(&loser.pubkey(), &pair.price_mint).await, 920);
+
+    let buyer_salt = salt(&pair.buyer);
+    assert_error(
+        reveal_bid(&mut env, &pair.buyer, &auction, 90, &buyer_salt).await,
+        P2PSwapError::NotRevealPhase,
+    );
+
+    env.warp_slots(50).await;
+    assert_error(commit_bid(&mut env, &loser, &auction, 70, 80).await, P2PSwapError::AuctionEnded);
+    assert_error(
+        reveal_bid(&mut env, &pair.buyer, &auction, 90, &[0; 32]).await,
+        P2PSwapError::CommitmentMismatch,
+    );
+    reveal_bid(&mut env, &pair.buyer, &auction, 90, &buyer_salt).await.unwrap();
+    reveal_bid(&mut env, &loser, &auction, 60, &salt(&loser)).await.unwrap();
+    let state = env.get::<SealedAuction>(&auction).await;
+    assert_eq!(state.highest_bidder, pair.buyer.pubkey());
+    assert_eq!(state.highest_bid, 90);
+
+    assert_error(settle_sealed_auction(&mut env, &auction).await, P2PSwapError::AuctionNotEnded);
+    assert_error(withdraw_sealed_bid(&mut env, &loser, &auction).await, P2PSwapError::AuctionNotSettled);
+
+    env.warp_slots(50).await;
+    settle_sealed_auction(&mut env, &auction).await.unwrap();
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.token_mint).await, 100);
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.price_mint).await, 90);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 0);
+    let (winner_bid, _) = get_sealed_bid_address(&env.program_id, &auction, &pair.buyer.pubkey());
+    assert_eq!(env.get::<SealedBid>(&winner_bid).await.deposit, 10);
+    assert!(env.get::<SealedAuction>(&auction).await.is_settled);
+    assert_error(settle_sealed_auction(&mut env, &auction).await, P2PSwapError::AuctionSettled);
+
+    // winner gets the rest of the deposit, revealed loser is refunded, unrevealed deposit goes to seller
+    withdraw_sealed_bid(&mut env, &pair.buyer, &auction).await.unwrap();
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 910);
+    assert!(!env.exists(&winner_bid).await);
+    withdraw_sealed_bid(&mut env, &loser, &auction).await.unwrap();
+    assert_eq!(env.wallet_balance(&loser.pubkey(), &pair.price_mint).await, 1000);
+    withdraw_sealed_bid(&mut env, &silent, &auction).await.unwrap();
+    assert_eq!(env.wallet_balance(&silent.pubkey(), &pair.price_mint).await, 920);
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.price_mint).await, 170);
+    assert_eq!(env.escrow_balance(&silent.pubkey(), &pair.price_mint).await, 0);
+    assert!(!env.exists(&auction).await);
+}
@@ -0,0 +1,725 @@
+// Helpers shared by instruction handler tests. Every test binary uses only a part of them
+#![allow(dead_code)]
+
+use {
+    p2p_swap::{
+        get_arbitration_config_address, get_config_address, get_delegate_address, get_flip_address,
+        get_market_address, get_mint_list_entry_address, get_order_address, get_order_book_address,
+        get_order_counter_address, get_order_wallet_address, get_order_wallet_authority,
+        get_payout_split_address, get_reputation_address, get_seller_epoch_address,
+        get_trade_receipt_address, Flip, OrderCounter, P2PSwapError, P2PSwapInstructions, PayoutSplit,
+        StartAt, SwapSPLOrder,
+    },
+    solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::{Account, AccountSharedData},
+        account_info::AccountInfo,
+        bpf_loader_upgradeable,
+        clock::Clock,
+        ed25519_program,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        native_token::LAMPORTS_PER_SOL,
+        program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
+        system_instruction, system_program, sysvar,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_associated_token_account::get_associated_token_address,
+    spl_token::state::{Account as SPLAccount, Mint as SPLMint},
+    std::collections::HashSet,
+};
+
+// program-test hands out accounts and their data with independent lifetimes,
+// processor expects them to be the same
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    let accounts = unsafe { std::slice::from_raw_parts(accounts.as_ptr().cast::<AccountInfo>(), accounts.len()) };
+    p2p_swap::entrypoint::process_instruction(program_id, accounts, instruction_data)
+}
+
+pub fn instruction(program_id: &Pubkey, tag: P2PSwapInstructions, data: &[u8], accounts: Vec<AccountMeta>) -> Instruction {
+    let mut instruction_data = vec![tag as u8];
+    instruction_data.extend_from_slice(data);
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction_data,
+    }
+}
+
+// Placeholder of optional and ignored accounts
+pub fn skipped() -> AccountMeta {
+    AccountMeta::new_readonly(system_program::id(), false)
+}
+
+pub fn instruction_error(result: Result<(), BanksClientError>) -> Option<InstructionError> {
+    match result.err()?.unwrap() {
+        TransactionError::InstructionError(_, error) => Some(error),
+        _ => None,
+    }
+}
+
+pub fn assert_error(result: Result<(), BanksClientError>, error: P2PSwapError) {
+    assert_eq!(instruction_error(result), Some(InstructionError::Custom(error as u32)));
+}
+
+#[derive(Clone, Copy)]
+pub struct OrderOptions {
+    pub min_sell_amount: u64,
+    pub is_private: bool,
+    pub start_at: StartAt,
+    // Pubkey::default() - associated token account of seller
+    pub payout_wallet: Pubkey,
+    // gate mint and minimum balance
+    pub gate: Option<(Pubkey, u64)>,
+}
+
+impl Default for OrderOptions {
+    fn default() -> Self {
+        OrderOptions {
+            min_sell_amount: 0,
+            is_private: false,
+            start_at: StartAt::Immediately,
+            payout_wallet: Pubkey::default(),
+            gate: None,
+        }
+    }
+}
+
+pub struct Pair {
+    pub seller: Keypair,
+    pub buyer: Keypair,
+    pub token_mint: Pubkey,
+    pub price_mint: Pubkey,
+}
+
+// Seller with `tokens` of token mint, buyer with `price_tokens` of price mint
+pub async fn setup_pair(env: &mut TestEnv, tokens: u64, price_tokens: u64) -> Pair {
+    let seller = env.create_user().await;
+    let buyer = env.create_user().await;
+    let token_mint = env.create_mint(0).await;
+    let price_mint = env.create_mint(0).await;
+    env.fund_wallet(&seller.pubkey(), &token_mint, tokens).await;
+    env.fund_wallet(&buyer.pubkey(), &price_mint, price_tokens).await;
+
+    Pair {
+        seller,
+        buyer,
+        token_mint,
+        price_mint,
+    }
+}
+
+pub struct TestEnv {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+    // upgrade authority of the program, so it can init config
+    pub admin: Keypair,
+    signatures: HashSet<Signature>,
+}
+
+impl TestEnv {
+    pub async fn start() -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = ProgramTest::new("p2p_swap", program_id, processor!(process_instruction));
+        // bundled BPF builds of SPL programs don't run on current toolchains, native ones are used instead
+        program_test.add_program("spl_token", spl_token::id(), processor!(spl_token::processor::Processor::process));
+        program_test.add_program(
+            "spl_associated_token_account",
+            spl_associated_token_account::id(),
+            processor!(spl_associated_token_account::processor::process_instruction),
+        );
+
+        let admin = Keypair::new();
+        program_test.add_account(admin.pubkey(), Account {
+            lamports: 100 * LAMPORTS_PER_SOL,
+            ..Account::default()
+        });
+
+        // UpgradeableLoaderState::ProgramData - 4 bytes tag, 8 bytes slot, Option<Pubkey>
+        let (program_data, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+        let mut program_data_bytes = vec![0; 45];
+        program_data_bytes[..4].copy_from_slice(&3u32.to_le_bytes());
+        program_data_bytes[12] = 1;
+        program_data_bytes[13..].copy_from_slice(admin.pubkey().as_ref());
+        program_test.add_account(program_data, Account {
+            lamports: LAMPORTS_PER_SOL,
+            data: program_data_bytes,
+            owner: bpf_loader_upgradeable::id(),
+            ..Account::default()
+        });
+
+        TestEnv {
+            context: program_test.start_with_context().await,
+            program_id,
+            admin,
+            signatures: HashSet::new(),
+        }
+    }
+
+    pub fn admin(&self) -> Keypair {
+        Keypair::from_bytes(&self.admin.to_bytes()).unwrap()
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<(), BanksClientError> {
+        let payer = Keypair::from_bytes(&self.context.payer.to_bytes()).unwrap();
+        let mut all_signers = vec![&payer];
+        all_signers.extend(signers.iter().filter(|signer| signer.pubkey() != payer.pubkey()));
+
+        let blockhash = self.context.banks_client.get_latest_blockhash().await.unwrap();
+        let mut transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        // repeated transaction would be rejected as already processed until blockhash changes
+        if !self.signatures.insert(transaction.signatures[0]) {
+            self.warp_slots(1).await;
+            let blockhash = self.context.banks_client.get_latest_blockhash().await.unwrap();
+            transaction.sign(&all_signers, blockhash);
+            self.signatures.insert(transaction.signatures[0]);
+        }
+
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    pub fn set_account(&mut self, address: &Pubkey, account: Account) {
+        self.context.set_account(address, &AccountSharedData::from(account));
+    }
+
+    pub async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(*address).await.unwrap()
+    }
+
+    pub async fn exists(&mut self, address: &Pubkey) -> bool {
+        self.account(address).await.is_some()
+    }
+
+    pub async fn get<T: Pack + IsInitialized>(&mut self, address: &Pubkey) -> T {
+        let account = self.account(address).await.unwrap_or_else(|| panic!("Account {:?} not found", address));
+        T::unpack(&account.data).unwrap()
+    }
+
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.account(address).await.map_or(0, |account| account.lamports)
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
+    pub async fn warp_slots(&mut self, slots: u64) {
+        let slot = self.clock().await.slot;
+        self.context.warp_to_slot(slot + slots).unwrap();
+    }
+
+    // Moves unix timestamp forward (deadlines of deals, RFQs and fiat trades)
+    pub async fn warp_seconds(&mut self, seconds: i64) {
+        self.warp_slots(1).await;
+        let mut clock = self.clock().await;
+        clock.unix_timestamp += seconds;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn create_user(&mut self) -> Keypair {
+        let user = Keypair::new();
+        let payer = self.payer();
+        self.process(&[system_instruction::transfer(&payer, &user.pubkey(), 10 * LAMPORTS_PER_SOL)], &[])
+            .await
+            .unwrap();
+        user
+    }
+
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent.minimum_balance(SPLMint::LEN),
+                    SPLMint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer, None, decimals)
+                    .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    pub fn create_wallet_instruction(&self, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            &self.payer(),
+            owner,
+            mint,
+            &spl_token::id(),
+        )
+    }
+
+    // Associated token account of the owner, created if missing
+    pub async fn create_wallet(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let instruction = self.create_wallet_instruction(owner, mint);
+        self.process(&[instruction], &[]).await.unwrap();
+        get_associated_token_address(owner, mint)
+    }
+
+    pub async fn mint_to(&mut self, mint: &Pubkey, wallet: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        let instruction =
+            spl_token::instruction::mint_to(&spl_token::id(), mint, wallet, &payer, &[], amount).unwrap();
+        self.process(&[instruction], &[]).await.unwrap();
+    }
+
+    // Creates associated wallet of the owner with given balance
+    pub async fn fund_wallet(&mut self, owner: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+        let wallet = self.create_wallet(owner, mint).await;
+        if amount > 0 {
+            self.mint_to(mint, &wallet, amount).await;
+        }
+        wallet
+    }
+
+    // Balance of the token account, 0 if it does not exist
+    pub async fn balance(&mut self, wallet: &Pubkey) -> u64 {
+        match self.account(wallet).await {
+            Some(account) => SPLAccount::unpack(&account.data).unwrap().amount,
+            None => 0,
+        }
+    }
+
+    pub async fn wallet_balance(&mut self, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        self.balance(&get_associated_token_address(owner, mint)).await
+    }
+
+    // Order (and escrow) wallet authority and wallet of the owner for given mint
+    pub fn escrow(&self, owner: &Pubkey, mint: &Pubkey) -> (Pubkey, Pubkey) {
+        let (authority, _) = get_order_wallet_authority(&self.program_id, owner);
+        (authority, get_order_wallet_address(mint, &authority))
+    }
+
+    pub async fn create_escrow_wallet(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let (authority, wallet) = self.escrow(owner, mint);
+        self.create_wallet(&authority, mint).await;
+        wallet
+    }
+
+    pub async fn escrow_balance(&mut self, owner: &Pubkey, mint: &Pubkey) -> u64 {
+        let (_, wallet) = self.escrow(owner, mint);
+        self.balance(&wallet).await
+    }
+
+    pub fn config(&self) -> Pubkey {
+        get_config_address(&self.program_id).0
+    }
+
+    pub async fn init_config(&mut self) {
+        let accounts = vec![
+            AccountMeta::new(self.admin.pubkey(), true),
+            AccountMeta::new(self.config(), false),
+            AccountMeta::new_readonly(
+                Pubkey::find_program_address(&[self.program_id.as_ref()], &bpf_loader_upgradeable::id()).0,
+                false,
+            ),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        let instruction = instruction(&self.program_id, P2PSwapInstructions::InitConfig, &[], accounts);
+        let admin = self.admin();
+        self.process(&[instruction], &[&admin]).await.unwrap();
+    }
+
+    // Admin instruction which takes admin and config accounts only
+    pub async fn admin_instruction(&mut self, tag: P2PSwapInstructions, data: &[u8]) -> Result<(), BanksClientError> {
+        let accounts = vec![
+            AccountMeta::new(self.admin.pubkey(), true),
+            AccountMeta::new(self.config(), false),
+        ];
+        let instruction = instruction(&self.program_id, tag, data, accounts);
+        let admin = self.admin();
+        self.process(&[instruction], &[&admin]).await
+    }
+
+    pub async fn set_mint_status(&mut self, mint: &Pubkey, status: u8) {
+        let accounts = vec![
+            AccountMeta::new(self.admin.pubkey(), true),
+            AccountMeta::new_readonly(self.config(), false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(get_mint_list_entry_address(&self.program_id, mint).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        let instruction = instruction(&self.program_id, P2PSwapInstructions::SetMintStatus, &[status], accounts);
+        let admin = self.admin();
+        self.process(&[instruction], &[&admin]).await.unwrap();
+    }
+
+    pub async fn set_arbitration_config(&mut self, arbitrator: &Pubkey, fee_bps: u16) {
+        let mut data = arbitrator.to_bytes().to_vec();
+        data.extend_from_slice(&fee_bps.to_le_bytes());
+        let accounts = vec![
+            AccountMeta::new(self.admin.pubkey(), true),
+            AccountMeta::new_readonly(self.config(), false),
+            AccountMeta::new(get_arbitration_config_address(&self.program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        let instruction = instruction(&self.program_id, P2PSwapInstructions::SetArbitrationConfig, &data, accounts);
+        let admin = self.admin();
+        self.process(&[instruction], &[&admin]).await.unwrap();
+    }
+
+    pub fn market_rules_data(tick_size: u64, lot_size: u64, min_order_size: u64) -> Vec<u8> {
+        [tick_size.to_le_bytes(), lot_size.to_le_bytes(), min_order_size.to_le_bytes()].concat()
+    }
+
+    pub async fn create_market(
+        &mut self,
+        authority: &Keypair,
+        token_mint: &Pubkey,
+        price_mint: &Pubkey,
+        tick_size: u64,
+        lot_size: u64,
+        min_order_size: u64,
+    ) -> Result<(), BanksClientError> {
+        let accounts = vec![
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(*price_mint, false),
+            AccountMeta::new(get_market_address(&self.program_id, token_mint, price_mint).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(self.config(), false),
+        ];
+        let data = Self::market_rules_data(tick_size, lot_size, min_order_size);
+        let instruction = instruction(&self.program_id, P2PSwapInstructions::CreateMarket, &data, accounts);
+        self.process(&[instruction], &[authority]).await
+    }
+
+    pub async fn next_order_index(&mut self, seller: &Pubkey) -> u64 {
+        let (order_counter, _) = get_order_counter_address(&self.program_id, seller);
+        match self.account(&order_counter).await {
+            Some(account) => OrderCounter::unpack(&account.data).unwrap().next_order_index,
+            None => 0,
+        }
+    }
+
+    pub async fn create_order_instructions(
+        &mut self,
+        seller: &Pubkey,
+        sell_mint: &Pubkey,
+        buy_mint: &Pubkey,
+        sell_amount: u64,
+        buy_amount: u64,
+        options: OrderOptions,
+    ) -> (Pubkey, Vec<Instruction>) {
+        let order_index = self.next_order_index(seller).await;
+        let (order, _) = get_order_address(&self.program_id, seller, order_index);
+        let (order_wallet_authority, order_wallet) = self.escrow(seller, sell_mint);
+        let (gate_mint, gate_min_balance) = options.gate.unwrap_or((system_program::id(), 0));
+
+        let mut start_at = [0; StartAt::LEN];
+        options.start_at.pack(&mut start_at);
+        let data = [
+            &sell_amount.to_le_bytes()[..],
+            &buy_amount.to_le_bytes(),
+            &options.min_sell_amount.to_le_bytes(),
+            &order_index.to_le_bytes(),
+            &start_at,
+            &gate_min_balance.to_le_bytes(),
+        ]
+        .concat();
+
+        let tag = if options.is_private {
+            P2PSwapInstructions::CreatePrivateOrder
+        } else {
+            P2PSwapInstructions::CreatePublicOrder
+        };
+
+        let accounts = vec![
+            AccountMeta::new(*seller, true),
+            AccountMeta::new(get_associated_token_address(seller, sell_mint), false),
+            AccountMeta::new_readonly(*sell_mint, false),
+            AccountMeta::new_readonly(order_wallet_authority, false),
+            AccountMeta::new_readonly(*buy_mint, false),
+            AccountMeta::new(order_wallet, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(order, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(get_order_counter_address(&self.program_id, seller).0, false),
+            AccountMeta::new_readonly(get_market_address(&self.program_id, sell_mint, buy_mint).0, false),
+            AccountMeta::new(get_order_book_address(&self.program_id, sell_mint, buy_mint).0, false),
+            AccountMeta::new_readonly(self.config(), false),
+            AccountMeta::new_readonly(get_mint_list_entry_address(&self.program_id, sell_mint).0, false),
+            AccountMeta::new_readonly(get_mint_list_entry_address(&self.program_id, buy_mint).0, false),
+            AccountMeta::new_readonly(options.payout_wallet, false),
+            AccountMeta::new_readonly(get_seller_epoch_address(&self.program_id, seller).0, false),
+            AccountMeta::new_readonly(gate_mint, false),
+        ];
+
+        (
+            order,
+            vec![
+                self.create_wallet_instruction(&order_wallet_authority, sell_mint),
+                instruction(&self.program_id, tag, &data, accounts),
+            ],
+        )
+    }
+
+    pub async fn try_create_order(
+        &mut self,
+        seller: &Keypair,
+        sell_mint: &Pubkey,
+        buy_mint: &Pubkey,
+        sell_amount: u64,
+        buy_amount: u64,
+        options: OrderOptions,
+    ) -> Result<Pubkey, BanksClientError> {
+        let (order, instructions) = self
+            .create_order_instructions(&seller.pubkey(), sell_mint, buy_mint, sell_amount, buy_amount, options)
+            .await;
+        self.process(&instructions, &[seller]).await.map(|_| order)
+    }
+
+    pub async fn create_order(
+        &mut self,
+        seller: &Keypair,
+        sell_mint: &Pubkey,
+        buy_mint: &Pubkey,
+        sell_amount: u64,
+        buy_amount: u64,
+        min_sell_amount: u64,
+    ) -> Pubkey {
+        let options = OrderOptions {
+            min_sell_amount,
+            ..OrderOptions::default()
+        };
+        self.try_create_order(seller, sell_mint, buy_mint, sell_amount, buy_amount, options)
+            .await
+            .unwrap()
+    }
+
+    pub async fn order(&mut self, order: &Pubkey) -> SwapSPLOrder {
+        self.get(order).await
+    }
+
+    // Fill instruction built from the current state of the order, like clients do
+    pub async fn fill_instruction(&mut self, buyer: &Pubkey, order_address: &Pubkey, amount: u64, is_exact_spend: bool) -> Instruction {
+        let order = self.order(order_address).await;
+        let program_id = self.program_id;
+        let (order_wallet_authority, _) = get_order_wallet_authority(&program_id, &order.seller);
+
+        let mut accounts = vec![
+            AccountMeta::new(order.seller, false),
+            AccountMeta::new(*buyer, true),
+            AccountMeta::new(*order_address, false),
+        ];
+        if order.is_private {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+        }
+
+        accounts.extend([
+            AccountMeta::new_readonly(order_wallet_authority, false),
+            AccountMeta::new_readonly(order.token_mint, false),
+            AccountMeta::new(order.order_wallet, false),
+            AccountMeta::new_readonly(order.price_mint, false),
+            AccountMeta::new(get_associated_token_address(buyer, &order.price_mint), false),
+            AccountMeta::new(order.get_payout_wallet(), false),
+            AccountMeta::new(get_associated_token_address(buyer, &order.token_mint), false),
+            AccountMeta::new_readonly(*buyer, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(get_market_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+            AccountMeta::new(get_order_book_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+            AccountMeta::new_readonly(self.config(), false),
+        ]);
+
+        let (payout_split, _) = get_payout_split_address(&program_id, order_address);
+        accounts.push(AccountMeta::new_readonly(payout_split, false));
+
+        let (flip, _) = get_flip_address(&program_id, order_address);
+        accounts.push(AccountMeta::new_readonly(flip, false));
+        match self.account(&flip).await {
+            Some(flip) => {
+                let flip = Flip::unpack(&flip.data).unwrap();
+                let reverse_order = self.order(&flip.reverse_order).await;
+                accounts.extend([
+                    AccountMeta::new(flip.reverse_order, false),
+                    AccountMeta::new(reverse_order.order_wallet, false),
+                    AccountMeta::new(
+                        get_order_book_address(&program_id, &reverse_order.token_mint, &reverse_order.price_mint).0,
+                        false,
+                    ),
+                ]);
+            },
+            None => accounts.extend([skipped(), skipped(), skipped()]),
+        }
+
+        accounts.extend([
+            AccountMeta::new(get_reputation_address(&program_id, &order.seller).0, false),
+            AccountMeta::new(get_reputation_address(&program_id, buyer).0, false),
+            AccountMeta::new(get_trade_receipt_address(&program_id, order_address, buyer).0, false),
+            AccountMeta::new_readonly(get_seller_epoch_address(&program_id, &order.seller).0, false),
+        ]);
+
+        if order.gate_mint == Pubkey::default() {
+            accounts.push(skipped());
+        } else {
+            accounts.push(AccountMeta::new_readonly(get_associated_token_address(buyer, &order.gate_mint), false));
+        }
+
+        accounts.extend(self.payout_recipient_accounts(order_address).await);
+
+        let tag = if is_exact_spend {
+            P2PSwapInstructions::FillOrderExactSpend
+        } else {
+            P2PSwapInstructions::FillOrder
+        };
+        instruction(&program_id, tag, &amount.to_le_bytes(), accounts)
+    }
+
+    // ed25519 signature of the order address by the seller, which unlocks a private order for the next instruction
+    pub fn unlock_instruction(signer: &Keypair, order: &Pubkey) -> Instruction {
+        const DATA_START: u16 = 16;
+        let pubkey_offset = DATA_START;
+        let signature_offset = pubkey_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1, 0];
+        for offset in [
+            signature_offset,
+            u16::MAX,
+            pubkey_offset,
+            u16::MAX,
+            message_offset,
+            32,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(signer.pubkey().as_ref());
+        data.extend_from_slice(signer.sign_message(order.as_ref()).as_ref());
+        data.extend_from_slice(order.as_ref());
+
+        Instruction {
+            program_id: ed25519_program::id(),
+            accounts: vec![],
+            data,
+        }
+    }
+
+    pub async fn fill(&mut self, buyer: &Keypair, order: &Pubkey, amount: u64) -> Result<(), BanksClientError> {
+        let instruction = self.fill_instruction(&buyer.pubkey(), order, amount, false).await;
+        self.process(&[instruction], &[buyer]).await
+    }
+
+    pub async fn fill_exact_spend(&mut self, buyer: &Keypair, order: &Pubkey, spend: u64) -> Result<(), BanksClientError> {
+        let instruction = self.fill_instruction(&buyer.pubkey(), order, spend, true).await;
+        self.process(&[instruction], &[buyer]).await
+    }
+
+    // Wallets of payout split recipients (nothing if the order has no split)
+    pub async fn payout_recipient_accounts(&mut self, order: &Pubkey) -> Vec<AccountMeta> {
+        let (payout_split, _) = get_payout_split_address(&self.program_id, order);
+        match self.account(&payout_split).await {
+            Some(account) => PayoutSplit::unpack(&account.data)
+                .unwrap()
+                .recipients
+                .iter()
+                .map(|recipient| AccountMeta::new(recipient.wallet, false))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Revoke by seller, its operator or anyone else. Optional accounts: order book, seller reputation, caller delegate
+    // (when caller is an operator), seller epoch, payout split and flip
+    pub async fn revoke_instruction(&mut self, caller: &Pubkey, order_address: &Pubkey, amount: u64) -> Instruction {
+        let order = self.order(order_address).await;
+        let program_id = self.program_id;
+        let (order_wallet_authority, _) = get_order_wallet_authority(&program_id, &order.seller);
+        let (delegate, _) = get_delegate_address(&program_id, &order.seller, caller);
+        let delegate = if *caller != order.seller && self.exists(&delegate).await {
+            AccountMeta::new_readonly(delegate, false)
+        } else {
+            skipped()
+        };
+
+        let accounts = vec![
+            AccountMeta::new(*caller, true),
+            AccountMeta::new(order.seller, false),
+            AccountMeta::new(*order_address, false),
+            AccountMeta::new_readonly(order_wallet_authority, false),
+            AccountMeta::new(order.order_wallet, false),
+            AccountMeta::new(get_associated_token_address(&order.seller, &order.token_mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new(get_order_book_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+            AccountMeta::new(get_reputation_address(&program_id, &order.seller).0, false),
+            delegate,
+            AccountMeta::new_readonly(get_seller_epoch_address(&program_id, &order.seller).0, false),
+            AccountMeta::new(get_payout_split_address(&program_id, order_address).0, false),
+            AccountMeta::new(get_flip_address(&program_id, order_address).0, false),
+        ];
+
+        instruction(&program_id, P2PSwapInstructions::RevokeOrder, &amount.to_le_bytes(), accounts)
+    }
+
+    pub async fn revoke(&mut self, caller: &Keypair, order: &Pubkey, amount: u64) -> Result<(), BanksClientError> {
+        let instruction = self.revoke_instruction(&caller.pubkey(), order, amount).await;
+        self.process(&[instruction], &[caller]).await
+    }
+
+    // Pause or resume by seller, or by operator when given
+    pub async fn set_order_status(
+        &mut self,
+        signer: &Keypair,
+        seller: &Pubkey,
+        order: &Pubkey,
+        tag: P2PSwapInstructions,
+    ) -> Result<(), BanksClientError> {
+        let mut accounts = vec![
+            AccountMeta::new_readonly(*seller, signer.pubkey() == *seller),
+            AccountMeta::new(*order, false),
+        ];
+        if signer.pubkey() != *seller {
+            accounts.push(AccountMeta::new(signer.pubkey(), true));
+            accounts.push(AccountMeta::new_readonly(
+                get_delegate_address(&self.program_id, seller, &signer.pubkey()).0,
+                false,
+            ));
+        }
+
+        let instruction = instruction(&self.program_id, tag, &[], accounts);
+        self.process(&[instruction], &[signer]).await
+    }
+
+    pub async fn create_reputation(&mut self, wallet: &Pubkey) -> Pubkey {
+        let (reputation, _) = get_reputation_address(&self.program_id, wallet);
+        let accounts = vec![
+            AccountMeta::new(self.payer(), true),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new(reputation, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        let instruction = instruction(&self.program_id, P2PSwapInstructions::CreateReputation, &[], accounts);
+        self.process(&[instruction], &[]).await.unwrap();
+        reputation
+    }
+
+    // Optional trailing seller and buyer reputations of settlement instructions
+    pub fn reputation_accounts(&self, seller: &Pubkey, buyer: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(get_reputation_address(&self.program_id, seller).0, false),
+            AccountMeta::new(get_reputation_address(&self.program_id, buyer).0, false),
+        ]
+    }
+}
@@ -0,0 +1,460 @@
+mod common;
+
+use {
+    common::*,
+    p2p_swap::{
+        get_deal_address, get_market_address, get_order_book_address, get_payout_split_address, get_proposal_address,
+        get_quote_address, get_rfq_address, get_seller_epoch_address, P2PSwapError, P2PSwapInstructions, Reputation,
+        Rfq,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        instruction::{AccountMeta, InstructionError},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+    },
+    spl_associated_token_account::get_associated_token_address,
+};
+
+async fn create_proposal(
+    env: &mut TestEnv,
+    buyer: &Keypair,
+    order_address: &Pubkey,
+    sell_amount: u64,
+    buy_amount: u64,
+) -> Result<(), BanksClientError> {
+    let order = env.order(order_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&buyer.pubkey(), &order.price_mint);
+    let accounts = vec![
+        AccountMeta::new(buyer.pubkey(), true),
+        AccountMeta::new(get_associated_token_address(&buyer.pubkey(), &order.price_mint), false),
+        AccountMeta::new_readonly(*order_address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(get_proposal_address(&env.program_id, order_address, &buyer.pubkey()).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    let data = [sell_amount.to_le_bytes(), buy_amount.to_le_bytes()].concat();
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &order.price_mint),
+        instruction(&env.program_id, P2PSwapInstructions::CreateProposal, &data, accounts),
+    ];
+    env.process(&instructions, &[buyer]).await
+}
+
+async fn withdraw_proposal(env: &mut TestEnv, buyer: &Keypair, order_address: &Pubkey) -> Result<(), BanksClientError> {
+    let order = env.order(order_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&buyer.pubkey(), &order.price_mint);
+    let accounts = vec![
+        AccountMeta::new(buyer.pubkey(), true),
+        AccountMeta::new(get_proposal_address(&env.program_id, order_address, &buyer.pubkey()).0, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&buyer.pubkey(), &order.price_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::WithdrawProposal, &[], accounts);
+    env.process(&[instruction], &[buyer]).await
+}
+
+async fn accept_proposal(
+    env: &mut TestEnv,
+    seller: &Keypair,
+    order_address: &Pubkey,
+    buyer: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let order = env.order(order_address).await;
+    let program_id = env.program_id;
+    let (order_wallet_authority, _) = env.escrow(&order.seller, &order.token_mint);
+    let (escrow_authority, escrow_wallet) = env.escrow(buyer, &order.price_mint);
+    let mut accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new(*order_address, false),
+        AccountMeta::new(get_proposal_address(&program_id, order_address, buyer).0, false),
+        AccountMeta::new(*buyer, false),
+        AccountMeta::new_readonly(order_wallet_authority, false),
+        AccountMeta::new(order.order_wallet, false),
+        AccountMeta::new(get_associated_token_address(buyer, &order.token_mint), false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(order.get_payout_wallet(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(get_order_book_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+        AccountMeta::new_readonly(env.config(), false),
+        AccountMeta::new_readonly(get_market_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+        AccountMeta::new_readonly(get_seller_epoch_address(&program_id, &order.seller).0, false),
+        skipped(),
+        AccountMeta::new_readonly(get_payout_split_address(&program_id, order_address).0, false),
+    ];
+    accounts.extend(env.payout_recipient_accounts(order_address).await);
+    accounts.extend(env.reputation_accounts(&seller.pubkey(), buyer));
+
+    let instruction = instruction(&program_id, P2PSwapInstructions::AcceptProposal, &[], accounts);
+    env.process(&[instruction], &[seller]).await
+}
+
+#[tokio::test]
+async fn test_proposals() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 2000, 10000).await;
+    let (seller, buyer) = (pair.seller.pubkey(), pair.buyer.pubkey());
+    env.create_wallet(&seller, &pair.price_mint).await;
+    env.create_wallet(&buyer, &pair.token_mint).await;
+    let order1 = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, 0).await;
+    let order2 = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 500, 1000, 0).await;
+
+    assert_error(
+        create_proposal(&mut env, &pair.buyer, &order2, 600, 500).await,
+        P2PSwapError::NotEnoughTokensInOrder,
+    );
+
+    // payments of both proposals wait in the same escrow wallet of buyer
+    create_proposal(&mut env, &pair.buyer, &order1, 400, 300).await.unwrap();
+    create_proposal(&mut env, &pair.buyer, &order2, 200, 350).await.unwrap();
+    assert_eq!(env.escrow_balance(&buyer, &pair.price_mint).await, 650);
+    assert_eq!(env.wallet_balance(&buyer, &pair.price_mint).await, 9350);
+    assert_error(create_proposal(&mut env, &pair.buyer, &order1, 400, 310).await, P2PSwapError::ProposalExists);
+
+    withdraw_proposal(&mut env, &pair.buyer, &order2).await.unwrap();
+    assert!(!env.exists(&get_proposal_address(&env.program_id, &order2, &buyer).0).await);
+    assert_eq!(env.escrow_balance(&buyer, &pair.price_mint).await, 300);
+    assert_eq!(env.wallet_balance(&buyer, &pair.price_mint).await, 9700);
+
+    let seller_reputation = env.create_reputation(&seller).await;
+    let buyer_reputation = env.create_reputation(&buyer).await;
+    assert_eq!(
+        instruction_error(accept_proposal(&mut env, &pair.buyer, &order1, &buyer).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+    accept_proposal(&mut env, &pair.seller, &order1, &buyer).await.unwrap();
+    assert!(!env.exists(&get_proposal_address(&env.program_id, &order1, &buyer).0).await);
+    assert_eq!(env.escrow_balance(&buyer, &pair.price_mint).await, 0);
+    assert_eq!(env.wallet_balance(&seller, &pair.price_mint).await, 300);
+    assert_eq!(env.wallet_balance(&buyer, &pair.token_mint).await, 400);
+    assert_eq!(env.order(&order1).await.remains_to_fill, 600);
+    assert_eq!(env.escrow_balance(&seller, &pair.token_mint).await, 1100);
+    for reputation in [seller_reputation, buyer_reputation] {
+        assert_eq!(env.get::<Reputation>(&reputation).await.trade_count, 1);
+    }
+}
+
+// Party A is seller of the pair and gives token mint, party B is buyer and gives price mint
+struct Deal {
+    address: Pubkey,
+    party_a: Keypair,
+    party_b: Keypair,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+async fn create_deal(env: &mut TestEnv, pair: Pair, amounts: (u64, u64), deadline: i64, nonce: u64) -> Deal {
+    let (address, _) = get_deal_address(&env.program_id, &pair.seller.pubkey(), nonce);
+    let accounts = vec![
+        AccountMeta::new(pair.seller.pubkey(), true),
+        AccountMeta::new_readonly(pair.buyer.pubkey(), false),
+        AccountMeta::new_readonly(pair.token_mint, false),
+        AccountMeta::new_readonly(pair.price_mint, false),
+        AccountMeta::new(address, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    let data = [
+        amounts.0.to_le_bytes(),
+        amounts.1.to_le_bytes(),
+        deadline.to_le_bytes(),
+        nonce.to_le_bytes(),
+    ]
+    .concat();
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::CreateDeal, &data, accounts);
+    env.process(&[instruction], &[&pair.seller]).await.unwrap();
+
+    Deal {
+        address,
+        party_a: pair.seller,
+        party_b: pair.buyer,
+        mint_a: pair.token_mint,
+        mint_b: pair.price_mint,
+    }
+}
+
+impl Deal {
+    fn party(&self, is_party_a: bool) -> (&Keypair, Pubkey) {
+        if is_party_a {
+            (&self.party_a, self.mint_a)
+        } else {
+            (&self.party_b, self.mint_b)
+        }
+    }
+}
+
+async fn deposit_deal(env: &mut TestEnv, deal: &Deal, is_party_a: bool) -> Result<(), BanksClientError> {
+    let (party, mint) = deal.party(is_party_a);
+    let (escrow_authority, escrow_wallet) = env.escrow(&party.pubkey(), &mint);
+    let accounts = vec![
+        AccountMeta::new(party.pubkey(), true),
+        AccountMeta::new(deal.address, false),
+        AccountMeta::new(get_associated_token_address(&party.pubkey(), &mint), false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &mint),
+        instruction(&env.program_id, P2PSwapInstructions::DepositDeal, &[], accounts),
+    ];
+    env.process(&instructions, &[party]).await
+}
+
+async fn settle_deal(env: &mut TestEnv, deal: &Deal) -> Result<(), BanksClientError> {
+    let (party_a, party_b) = (deal.party_a.pubkey(), deal.party_b.pubkey());
+    let (escrow_authority_a, escrow_wallet_a) = env.escrow(&party_a, &deal.mint_a);
+    let (escrow_authority_b, escrow_wallet_b) = env.escrow(&party_b, &deal.mint_b);
+    let mut accounts = vec![
+        AccountMeta::new(party_a, false),
+        AccountMeta::new(party_b, false),
+        AccountMeta::new(deal.address, false),
+        AccountMeta::new_readonly(escrow_authority_a, false),
+        AccountMeta::new(escrow_wallet_a, false),
+        AccountMeta::new_readonly(escrow_authority_b, false),
+        AccountMeta::new(escrow_wallet_b, false),
+        AccountMeta::new(get_associated_token_address(&party_a, &deal.mint_b), false),
+        AccountMeta::new(get_associated_token_address(&party_b, &deal.mint_a), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(env.reputation_accounts(&party_a, &party_b));
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::SettleDeal, &[], accounts);
+    env.process(&[instruction], &[]).await
+}
+
+async fn reclaim_deal(env: &mut TestEnv, deal: &Deal, is_party_a: bool) -> Result<(), BanksClientError> {
+    let (party, mint) = deal.party(is_party_a);
+    let (escrow_authority, escrow_wallet) = env.escrow(&party.pubkey(), &mint);
+    let accounts = vec![
+        AccountMeta::new(party.pubkey(), true),
+        AccountMeta::new(deal.address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&party.pubkey(), &mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(deal.party_a.pubkey(), false),
+    ];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::ReclaimDeal, &[], accounts);
+    env.process(&[instruction], &[party]).await
+}
+
+#[tokio::test]
+async fn test_deal_settle() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 500, 300).await;
+    let (party_a, party_b) = (pair.seller.pubkey(), pair.buyer.pubkey());
+    env.create_wallet(&party_a, &pair.price_mint).await;
+    env.create_wallet(&party_b, &pair.token_mint).await;
+    let reputation_a = env.create_reputation(&party_a).await;
+    let reputation_b = env.create_reputation(&party_b).await;
+
+    let deadline = env.clock().await.unix_timestamp + 100;
+    let deal = create_deal(&mut env, pair, (500, 300), deadline, 1).await;
+    assert_error(settle_deal(&mut env, &deal).await, P2PSwapError::DealNotFunded);
+
+    deposit_deal(&mut env, &deal, true).await.unwrap();
+    assert_error(deposit_deal(&mut env, &deal, true).await, P2PSwapError::AlreadyDeposited);
+    assert_error(settle_deal(&mut env, &deal).await, P2PSwapError::DealNotFunded);
+    deposit_deal(&mut env, &deal, false).await.unwrap();
+    assert_eq!(env.escrow_balance(&party_a, &deal.mint_a).await, 500);
+    assert_eq!(env.escrow_balance(&party_b, &deal.mint_b).await, 300);
+    assert_error(reclaim_deal(&mut env, &deal, true).await, P2PSwapError::DealNotExpired);
+
+    let deal_rent = env.lamports(&deal.address).await;
+    let party_a_lamports = env.lamports(&party_a).await;
+    settle_deal(&mut env, &deal).await.unwrap();
+    assert!(!env.exists(&deal.address).await);
+    assert_eq!(env.lamports(&party_a).await, party_a_lamports + deal_rent);
+    assert_eq!(env.wallet_balance(&party_a, &deal.mint_b).await, 300);
+    assert_eq!(env.wallet_balance(&party_b, &deal.mint_a).await, 500);
+    assert_eq!(env.escrow_balance(&party_a, &deal.mint_a).await, 0);
+    assert_eq!(env.escrow_balance(&party_b, &deal.mint_b).await, 0);
+    for reputation in [reputation_a, reputation_b] {
+        assert_eq!(env.get::<Reputation>(&reputation).await.trade_count, 1);
+    }
+}
+
+#[tokio::test]
+async fn test_deal_reclaim_shares_order_wallet() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 700, 300).await;
+    let buyer = env.create_user().await;
+    env.fund_wallet(&buyer.pubkey(), &pair.price_mint, 1000).await;
+    let party_a = pair.seller.pubkey();
+
+    // deposit of party A shares order wallet with its order
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 200, 200, 0).await;
+    let deadline = env.clock().await.unix_timestamp + 100;
+    let deal = create_deal(&mut env, pair, (500, 300), deadline, 7).await;
+    deposit_deal(&mut env, &deal, true).await.unwrap();
+    assert_eq!(env.escrow_balance(&party_a, &deal.mint_a).await, 700);
+    assert_eq!(env.wallet_balance(&party_a, &deal.mint_a).await, 0);
+
+    assert_error(reclaim_deal(&mut env, &deal, true).await, P2PSwapError::DealNotExpired);
+    env.warp_seconds(200).await;
+    assert_error(deposit_deal(&mut env, &deal, false).await, P2PSwapError::DealExpired);
+
+    // party B never deposited, nothing to return but the deal is closed once both sides are out
+    reclaim_deal(&mut env, &deal, true).await.unwrap();
+    assert!(!env.exists(&deal.address).await);
+    assert_eq!(env.wallet_balance(&party_a, &deal.mint_a).await, 500);
+    assert_eq!(env.escrow_balance(&party_a, &deal.mint_a).await, 200);
+
+    env.fill(&buyer, &order, 200).await.unwrap();
+    assert_eq!(env.escrow_balance(&party_a, &deal.mint_a).await, 0);
+}
+
+async fn create_rfq(env: &mut TestEnv, buyer: &Keypair, pair: &Pair, amount: u64, deadline: i64, nonce: u64) -> Pubkey {
+    let (rfq, _) = get_rfq_address(&env.program_id, &buyer.pubkey(), nonce);
+    let accounts = vec![
+        AccountMeta::new(buyer.pubkey(), true),
+        AccountMeta::new_readonly(pair.token_mint, false),
+        AccountMeta::new_readonly(pair.price_mint, false),
+        AccountMeta::new(rfq, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    let data = [amount.to_le_bytes(), deadline.to_le_bytes(), nonce.to_le_bytes()].concat();
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::CreateRfq, &data, accounts);
+    env.process(&[instruction], &[buyer]).await.unwrap();
+    rfq
+}
+
+async fn create_quote(env: &mut TestEnv, seller: &Keypair, rfq_address: &Pubkey, price: u64) -> Result<Pubkey, BanksClientError> {
+    let rfq = env.get::<Rfq>(rfq_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&seller.pubkey(), &rfq.token_mint);
+    let (quote, _) = get_quote_address(&env.program_id, rfq_address, &seller.pubkey());
+    let accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new(get_associated_token_address(&seller.pubkey(), &rfq.token_mint), false),
+        AccountMeta::new_readonly(*rfq_address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(quote, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &rfq.token_mint),
+        instruction(&env.program_id, P2PSwapInstructions::CreateQuote, &price.to_le_bytes(), accounts),
+    ];
+    env.process(&instructions, &[seller]).await.map(|_| quote)
+}
+
+async fn accept_quote(
+    env: &mut TestEnv,
+    buyer: &Keypair,
+    rfq_address: &Pubkey,
+    seller: &Pubkey,
+    pair: &Pair,
+) -> Result<(), BanksClientError> {
+    let (escrow_authority, escrow_wallet) = env.escrow(seller, &pair.token_mint);
+    let mut accounts = vec![
+        AccountMeta::new(buyer.pubkey(), true),
+        AccountMeta::new(*rfq_address, false),
+        AccountMeta::new(get_quote_address(&env.program_id, rfq_address, seller).0, false),
+        AccountMeta::new(*seller, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&buyer.pubkey(), &pair.token_mint), false),
+        AccountMeta::new(get_associated_token_address(&buyer.pubkey(), &pair.price_mint), false),
+        AccountMeta::new(get_associated_token_address(seller, &pair.price_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+    ];
+    accounts.extend(env.reputation_accounts(seller, &buyer.pubkey()));
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::AcceptQuote, &[], accounts);
+    env.process(&[instruction], &[buyer]).await
+}
+
+async fn reclaim_quote(env: &mut TestEnv, seller: &Keypair, rfq_address: &Pubkey, token_mint: &Pubkey) -> Result<(), BanksClientError> {
+    let (escrow_authority, escrow_wallet) = env.escrow(&seller.pubkey(), token_mint);
+    let accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new(get_quote_address(&env.program_id, rfq_address, &seller.pubkey()).0, false),
+        AccountMeta::new_readonly(*rfq_address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&seller.pubkey(), token_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::ReclaimQuote, &[], accounts);
+    env.process(&[instruction], &[seller]).await
+}
+
+async fn close_rfq(env: &mut TestEnv, buyer: &Keypair, rfq: &Pubkey) {
+    let accounts = vec![
+        AccountMeta::new(buyer.pubkey(), true),
+        AccountMeta::new(*rfq, false),
+    ];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::CloseRfq, &[], accounts);
+    env.process(&[instruction], &[buyer]).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_rfq_quotes() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 100, 10000).await;
+    let (seller1, buyer) = (&pair.seller, &pair.buyer);
+    let seller2 = env.create_user().await;
+    env.fund_wallet(&seller2.pubkey(), &pair.token_mint, 100).await;
+    for seller in [seller1.pubkey(), seller2.pubkey()] {
+        env.create_wallet(&seller, &pair.price_mint).await;
+    }
+    env.create_wallet(&buyer.pubkey(), &pair.token_mint).await;
+
+    let deadline = env.clock().await.unix_timestamp + 1000;
+    let rfq = create_rfq(&mut env, buyer, &pair, 100, deadline, 1).await;
+    create_quote(&mut env, seller1, &rfq, 300).await.unwrap();
+    create_quote(&mut env, &seller2, &rfq, 250).await.unwrap();
+    assert_eq!(env.escrow_balance(&seller1.pubkey(), &pair.token_mint).await, 100);
+    assert_eq!(env.escrow_balance(&seller2.pubkey(), &pair.token_mint).await, 100);
+    assert_error(create_quote(&mut env, &seller2, &rfq, 240).await.map(|_| ()), P2PSwapError::QuoteExists);
+
+    // quotes are locked while RFQ is open
+    assert_error(reclaim_quote(&mut env, &seller2, &rfq, &pair.token_mint).await, P2PSwapError::RfqActive);
+
+    accept_quote(&mut env, buyer, &rfq, &seller2.pubkey(), &pair).await.unwrap();
+    assert_eq!(env.get::<Rfq>(&rfq).await.accepted_quote, get_quote_address(&env.program_id, &rfq, &seller2.pubkey()).0);
+    assert_eq!(env.wallet_balance(&buyer.pubkey(), &pair.token_mint).await, 100);
+    assert_eq!(env.wallet_balance(&buyer.pubkey(), &pair.price_mint).await, 9750);
+    assert_eq!(env.wallet_balance(&seller2.pubkey(), &pair.price_mint).await, 250);
+    assert_eq!(env.escrow_balance(&seller2.pubkey(), &pair.token_mint).await, 0);
+
+    assert_error(accept_quote(&mut env, buyer, &rfq, &seller1.pubkey(), &pair).await, P2PSwapError::RfqSettled);
+    reclaim_quote(&mut env, seller1, &rfq, &pair.token_mint).await.unwrap();
+    assert_eq!(env.wallet_balance(&seller1.pubkey(), &pair.token_mint).await, 100);
+    assert_eq!(env.escrow_balance(&seller1.pubkey(), &pair.token_mint).await, 0);
+}
+
+#[tokio::test]
+async fn test_recreated_rfq_does_not_lock_old_quotes() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 100, 10000).await;
+    let (seller, buyer) = (&pair.seller, &pair.buyer);
+    env.create_wallet(&seller.pubkey(), &pair.price_mint).await;
+    env.create_wallet(&buyer.pubkey(), &pair.token_mint).await;
+
+    let deadline = env.clock().await.unix_timestamp + 1000;
+    let rfq = create_rfq(&mut env, buyer, &pair, 100, deadline, 2).await;
+    create_quote(&mut env, seller, &rfq, 300).await.unwrap();
+    close_rfq(&mut env, buyer, &rfq).await;
+
+    // same address, new deadline: the old quote can't be accepted and is reclaimable right away
+    let recreated = create_rfq(&mut env, buyer, &pair, 100, deadline + 10, 2).await;
+    assert_eq!(recreated, rfq);
+    assert_eq!(
+        instruction_error(accept_quote(&mut env, buyer, &rfq, &seller.pubkey(), &pair).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+    reclaim_quote(&mut env, seller, &rfq, &pair.token_mint).await.unwrap();
+    assert_eq!(env.wallet_balance(&seller.pubkey(), &pair.token_mint).await, 100);
+}
@@ -0,0 +1,255 @@
+mod common;
+
+use {
+    common::*,
+    p2p_swap::{
+        get_arbitration_config_address, get_fiat_trade_address, get_reputation_address, FiatTrade, FiatTradeStatus,
+        P2PSwapError, P2PSwapInstructions, Reputation, DISPUTE_RESOLUTION_PERIOD,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        instruction::{AccountMeta, InstructionError},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+    },
+    spl_associated_token_account::get_associated_token_address,
+};
+
+const PAYMENT_WINDOW: i64 = 60 * 60;
+
+// System program as arbitrator means the default arbitrator of the arbitration config
+async fn create_fiat_trade(
+    env: &mut TestEnv,
+    pair: &Pair,
+    arbitrator: &Pubkey,
+    fee_bps: u16,
+    amount: u64,
+    nonce: u64,
+) -> Result<Pubkey, BanksClientError> {
+    let seller = pair.seller.pubkey();
+    let (escrow_authority, escrow_wallet) = env.escrow(&seller, &pair.token_mint);
+    let (trade, _) = get_fiat_trade_address(&env.program_id, &seller, nonce);
+    let accounts = vec![
+        AccountMeta::new(seller, true),
+        AccountMeta::new_readonly(pair.buyer.pubkey(), false),
+        AccountMeta::new_readonly(pair.token_mint, false),
+        AccountMeta::new(get_associated_token_address(&seller, &pair.token_mint), false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(trade, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(env.config(), false),
+        AccountMeta::new_readonly(*arbitrator, false),
+        AccountMeta::new_readonly(get_arbitration_config_address(&env.program_id).0, false),
+    ];
+    let payment_deadline = env.clock().await.unix_timestamp + PAYMENT_WINDOW;
+    let data = [
+        &amount.to_le_bytes()[..],
+        &(amount * 100).to_le_bytes(),
+        b"EUR",
+        &payment_deadline.to_le_bytes(),
+        &nonce.to_le_bytes(),
+        &fee_bps.to_le_bytes(),
+    ]
+    .concat();
+    let instructions = [
+        env.create_wallet_instruction(&escrow_authority, &pair.token_mint),
+        instruction(&env.program_id, P2PSwapInstructions::CreateFiatTrade, &data, accounts),
+    ];
+    env.process(&instructions, &[&pair.seller]).await.map(|_| trade)
+}
+
+async fn mark_fiat_sent(env: &mut TestEnv, buyer: &Keypair, trade: &Pubkey) -> Result<(), BanksClientError> {
+    let accounts = vec![AccountMeta::new_readonly(buyer.pubkey(), true), AccountMeta::new(*trade, false)];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::MarkFiatSent, &[], accounts);
+    env.process(&[instruction], &[buyer]).await
+}
+
+async fn release_fiat_trade(env: &mut TestEnv, seller: &Keypair, trade_address: &Pubkey) -> Result<(), BanksClientError> {
+    let trade = env.get::<FiatTrade>(trade_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&trade.seller, &trade.token_mint);
+    let mut accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new(*trade_address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&trade.buyer, &trade.token_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(trade.buyer, false),
+        AccountMeta::new_readonly(trade.token_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+    accounts.extend(env.reputation_accounts(&trade.seller, &trade.buyer));
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::ReleaseFiatTrade, &[], accounts);
+    env.process(&[instruction], &[seller]).await
+}
+
+async fn cancel_fiat_trade(env: &mut TestEnv, caller: &Keypair, trade_address: &Pubkey) -> Result<(), BanksClientError> {
+    let trade = env.get::<FiatTrade>(trade_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&trade.seller, &trade.token_mint);
+    let accounts = vec![
+        AccountMeta::new(caller.pubkey(), true),
+        AccountMeta::new(trade.seller, false),
+        AccountMeta::new(*trade_address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&trade.seller, &trade.token_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::CancelFiatTrade, &[], accounts);
+    env.process(&[instruction], &[caller]).await
+}
+
+async fn raise_dispute(env: &mut TestEnv, caller: &Keypair, trade: &Pubkey) -> Result<(), BanksClientError> {
+    let accounts = vec![AccountMeta::new_readonly(caller.pubkey(), true), AccountMeta::new(*trade, false)];
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::RaiseDispute, &[], accounts);
+    env.process(&[instruction], &[caller]).await
+}
+
+async fn resolve_dispute(
+    env: &mut TestEnv,
+    arbitrator: &Keypair,
+    trade_address: &Pubkey,
+    to_buyer: bool,
+    fee_bps: u16,
+) -> Result<(), BanksClientError> {
+    let trade = env.get::<FiatTrade>(trade_address).await;
+    let (escrow_authority, escrow_wallet) = env.escrow(&trade.seller, &trade.token_mint);
+    let (winner, loser) = if to_buyer { (trade.buyer, trade.seller) } else { (trade.seller, trade.buyer) };
+    let mut accounts = vec![
+        AccountMeta::new_readonly(arbitrator.pubkey(), true),
+        AccountMeta::new(trade.seller, false),
+        AccountMeta::new(*trade_address, false),
+        AccountMeta::new_readonly(escrow_authority, false),
+        AccountMeta::new(escrow_wallet, false),
+        AccountMeta::new(get_associated_token_address(&winner, &trade.token_mint), false),
+        AccountMeta::new(get_associated_token_address(&arbitrator.pubkey(), &trade.token_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(get_reputation_address(&env.program_id, &loser).0, false),
+    ];
+    if trade.arbitrator != arbitrator.pubkey() {
+        accounts.push(AccountMeta::new_readonly(get_arbitration_config_address(&env.program_id).0, false));
+    }
+    let data = [&[to_buyer as u8][..], &fee_bps.to_le_bytes()].concat();
+    let instruction = instruction(&env.program_id, P2PSwapInstructions::ResolveDispute, &data, accounts);
+    env.process(&[instruction], &[arbitrator]).await
+}
+
+#[tokio::test]
+async fn test_fiat_trade_release() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 0).await;
+    let arbitrator = Pubkey::new_unique();
+
+    // every trade needs an arbitrator
+    assert_error(
+        create_fiat_trade(&mut env, &pair, &system_program::id(), 0, 100, 1).await.map(|_| ()),
+        P2PSwapError::NoArbitrator,
+    );
+
+    let trade = create_fiat_trade(&mut env, &pair, &arbitrator, 100, 100, 1).await.unwrap();
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.token_mint).await, 900);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 100);
+    let state = env.get::<FiatTrade>(&trade).await;
+    assert_eq!(state.arbitrator, arbitrator);
+    assert_eq!(state.fiat_currency, *b"EUR");
+
+    assert_error(cancel_fiat_trade(&mut env, &pair.seller, &trade).await, P2PSwapError::PaymentWindowActive);
+    mark_fiat_sent(&mut env, &pair.buyer, &trade).await.unwrap();
+    assert_eq!(env.get::<FiatTrade>(&trade).await.status, FiatTradeStatus::PaymentSent);
+    assert_error(mark_fiat_sent(&mut env, &pair.buyer, &trade).await, P2PSwapError::PaymentAlreadySent);
+    assert_error(cancel_fiat_trade(&mut env, &pair.seller, &trade).await, P2PSwapError::PaymentAlreadySent);
+
+    let seller_reputation = env.create_reputation(&pair.seller.pubkey()).await;
+    let buyer_reputation = env.create_reputation(&pair.buyer.pubkey()).await;
+    release_fiat_trade(&mut env, &pair.seller, &trade).await.unwrap();
+    assert!(!env.exists(&trade).await);
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.token_mint).await, 100);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 0);
+    assert_eq!(env.get::<Reputation>(&seller_reputation).await.trade_count, 1);
+    assert_eq!(env.get::<Reputation>(&buyer_reputation).await.trade_count, 1);
+}
+
+#[tokio::test]
+async fn test_fiat_trade_cancel() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 0).await;
+    let arbitrator = Pubkey::new_unique();
+
+    // buyer can cancel at any moment
+    let trade = create_fiat_trade(&mut env, &pair, &arbitrator, 0, 100, 1).await.unwrap();
+    mark_fiat_sent(&mut env, &pair.buyer, &trade).await.unwrap();
+    cancel_fiat_trade(&mut env, &pair.buyer, &trade).await.unwrap();
+    assert!(!env.exists(&trade).await);
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.token_mint).await, 1000);
+
+    // seller can cancel once payment window passes without payment
+    let trade = create_fiat_trade(&mut env, &pair, &arbitrator, 0, 200, 2).await.unwrap();
+    let stranger = env.create_user().await;
+    assert_eq!(
+        instruction_error(cancel_fiat_trade(&mut env, &stranger, &trade).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+    env.warp_seconds(PAYMENT_WINDOW).await;
+    assert_error(mark_fiat_sent(&mut env, &pair.buyer, &trade).await, P2PSwapError::PaymentWindowExpired);
+    cancel_fiat_trade(&mut env, &pair.seller, &trade).await.unwrap();
+    assert!(!env.exists(&trade).await);
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.token_mint).await, 1000);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 0);
+}
+
+#[tokio::test]
+async fn test_fiat_disputes() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 0).await;
+    let arbitrator = env.create_user().await;
+    let default_arbitrator = env.create_user().await;
+    env.init_config().await;
+    env.set_arbitration_config(&default_arbitrator.pubkey(), 0).await;
+    // resolving does not create wallets of the winner and the arbitrator
+    env.create_wallet(&pair.buyer.pubkey(), &pair.token_mint).await;
+    env.create_wallet(&arbitrator.pubkey(), &pair.token_mint).await;
+    let seller_reputation = env.create_reputation(&pair.seller.pubkey()).await;
+    let buyer_reputation = env.create_reputation(&pair.buyer.pubkey()).await;
+
+    let trade = create_fiat_trade(&mut env, &pair, &arbitrator.pubkey(), 100, 100, 1).await.unwrap();
+    assert_eq!(
+        instruction_error(raise_dispute(&mut env, &pair.buyer, &trade).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+    mark_fiat_sent(&mut env, &pair.buyer, &trade).await.unwrap();
+    raise_dispute(&mut env, &pair.seller, &trade).await.unwrap();
+    assert_eq!(env.get::<FiatTrade>(&trade).await.status, FiatTradeStatus::Disputed);
+
+    // only arbitrator moves escrowed tokens of disputed trade
+    assert_error(release_fiat_trade(&mut env, &pair.seller, &trade).await, P2PSwapError::TradeDisputed);
+    assert_error(cancel_fiat_trade(&mut env, &pair.buyer, &trade).await, P2PSwapError::TradeDisputed);
+    assert_error(resolve_dispute(&mut env, &default_arbitrator, &trade, true, 0).await, P2PSwapError::NotArbitrator);
+    assert_eq!(
+        instruction_error(resolve_dispute(&mut env, &arbitrator, &trade, true, 200).await),
+        Some(InstructionError::InvalidInstructionData),
+    );
+
+    resolve_dispute(&mut env, &arbitrator, &trade, true, 100).await.unwrap();
+    assert!(!env.exists(&trade).await);
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.token_mint).await, 99);
+    assert_eq!(env.wallet_balance(&arbitrator.pubkey(), &pair.token_mint).await, 1);
+    assert_eq!(env.get::<Reputation>(&seller_reputation).await.disputes_lost, 1);
+    assert_eq!(env.get::<Reputation>(&buyer_reputation).await.disputes_lost, 0);
+
+    // default arbitrator resolves disputes abandoned by the trade arbitrator
+    let trade = create_fiat_trade(&mut env, &pair, &arbitrator.pubkey(), 100, 200, 2).await.unwrap();
+    mark_fiat_sent(&mut env, &pair.buyer, &trade).await.unwrap();
+    raise_dispute(&mut env, &pair.buyer, &trade).await.unwrap();
+    assert_error(resolve_dispute(&mut env, &default_arbitrator, &trade, false, 0).await, P2PSwapError::NotArbitrator);
+
+    env.warp_seconds(DISPUTE_RESOLUTION_PERIOD).await;
+    resolve_dispute(&mut env, &default_arbitrator, &trade, false, 0).await.unwrap();
+    assert!(!env.exists(&trade).await);
+    assert_eq!(env.wallet_balance(&pair.seller.pubkey(), &pair.token_mint).await, 900);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 0);
+    assert_eq!(env.get::<Reputation>(&buyer_reputation).await.disputes_lost, 1);
+}
@@ -0,0 +1,263 @@
+use {
+    p2p_swap::{
+        OrderBook, OrderBookEntry, OrderStatus, P2PSwapError, PayoutRecipient, PayoutSplit, StartAt, SwapSPLOrder,
+        BPS_DENOMINATOR, MAX_ORDER_BOOK_ENTRIES, MAX_PAYOUT_RECIPIENTS,
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+fn order(sell_amount: u64, buy_amount: u64, min_sell_amount: u64) -> SwapSPLOrder {
+    SwapSPLOrder {
+        order_index: 0,
+        seller: Pubkey::new_unique(),
+        sell_amount,
+        order_wallet: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        price_mint: Pubkey::new_unique(),
+        buy_amount,
+        min_sell_amount,
+        remains_to_fill: sell_amount,
+        is_private: false,
+        status: OrderStatus::Active,
+        start_at: StartAt::Immediately,
+        payout_wallet: Pubkey::default(),
+        epoch: 0,
+        gate_mint: Pubkey::default(),
+        gate_min_balance: 0,
+        is_listed: false,
+    }
+}
+
+// xorshift64 - deterministic pseudo-random inputs without extra dependencies
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+}
+
+fn random_order(rng: &mut Rng) -> SwapSPLOrder {
+    let scale = [10, 1_000, 1_000_000, 1_000_000_000_000];
+    let sell_scale = scale[rng.below(4) as usize];
+    let sell_amount = 1 + rng.below(sell_scale);
+    let buy_scale = scale[rng.below(4) as usize];
+    let buy_amount = 1 + rng.below(buy_scale);
+    order(sell_amount, buy_amount, 0)
+}
+
+// payment * sell_amount >= amount * buy_amount means seller gets at least order price
+fn pays_at_least_order_price(order: &SwapSPLOrder, amount: u64, payment: u64) -> bool {
+    (payment as u128) * (order.sell_amount as u128) >= (amount as u128) * (order.buy_amount as u128)
+}
+
+#[test]
+fn seller_never_receives_less_than_order_price() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..100_000 {
+        let order = random_order(&mut rng);
+        let amount = 1 + rng.below(order.sell_amount);
+        let payment = order.price_for_amount(amount).unwrap();
+
+        assert!(payment > 0);
+        assert!(pays_at_least_order_price(&order, amount, payment));
+        // rounding up never adds more than one smallest unit
+        assert!(!pays_at_least_order_price(&order, amount, payment - 1));
+    }
+}
+
+#[test]
+fn split_fills_never_pay_less_than_single_fill() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..10_000 {
+        let mut order = random_order(&mut rng);
+        let whole_payment = order.price_for_amount(order.sell_amount).unwrap();
+
+        let mut paid = 0u128;
+        while order.remains_to_fill > 0 {
+            let amount = 1 + rng.below(order.remains_to_fill.min(1 + order.sell_amount / 8));
+            order.check_fill_amount(amount).unwrap();
+            paid += order.price_for_amount(amount).unwrap() as u128;
+            order.remains_to_fill -= amount;
+        }
+
+        assert!(paid >= whole_payment as u128);
+    }
+}
+
+#[test]
+fn exact_spend_never_exceeds_budget() {
+    let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+    for _ in 0..100_000 {
+        let order = random_order(&mut rng);
+        let budget = rng.below(order.buy_amount.saturating_mul(2).max(1));
+        let amount = order.amount_for_price(budget).unwrap().min(order.remains_to_fill);
+        let payment = order.price_for_amount(amount).unwrap();
+
+        assert!(payment <= budget);
+        assert!(pays_at_least_order_price(&order, amount, payment));
+    }
+}
+
+#[test]
+fn tiny_fills_are_not_free() {
+    // 1 price token buys 1_000_000 order tokens
+    let order = order(1_000_000_000, 1_000, 0);
+    assert_eq!(order.price_for_amount(1).unwrap(), 1);
+    assert_eq!(order.price_for_amount(1_000_000).unwrap(), 1);
+    assert_eq!(order.price_for_amount(1_000_001).unwrap(), 2);
+    assert_eq!(order.price_for_amount(0).unwrap(), 0);
+}
+
+#[test]
+fn dust_below_minimum_can_be_bought_out() {
+    let mut order = order(1_000, 1_000, 300);
+    order.remains_to_fill = 100;
+
+    assert!(matches!(order.check_fill_amount(50), Err(P2PSwapError::BuyAmountBelowMinimum)));
+    assert!(matches!(order.check_fill_amount(101), Err(P2PSwapError::NotEnoughTokensInOrder)));
+    assert!(order.check_fill_amount(100).is_ok());
+
+    order.remains_to_fill = 1_000;
+    assert!(matches!(order.check_fill_amount(299), Err(P2PSwapError::BuyAmountBelowMinimum)));
+    assert!(order.check_fill_amount(300).is_ok());
+}
+
+#[test]
+fn payout_split_shares_never_exceed_payment() {
+    let mut rng = Rng(0x94d0_49bb_1331_11eb);
+    for _ in 0..10_000 {
+        let mut total_bps = 0;
+        let recipients: Vec<PayoutRecipient> = (0..1 + rng.below(MAX_PAYOUT_RECIPIENTS as u64))
+            .map(|_| {
+                let bps = 1 + rng.below((BPS_DENOMINATOR - total_bps) / MAX_PAYOUT_RECIPIENTS as u64);
+                total_bps += bps;
+                PayoutRecipient { wallet: Pubkey::new_unique(), bps: bps as u16 }
+            })
+            .collect();
+        PayoutSplit::check_recipients(&recipients).unwrap();
+
+        let payout_split = PayoutSplit { order: Pubkey::new_unique(), recipients };
+        let amount = rng.below(1_000_000_000_000);
+        let shares = payout_split.shares(amount);
+        let paid: u128 = shares.iter().map(|share| *share as u128).sum();
+
+        assert!(paid <= amount as u128);
+        // the rest left to seller is less than one unit per recipient above seller's exact share
+        let seller_exact = (amount as u128) * ((BPS_DENOMINATOR - total_bps) as u128);
+        let seller_rest = (amount as u128 - paid) * (BPS_DENOMINATOR as u128);
+        assert!(seller_rest >= seller_exact);
+        assert!(seller_rest < seller_exact + (shares.len() as u128) * (BPS_DENOMINATOR as u128));
+    }
+}
+
+#[test]
+fn payout_split_rejects_invalid_shares() {
+    let recipient = |bps| PayoutRecipient { wallet: Pubkey::new_unique(), bps };
+
+    assert!(PayoutSplit::check_recipients(&[recipient(6_000), recipient(4_000)]).is_ok());
+    assert!(matches!(
+        PayoutSplit::check_recipients(&[recipient(6_000), recipient(4_001)]),
+        Err(P2PSwapError::InvalidPayoutSplit),
+    ));
+    assert!(matches!(
+        PayoutSplit::check_recipients(&[recipient(0)]),
+        Err(P2PSwapError::InvalidPayoutSplit),
+    ));
+    assert!(matches!(
+        PayoutSplit::check_recipients(&vec![recipient(1); MAX_PAYOUT_RECIPIENTS + 1]),
+        Err(P2PSwapError::InvalidPayoutSplit),
+    ));
+}
+
+#[test]
+fn flip_reverse_order_price_includes_spread() {
+    let mut rng = Rng(0xbf58_476d_1ce4_e5b9);
+    for _ in 0..100_000 {
+        let order = random_order(&mut rng);
+        let amount = 1 + rng.below(order.sell_amount);
+        let payment = order.price_for_amount(amount).unwrap();
+        let spread_bps = rng.below(BPS_DENOMINATOR) as u16;
+
+        let reverse_buy_amount = order.flip_buy_amount(payment, spread_bps).unwrap();
+
+        // reverse_buy_amount / payment >= order.sell_amount / order.buy_amount / (1 - spread)
+        let wanted = (reverse_buy_amount as u128)
+            * (order.buy_amount as u128)
+            * ((BPS_DENOMINATOR - spread_bps as u64) as u128);
+        let exact = (payment as u128) * (order.sell_amount as u128) * (BPS_DENOMINATOR as u128);
+        assert!(wanted >= exact);
+        assert!(wanted - exact < (order.buy_amount as u128) * ((BPS_DENOMINATOR - spread_bps as u64) as u128));
+    }
+}
+
+#[test]
+fn grid_round_trip_returns_to_order_price() {
+    // ask: 1_000 tokens for 2_000 price tokens, 1% spread both ways
+    let ask = order(1_000, 2_000, 0);
+    let mut bid = order(0, 0, 0);
+
+    let payment = ask.price_for_amount(1_000).unwrap();
+    bid.refill(payment, ask.flip_buy_amount(payment, 100).unwrap()).unwrap();
+    // bid buys tokens 1% cheaper than the ask sells them
+    assert_eq!((bid.sell_amount, bid.buy_amount, bid.remains_to_fill), (2_000, 1_011, 2_000));
+
+    // filling the bid refills the ask at the original price (up to rounding of the bid)
+    let tokens = bid.price_for_amount(bid.sell_amount).unwrap();
+    let mut ask_refilled = order(0, 0, 0);
+    ask_refilled.refill(tokens, bid.flip_buy_amount(tokens, 100).unwrap()).unwrap();
+    assert_eq!((ask_refilled.sell_amount, ask_refilled.buy_amount), (1_011, 2_021));
+}
+
+#[test]
+fn refill_rebases_order_to_remaining_part() {
+    let mut order = order(100, 100, 0);
+    order.remains_to_fill = 0;
+    order.refill(100, 200).unwrap();
+    assert_eq!(order.price_for_amount(100).unwrap(), 200);
+
+    order.remains_to_fill = 50;
+    order.refill(50, 100).unwrap();
+    assert_eq!((order.sell_amount, order.buy_amount, order.remains_to_fill), (100, 200, 100));
+}
+
+#[test]
+fn full_order_book_never_evicts_listed_orders() {
+    let entry = |buy_amount: u64| OrderBookEntry {
+        order: Pubkey::new_unique(),
+        sell_amount: 100,
+        buy_amount,
+        remains_to_fill: 100,
+    };
+
+    let mut order_book = OrderBook {
+        token_mint: Pubkey::new_unique(),
+        price_mint: Pubkey::new_unique(),
+        entries: Vec::new(),
+    };
+    for _ in 0..MAX_ORDER_BOOK_ENTRIES {
+        order_book.insert(entry(200)).unwrap();
+    }
+    let listed: Vec<Pubkey> = order_book.entries.iter().map(|entry| entry.order).collect();
+
+    // neither worse nor better price gets into full book, listed orders stay as is
+    assert!(matches!(order_book.insert(entry(300)), Err(P2PSwapError::OrderBookFull)));
+    assert!(matches!(order_book.insert(entry(150)), Err(P2PSwapError::OrderBookFull)));
+    assert_eq!(order_book.entries.iter().map(|entry| entry.order).collect::<Vec<_>>(), listed);
+
+    // once a listed order is filled, unlisted one fits again
+    order_book.update(&listed[0], 0);
+    let better = entry(150);
+    let better_order = better.order;
+    order_book.insert(better).unwrap();
+    assert_eq!(order_book.entries[0].order, better_order);
+    assert!(order_book.contains(&better_order));
+    assert!(!order_book.contains(&listed[0]));
+}
@@ -0,0 +1,273 @@
+mod common;
+
+use {
+    common::*,
+    p2p_swap::{
+        get_delegate_address, get_flip_address, get_payout_split_address, get_seller_epoch_address, OrderStatus,
+        P2PSwapError, P2PSwapInstructions, PERMISSION_ALL, PERMISSION_PAUSE,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        instruction::{AccountMeta, InstructionError},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_program,
+    },
+};
+
+async fn set_payout_split(
+    env: &mut TestEnv,
+    seller: &Keypair,
+    order: &Pubkey,
+    recipients: &[(Pubkey, u16)],
+) -> Result<(), BanksClientError> {
+    let program_id = env.program_id;
+    let mut accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new_readonly(*order, false),
+        AccountMeta::new(get_payout_split_address(&program_id, order).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(get_flip_address(&program_id, order).0, false),
+    ];
+    let mut data = Vec::new();
+    for (wallet, bps) in recipients {
+        accounts.push(AccountMeta::new_readonly(*wallet, false));
+        data.extend_from_slice(&bps.to_le_bytes());
+    }
+
+    let instruction = instruction(&program_id, P2PSwapInstructions::SetPayoutSplit, &data, accounts);
+    env.process(&[instruction], &[seller]).await
+}
+
+// Empty spread removes flip
+async fn set_flip(
+    env: &mut TestEnv,
+    seller: &Keypair,
+    order: &Pubkey,
+    reverse_order: &Pubkey,
+    spread_bps: Option<u16>,
+) -> Result<(), BanksClientError> {
+    let program_id = env.program_id;
+    let accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new_readonly(*order, false),
+        AccountMeta::new_readonly(*reverse_order, false),
+        AccountMeta::new(get_flip_address(&program_id, order).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(get_payout_split_address(&program_id, order).0, false),
+    ];
+    let data = spread_bps.map_or(Vec::new(), |spread_bps| spread_bps.to_le_bytes().to_vec());
+
+    let instruction = instruction(&program_id, P2PSwapInstructions::SetFlip, &data, accounts);
+    env.process(&[instruction], &[seller]).await
+}
+
+async fn set_delegate(env: &mut TestEnv, seller: &Keypair, operator: &Pubkey, permissions: u8) {
+    let program_id = env.program_id;
+    let accounts = vec![
+        AccountMeta::new(seller.pubkey(), true),
+        AccountMeta::new_readonly(*operator, false),
+        AccountMeta::new(get_delegate_address(&program_id, &seller.pubkey(), operator).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    let instruction = instruction(&program_id, P2PSwapInstructions::SetDelegate, &[permissions], accounts);
+    env.process(&[instruction], &[seller]).await.unwrap();
+}
+
+// Bump by seller, or by operator when given
+async fn bump_epoch(env: &mut TestEnv, signer: &Keypair, seller: &Pubkey) -> Result<(), BanksClientError> {
+    let program_id = env.program_id;
+    let mut accounts = vec![
+        AccountMeta::new(*seller, signer.pubkey() == *seller),
+        AccountMeta::new(get_seller_epoch_address(&program_id, seller).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if signer.pubkey() != *seller {
+        accounts.push(AccountMeta::new(signer.pubkey(), true));
+        accounts.push(AccountMeta::new_readonly(get_delegate_address(&program_id, seller, &signer.pubkey()).0, false));
+    }
+
+    let instruction = instruction(&program_id, P2PSwapInstructions::BumpEpoch, &[], accounts);
+    env.process(&[instruction], &[signer]).await
+}
+
+#[tokio::test]
+async fn test_payout_split() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let seller = pair.seller.pubkey();
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, 0).await;
+
+    let partner1 = env.fund_wallet(&Pubkey::new_unique(), &pair.price_mint, 0).await;
+    let partner2 = env.fund_wallet(&Pubkey::new_unique(), &pair.price_mint, 0).await;
+    assert_error(
+        set_payout_split(&mut env, &pair.seller, &order, &[(partner1, 6000), (partner2, 5000)]).await,
+        P2PSwapError::InvalidPayoutSplit,
+    );
+    set_payout_split(&mut env, &pair.seller, &order, &[(partner1, 1000), (partner2, 2500)]).await.unwrap();
+
+    env.fill(&pair.buyer, &order, 200).await.unwrap();
+    assert_eq!(env.balance(&partner1).await, 20);
+    assert_eq!(env.balance(&partner2).await, 50);
+    assert_eq!(env.wallet_balance(&seller, &pair.price_mint).await, 130);
+
+    // shares are rounded down, the rest goes to seller
+    env.fill(&pair.buyer, &order, 99).await.unwrap();
+    assert_eq!(env.balance(&partner1).await, 29);
+    assert_eq!(env.balance(&partner2).await, 74);
+    assert_eq!(env.wallet_balance(&seller, &pair.price_mint).await, 196);
+
+    // revoking the whole order closes its split
+    let (payout_split, _) = get_payout_split_address(&env.program_id, &order);
+    let split_rent = env.lamports(&payout_split).await;
+    let order_rent = env.lamports(&order).await;
+    let seller_lamports = env.lamports(&seller).await;
+    env.revoke(&pair.seller, &order, 0).await.unwrap();
+    assert!(!env.exists(&payout_split).await);
+    assert_eq!(env.lamports(&seller).await, seller_lamports + split_rent + order_rent);
+    assert_eq!(env.wallet_balance(&seller, &pair.token_mint).await, 701);
+}
+
+#[tokio::test]
+async fn test_flip_refills_reverse_order() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let seller = pair.seller.pubkey();
+    env.create_wallet(&seller, &pair.price_mint).await;
+
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 2000, 0).await;
+    // empty reverse order sells price token for token of the order
+    let reverse_order = env.create_order(&pair.seller, &pair.price_mint, &pair.token_mint, 0, 1, 0).await;
+
+    assert_eq!(
+        instruction_error(set_flip(&mut env, &pair.seller, &order, &order, Some(100)).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+    set_flip(&mut env, &pair.seller, &order, &reverse_order, Some(100)).await.unwrap();
+
+    // payment for 100 tokens is 200, reverse order asks for 200 * 1000 / 2000 improved by 1% spread
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+    let reverse = env.order(&reverse_order).await;
+    assert_eq!((reverse.sell_amount, reverse.buy_amount, reverse.remains_to_fill), (200, 102, 200));
+    assert_eq!(env.escrow_balance(&seller, &pair.price_mint).await, 200);
+    assert_eq!(env.wallet_balance(&seller, &pair.price_mint).await, 0);
+
+    // next fill is added to what remains at the current reverse price
+    env.fill(&pair.buyer, &order, 50).await.unwrap();
+    let reverse = env.order(&reverse_order).await;
+    assert_eq!((reverse.sell_amount, reverse.buy_amount, reverse.remains_to_fill), (300, 153, 300));
+    assert_eq!(env.escrow_balance(&seller, &pair.price_mint).await, 300);
+
+    // reverse order is filled like any other order, seller is paid in token of the order
+    let buyer = env.create_user().await;
+    env.fund_wallet(&buyer.pubkey(), &pair.token_mint, 100).await;
+    env.fill(&buyer, &reverse_order, 100).await.unwrap();
+    assert_eq!(env.wallet_balance(&buyer.pubkey(), &pair.price_mint).await, 100);
+    assert_eq!(env.wallet_balance(&seller, &pair.token_mint).await, 51);
+    assert_eq!(env.escrow_balance(&seller, &pair.price_mint).await, 200);
+
+    let partner = env.fund_wallet(&Pubkey::new_unique(), &pair.price_mint, 0).await;
+    assert_error(
+        set_payout_split(&mut env, &pair.seller, &order, &[(partner, 1000)]).await,
+        P2PSwapError::FlipPayoutConflict,
+    );
+
+    // without flip payments go to seller again
+    set_flip(&mut env, &pair.seller, &order, &reverse_order, None).await.unwrap();
+    assert!(!env.exists(&get_flip_address(&env.program_id, &order).0).await);
+    env.fill(&pair.buyer, &order, 10).await.unwrap();
+    assert_eq!(env.wallet_balance(&seller, &pair.price_mint).await, 20);
+    assert_eq!(env.escrow_balance(&seller, &pair.price_mint).await, 200);
+}
+
+#[tokio::test]
+async fn test_delegate_permissions() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let seller = pair.seller.pubkey();
+    let operator = env.create_user().await;
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, 0).await;
+
+    set_delegate(&mut env, &pair.seller, &operator.pubkey(), PERMISSION_PAUSE).await;
+    env.set_order_status(&operator, &seller, &order, P2PSwapInstructions::PauseOrder).await.unwrap();
+    assert_eq!(env.order(&order).await.status, OrderStatus::Paused);
+    assert_error(env.revoke(&operator, &order, 100).await, P2PSwapError::OperatorNotAllowed);
+
+    // operator revokes on behalf of seller: tokens and rent go to seller
+    set_delegate(&mut env, &pair.seller, &operator.pubkey(), PERMISSION_ALL).await;
+    env.revoke(&operator, &order, 100).await.unwrap();
+    assert_eq!(env.wallet_balance(&seller, &pair.token_mint).await, 100);
+    assert_eq!(env.order(&order).await.remains_to_fill, 900);
+
+    let order_rent = env.lamports(&order).await;
+    let seller_lamports = env.lamports(&seller).await;
+    let operator_lamports = env.lamports(&operator.pubkey()).await;
+    env.revoke(&operator, &order, 0).await.unwrap();
+    assert!(!env.exists(&order).await);
+    assert_eq!(env.wallet_balance(&seller, &pair.token_mint).await, 1000);
+    assert_eq!(env.lamports(&seller).await, seller_lamports + order_rent);
+    assert_eq!(env.lamports(&operator.pubkey()).await, operator_lamports);
+
+    // removed operator can't manage orders anymore
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 500, 500, 0).await;
+    set_delegate(&mut env, &pair.seller, &operator.pubkey(), 0).await;
+    assert!(!env.exists(&get_delegate_address(&env.program_id, &seller, &operator.pubkey()).0).await);
+    assert_error(
+        env.set_order_status(&operator, &seller, &order, P2PSwapInstructions::PauseOrder).await,
+        P2PSwapError::OperatorNotAllowed,
+    );
+}
+
+#[tokio::test]
+async fn test_bump_epoch_cancels_orders() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let seller = pair.seller.pubkey();
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 600, 600, 0).await;
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+
+    assert_error(bump_epoch(&mut env, &pair.buyer, &seller).await, P2PSwapError::OperatorNotAllowed);
+    bump_epoch(&mut env, &pair.seller, &seller).await.unwrap();
+    assert_error(env.fill(&pair.buyer, &order, 100).await, P2PSwapError::OrderEpochExpired);
+
+    // anyone cleans up cancelled order, tokens and rent go to seller
+    let order_rent = env.lamports(&order).await;
+    let seller_lamports = env.lamports(&seller).await;
+    env.revoke(&pair.buyer, &order, 0).await.unwrap();
+    assert!(!env.exists(&order).await);
+    assert_eq!(env.wallet_balance(&seller, &pair.token_mint).await, 900);
+    assert_eq!(env.lamports(&seller).await, seller_lamports + order_rent);
+
+    // orders created after the bump are not affected
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 500, 500, 0).await;
+    assert_eq!(env.order(&order).await.epoch, 1);
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_gated_order() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let buyer = pair.buyer.pubkey();
+    let gate_mint = env.create_mint(0).await;
+    let order = env
+        .try_create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, OrderOptions {
+            gate: Some((gate_mint, 10)),
+            ..OrderOptions::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        instruction_error(env.fill(&pair.buyer, &order, 100).await),
+        Some(InstructionError::IncorrectProgramId),
+    );
+
+    let gate_wallet = env.fund_wallet(&buyer, &gate_mint, 5).await;
+    assert_error(env.fill(&pair.buyer, &order, 100).await, P2PSwapError::GateTokenRequired);
+
+    env.mint_to(&gate_mint, &gate_wallet, 5).await;
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+    assert_eq!(env.wallet_balance(&buyer, &pair.token_mint).await, 100);
+}
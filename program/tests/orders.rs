@@ -0,0 +1,416 @@
+mod common;
+
+use {
+    common::*,
+    p2p_swap::{
+        get_market_address, get_order_address, get_order_book_address, get_reputation_address,
+        get_seller_epoch_address, get_trade_receipt_address, OrderBook, OrderStatus, P2PSwapError,
+        P2PSwapInstructions, Reputation, StartAt, SwapSPLOrder, TradeReceipt,
+    },
+    solana_program_test::BanksClientError,
+    solana_sdk::{
+        account::Account,
+        instruction::{AccountMeta, InstructionError},
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::Signer,
+        system_program,
+    },
+};
+
+async fn list_order(env: &mut TestEnv, order_address: &Pubkey) -> Result<(), BanksClientError> {
+    let order = env.order(order_address).await;
+    let program_id = env.program_id;
+    let accounts = vec![
+        AccountMeta::new(env.payer(), true),
+        AccountMeta::new(*order_address, false),
+        AccountMeta::new(get_order_book_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+        AccountMeta::new_readonly(get_market_address(&program_id, &order.token_mint, &order.price_mint).0, false),
+        AccountMeta::new_readonly(get_seller_epoch_address(&program_id, &order.seller).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    let instruction = instruction(&program_id, P2PSwapInstructions::ListOrder, &[], accounts);
+    env.process(&[instruction], &[]).await
+}
+
+async fn order_book(env: &mut TestEnv, pair: &Pair) -> OrderBook {
+    let (order_book, _) = get_order_book_address(&env.program_id, &pair.token_mint, &pair.price_mint);
+    env.get(&order_book).await
+}
+
+#[tokio::test]
+async fn test_orders_share_order_wallet() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 2000, 10000).await;
+    let (seller, buyer) = (&pair.seller.pubkey(), &pair.buyer.pubkey());
+
+    let order1 = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 2000, 100).await;
+    let order2 = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 500, 500, 50).await;
+    assert_eq!(env.escrow_balance(seller, &pair.token_mint).await, 1500);
+    assert_eq!(env.wallet_balance(seller, &pair.token_mint).await, 500);
+
+    env.fill(&pair.buyer, &order1, 300).await.unwrap();
+    assert_eq!(env.escrow_balance(seller, &pair.token_mint).await, 1200);
+    assert_eq!(env.wallet_balance(buyer, &pair.token_mint).await, 300);
+    assert_eq!(env.wallet_balance(buyer, &pair.price_mint).await, 9400);
+    assert_eq!(env.wallet_balance(seller, &pair.price_mint).await, 600);
+    assert_eq!(env.order(&order1).await.remains_to_fill, 700);
+    assert_eq!(env.order(&order2).await.remains_to_fill, 500);
+
+    // wallet holds tokens of both orders, but an order can't give away more than it has
+    assert_error(env.fill(&pair.buyer, &order1, 800).await, P2PSwapError::NotEnoughTokensInOrder);
+
+    env.revoke(&pair.seller, &order1, 200).await.unwrap();
+    assert_eq!(env.escrow_balance(seller, &pair.token_mint).await, 1000);
+    assert_eq!(env.wallet_balance(seller, &pair.token_mint).await, 700);
+    assert_eq!(env.order(&order1).await.remains_to_fill, 500);
+
+    // only seller revokes unfinished orders
+    assert_eq!(
+        instruction_error(env.revoke(&pair.buyer, &order2, 0).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+
+    env.fill(&pair.buyer, &order1, 500).await.unwrap();
+    assert_eq!(env.order(&order1).await.remains_to_fill, 0);
+    assert_eq!(env.escrow_balance(seller, &pair.token_mint).await, 500);
+
+    // anyone closes finished order and gets its rent
+    let order_rent = env.lamports(&order1).await;
+    let buyer_lamports = env.lamports(buyer).await;
+    env.revoke(&pair.buyer, &order1, 0).await.unwrap();
+    assert!(!env.exists(&order1).await);
+    assert_eq!(env.lamports(buyer).await, buyer_lamports + order_rent);
+    assert_eq!(env.escrow_balance(seller, &pair.token_mint).await, 500);
+
+    env.revoke(&pair.seller, &order2, 0).await.unwrap();
+    assert!(!env.exists(&order2).await);
+    assert_eq!(env.escrow_balance(seller, &pair.token_mint).await, 0);
+    assert_eq!(env.wallet_balance(seller, &pair.token_mint).await, 1200);
+    assert_eq!(env.wallet_balance(seller, &pair.price_mint).await, 1600);
+}
+
+#[tokio::test]
+async fn test_fill_exact_spend() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 3000, 0).await;
+
+    env.fill_exact_spend(&pair.buyer, &order, 1000).await.unwrap();
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.token_mint).await, 333);
+    assert_eq!(env.wallet_balance(&pair.buyer.pubkey(), &pair.price_mint).await, 10000 - 999);
+    assert_eq!(env.order(&order).await.remains_to_fill, 667);
+
+    assert_error(env.fill_exact_spend(&pair.buyer, &order, 2).await, P2PSwapError::BuyAmountBelowMinimum);
+}
+
+#[tokio::test]
+async fn test_fill_minimum_amount() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, 400).await;
+
+    assert_error(env.fill(&pair.buyer, &order, 100).await, P2PSwapError::BuyAmountBelowMinimum);
+    env.fill(&pair.buyer, &order, 700).await.unwrap();
+
+    // the rest is below minimum and can only be bought entirely
+    assert_error(env.fill(&pair.buyer, &order, 200).await, P2PSwapError::BuyAmountBelowMinimum);
+    env.fill(&pair.buyer, &order, 300).await.unwrap();
+    assert_eq!(env.order(&order).await.remains_to_fill, 0);
+    assert_eq!(env.escrow_balance(&pair.seller.pubkey(), &pair.token_mint).await, 0);
+}
+
+#[tokio::test]
+async fn test_pause_and_resume_order() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let seller = pair.seller.pubkey();
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, 0).await;
+
+    assert_error(
+        env.set_order_status(&pair.buyer, &seller, &order, P2PSwapInstructions::PauseOrder).await,
+        P2PSwapError::OperatorNotAllowed,
+    );
+
+    env.set_order_status(&pair.seller, &seller, &order, P2PSwapInstructions::PauseOrder).await.unwrap();
+    assert_eq!(env.order(&order).await.status, OrderStatus::Paused);
+    assert_error(env.fill(&pair.buyer, &order, 100).await, P2PSwapError::OrderPaused);
+
+    env.set_order_status(&pair.seller, &seller, &order, P2PSwapInstructions::ResumeOrder).await.unwrap();
+    assert_eq!(env.order(&order).await.status, OrderStatus::Active);
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_order_start_slot() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let start_slot = env.clock().await.slot + 100;
+    let order = env
+        .try_create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, OrderOptions {
+            start_at: StartAt::Slot(start_slot),
+            ..OrderOptions::default()
+        })
+        .await
+        .unwrap();
+
+    assert_error(env.fill(&pair.buyer, &order, 100).await, P2PSwapError::OrderNotStarted);
+
+    env.warp_slots(100).await;
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_private_order_unlock() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let options = OrderOptions {
+        is_private: true,
+        ..OrderOptions::default()
+    };
+    let order = env
+        .try_create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 500, 500, options)
+        .await
+        .unwrap();
+    let other_order = env
+        .try_create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 500, 500, options)
+        .await
+        .unwrap();
+    let buyer = pair.buyer.pubkey();
+
+    let fill = env.fill_instruction(&buyer, &order, 100, false).await;
+    assert_eq!(
+        instruction_error(env.process(std::slice::from_ref(&fill), &[&pair.buyer]).await),
+        Some(InstructionError::InvalidArgument),
+    );
+
+    let unlock = TestEnv::unlock_instruction(&pair.buyer, &order);
+    assert_error(
+        env.process(&[unlock, fill.clone()], &[&pair.buyer]).await,
+        P2PSwapError::UnlockInstructionInvalid,
+    );
+
+    let unlock = TestEnv::unlock_instruction(&pair.seller, &other_order);
+    assert_error(
+        env.process(&[unlock, fill.clone()], &[&pair.buyer]).await,
+        P2PSwapError::UnlockInstructionInvalid,
+    );
+
+    let unlock = TestEnv::unlock_instruction(&pair.seller, &order);
+    env.process(&[unlock, fill], &[&pair.buyer]).await.unwrap();
+    assert_eq!(env.order(&order).await.remains_to_fill, 400);
+    assert_eq!(env.wallet_balance(&buyer, &pair.token_mint).await, 100);
+}
+
+#[tokio::test]
+async fn test_market_and_order_book() {
+    let mut env = TestEnv::start().await;
+    env.init_config().await;
+    let pair = setup_pair(&mut env, 10000, 10000).await;
+    let (token_mint, price_mint) = (pair.token_mint, pair.price_mint);
+
+    // orders created before the market are not listed
+    let early_order = env.create_order(&pair.seller, &token_mint, &price_mint, 200, 400, 0).await;
+    let small_order = env.create_order(&pair.seller, &token_mint, &price_mint, 50, 100, 0).await;
+    assert_eq!(
+        instruction_error(list_order(&mut env, &early_order).await),
+        Some(InstructionError::UninitializedAccount),
+    );
+
+    assert_error(
+        env.create_market(&pair.seller, &token_mint, &price_mint, 1, 10, 100).await,
+        P2PSwapError::NotAdmin,
+    );
+    let admin = env.admin();
+    env.create_market(&admin, &token_mint, &price_mint, 1, 10, 100).await.unwrap();
+
+    let options = OrderOptions {
+        min_sell_amount: 10,
+        ..OrderOptions::default()
+    };
+    for (sell_amount, buy_amount, error) in [
+        (50, 100, P2PSwapError::OrderBelowMinimumSize),
+        (105, 210, P2PSwapError::InvalidLotSize),
+        (100, 15, P2PSwapError::InvalidTickSize),
+    ] {
+        assert_error(
+            env.try_create_order(&pair.seller, &token_mint, &price_mint, sell_amount, buy_amount, options)
+                .await
+                .map(|_| ()),
+            error,
+        );
+    }
+
+    let order = env.create_order(&pair.seller, &token_mint, &price_mint, 200, 400, 10).await;
+    assert!(env.order(&order).await.is_listed);
+    assert_eq!(order_book(&mut env, &pair).await.entries.len(), 1);
+
+    assert_error(env.fill(&pair.buyer, &order, 15).await, P2PSwapError::InvalidLotSize);
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+    let book = order_book(&mut env, &pair).await;
+    assert_eq!(book.entries[0].order, order);
+    assert_eq!(book.entries[0].remains_to_fill, 100);
+
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+    assert!(order_book(&mut env, &pair).await.entries.is_empty());
+    assert!(!env.order(&order).await.is_listed);
+
+    list_order(&mut env, &early_order).await.unwrap();
+    assert!(env.order(&early_order).await.is_listed);
+    assert!(order_book(&mut env, &pair).await.contains(&early_order));
+    assert_eq!(
+        instruction_error(list_order(&mut env, &early_order).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+
+    assert_error(list_order(&mut env, &small_order).await, P2PSwapError::OrderBelowMinimumSize);
+
+    // revoke takes the order out of the book
+    env.revoke(&pair.seller, &early_order, 0).await.unwrap();
+    assert!(order_book(&mut env, &pair).await.entries.is_empty());
+}
+
+#[tokio::test]
+async fn test_config_pause_and_mint_lists() {
+    let mut env = TestEnv::start().await;
+    env.init_config().await;
+    let pair = setup_pair(&mut env, 10000, 10000).await;
+    let (token_mint, price_mint) = (pair.token_mint, pair.price_mint);
+    let order = env.create_order(&pair.seller, &token_mint, &price_mint, 1000, 1000, 0).await;
+
+    env.admin_instruction(P2PSwapInstructions::SetPaused, &[1]).await.unwrap();
+    assert_error(
+        env.try_create_order(&pair.seller, &token_mint, &price_mint, 100, 100, OrderOptions::default())
+            .await
+            .map(|_| ()),
+        P2PSwapError::ProgramPaused,
+    );
+    assert_error(env.fill(&pair.buyer, &order, 100).await, P2PSwapError::ProgramPaused);
+    env.admin_instruction(P2PSwapInstructions::SetPaused, &[0]).await.unwrap();
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+
+    // denylist
+    env.admin_instruction(P2PSwapInstructions::SetMintListMode, &[1]).await.unwrap();
+    env.set_mint_status(&token_mint, 2).await;
+    assert_error(
+        env.try_create_order(&pair.seller, &token_mint, &price_mint, 103, 100, OrderOptions::default())
+            .await
+            .map(|_| ()),
+        P2PSwapError::MintNotAllowed,
+    );
+
+    // allowlist needs both mints to be allowed
+    env.admin_instruction(P2PSwapInstructions::SetMintListMode, &[2]).await.unwrap();
+    env.set_mint_status(&token_mint, 1).await;
+    assert_error(
+        env.try_create_order(&pair.seller, &token_mint, &price_mint, 101, 100, OrderOptions::default())
+            .await
+            .map(|_| ()),
+        P2PSwapError::MintNotAllowed,
+    );
+    env.set_mint_status(&price_mint, 1).await;
+    env.try_create_order(&pair.seller, &token_mint, &price_mint, 102, 100, OrderOptions::default())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_order() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 0, 10000).await;
+    let seller = pair.seller.pubkey();
+    let creation_slot = 42u64;
+    let (order, _) = get_order_address(&env.program_id, &seller, creation_slot);
+    let order_wallet = env.create_escrow_wallet(&seller, &pair.token_mint).await;
+    env.mint_to(&pair.token_mint, &order_wallet, 1000).await;
+
+    let legacy_data = [
+        &creation_slot.to_le_bytes()[..],
+        seller.as_ref(),
+        &1000u64.to_le_bytes(),
+        order_wallet.as_ref(),
+        pair.token_mint.as_ref(),
+        pair.price_mint.as_ref(),
+        &2000u64.to_le_bytes(),
+        &0u64.to_le_bytes(),
+        &800u64.to_le_bytes(),
+        &[0],
+    ]
+    .concat();
+    assert_eq!(legacy_data.len(), SwapSPLOrder::LEGACY_LEN);
+    let rent = env.context.banks_client.get_rent().await.unwrap();
+    env.set_account(&order, Account {
+        lamports: rent.minimum_balance(legacy_data.len()),
+        data: legacy_data,
+        owner: env.program_id,
+        ..Account::default()
+    });
+
+    let migrate = |env: &TestEnv| {
+        instruction(&env.program_id, P2PSwapInstructions::MigrateOrder, &[], vec![
+            AccountMeta::new(env.payer(), true),
+            AccountMeta::new(order, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ])
+    };
+    env.process(&[migrate(&env)], &[]).await.unwrap();
+
+    let migrated = env.order(&order).await;
+    assert_eq!(migrated.order_index, creation_slot);
+    assert_eq!(migrated.seller, seller);
+    assert_eq!(migrated.remains_to_fill, 800);
+    assert_eq!(migrated.status, OrderStatus::Active);
+    assert_eq!(env.lamports(&order).await, rent.minimum_balance(SwapSPLOrder::LEN));
+
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+    assert_eq!(env.order(&order).await.remains_to_fill, 700);
+    assert_eq!(env.wallet_balance(&seller, &pair.price_mint).await, 200);
+
+    env.warp_slots(1).await;
+    assert_eq!(
+        instruction_error(env.process(&[migrate(&env)], &[]).await),
+        Some(InstructionError::InvalidAccountData),
+    );
+}
+
+#[tokio::test]
+async fn test_fill_records_reputation_and_rating() {
+    let mut env = TestEnv::start().await;
+    let pair = setup_pair(&mut env, 1000, 10000).await;
+    let (seller, buyer) = (pair.seller.pubkey(), pair.buyer.pubkey());
+    let order = env.create_order(&pair.seller, &pair.token_mint, &pair.price_mint, 1000, 1000, 0).await;
+    let seller_reputation = env.create_reputation(&seller).await;
+    let buyer_reputation = env.create_reputation(&buyer).await;
+
+    env.fill(&pair.buyer, &order, 100).await.unwrap();
+    env.fill(&pair.buyer, &order, 150).await.unwrap();
+    for reputation in [seller_reputation, buyer_reputation] {
+        assert_eq!(env.get::<Reputation>(&reputation).await.trade_count, 2);
+    }
+
+    let (receipt, _) = get_trade_receipt_address(&env.program_id, &order, &buyer);
+    let trade_receipt = env.get::<TradeReceipt>(&receipt).await;
+    assert_eq!(trade_receipt.seller, seller);
+    assert_eq!(trade_receipt.volume, 250);
+
+    let rate = |env: &TestEnv, rater: &Pubkey, rating: u8| {
+        let counterparty = if *rater == seller { buyer } else { seller };
+        instruction(&env.program_id, P2PSwapInstructions::RateTrade, &[rating], vec![
+            AccountMeta::new_readonly(*rater, true),
+            AccountMeta::new(buyer, false),
+            AccountMeta::new(receipt, false),
+            AccountMeta::new(get_reputation_address(&env.program_id, &counterparty).0, false),
+        ])
+    };
+
+    env.process(&[rate(&env, &buyer, 5)], &[&pair.buyer]).await.unwrap();
+    assert_error(env.process(&[rate(&env, &buyer, 4)], &[&pair.buyer]).await, P2PSwapError::AlreadyRated);
+    let reputation = env.get::<Reputation>(&seller_reputation).await;
+    assert_eq!((reputation.rating_sum, reputation.rating_count), (5, 1));
+
+    // receipt is closed once both sides rated the trade
+    env.process(&[rate(&env, &seller, 3)], &[&pair.seller]).await.unwrap();
+    let reputation = env.get::<Reputation>(&buyer_reputation).await;
+    assert_eq!((reputation.rating_sum, reputation.rating_count), (3, 1));
+    assert!(!env.exists(&receipt).await);
+}
@@ -1,6 +1,6 @@
 import React from "react";
 import {ValueEdit} from "./ValueEdit";
-import { OrderDescriptionData } from "../p2p-swap";
+import { OrderDescriptionData, OrderStatus } from "../p2p-swap";
 
 function OrderDescription({description}: {description: OrderDescriptionData|null}) {
     if (!description) {
@@ -13,8 +13,8 @@ function OrderDescription({description}: {description: OrderDescriptionData|null
         <div className='vertical'>
             <div className="table-like">
                 <ValueEdit
-                    name={"Creation slot:"}
-                    value={description.creationSlot.toString()}
+                    name={"Order index:"}
+                    value={description.orderIndex.toString()}
                     readonly={true}
                 />
                 <ValueEdit
@@ -66,6 +66,21 @@ function OrderDescription({description}: {description: OrderDescriptionData|null
                     value={description.isPrivate.toString()}
                     readonly={true}
                 />
+                <ValueEdit
+                    name={"Status:"}
+                    value={OrderStatus[description.status]}
+                    readonly={true}
+                />
+                <ValueEdit
+                    name={"Is Listed:"}
+                    value={description.isListed.toString()}
+                    readonly={true}
+                />
+                <ValueEdit
+                    name={"Legacy layout:"}
+                    value={description.isLegacy.toString()}
+                    readonly={true}
+                />
             </div>
         </div>
     )
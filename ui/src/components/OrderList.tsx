@@ -4,6 +4,7 @@ import { Button } from "./Button";
 import {
     amountToDecimal,
     ORDER_ACCOUNT_SIZE,
+    LEGACY_ORDER_ACCOUNT_SIZE,
     OrderDescriptionData,
     P2P_SWAP_DEVNET,
     parseOrderDescription
@@ -65,11 +66,15 @@ export const OrderList: FC<OrderListProps> = (props) => {
     let [pageMessage, setPageMessage] = useState<string|null>(null);
 
     const updateOrders = async () => {
-        let accounts = await connection.getProgramAccounts(
-            P2P_SWAP_DEVNET,
-            {
-                filters: [{dataSize: ORDER_ACCOUNT_SIZE}]
-            })
+        // legacy orders are listed too, they are migrated by the first fill
+        let accounts = (await Promise.all(
+            [ORDER_ACCOUNT_SIZE, LEGACY_ORDER_ACCOUNT_SIZE].map(dataSize => connection.getProgramAccounts(
+                P2P_SWAP_DEVNET,
+                {
+                    filters: [{dataSize: dataSize}]
+                })
+            )
+        )).flat();
 
         let tokens: Set<string> = new Set();
         let orders: Map<string, OrderDescriptionData> = new Map();
@@ -96,7 +96,6 @@ function SellTab() {
                 sellAmount: sellAmountParsed,
                 buyAmount: buyAmountParsed,
                 minSellAmount: sellMinimBigint,
-                creationSlot: BigInt(0), // will be overriden later
                 signer: signer,
                 sellToken: sellToken.address,
                 buyToken: buyToken.address,
@@ -115,8 +114,7 @@ function SellTab() {
             return;
         }
 
-        let creationSlot = await connection.getSlot();
-        createOrderProps.creationSlot = BigInt(creationSlot);
+        // order index is read from order counter of the seller
         let [transaction, orderAccount] =
             await createOrderTransaction(connection, createOrderProps);
 
@@ -3,7 +3,7 @@ import {
     Connection, Ed25519Program,
     PublicKey,
     SystemProgram,
-    SYSVAR_CLOCK_PUBKEY, SYSVAR_INSTRUCTIONS_PUBKEY,
+    SYSVAR_INSTRUCTIONS_PUBKEY,
     Transaction,
     TransactionInstruction
 } from "@solana/web3.js";
@@ -13,6 +13,7 @@ import {
     Mint as TokenMint,
     getMint as getTokenMint,
     TOKEN_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID,
     createAssociatedTokenAccountInstruction,
     createApproveInstruction,
     getAssociatedTokenAddress
@@ -20,8 +21,25 @@ import {
 import Decimal from "decimal.js";
 const base58 = require('base58-js');
 
+enum OrderStatus {
+    Active = 0,
+    Paused = 1,
+}
+
+enum StartAtKind {
+    Immediately = 0,
+    Slot = 1,
+    UnixTimestamp = 2,
+}
+
+interface StartAt {
+    kind: StartAtKind,
+    value: bigint,
+}
+
 interface OrderDescriptionData {
-    creationSlot: bigint,
+    // creation slot for orders in legacy layout, it is the order address seed in both layouts
+    orderIndex: bigint,
     seller: PublicKey,
     sellAmount: bigint,
     orderWallet: PublicKey,
@@ -31,21 +49,41 @@ interface OrderDescriptionData {
     minSellAmount: bigint,
     remainsToFill: bigint,
     isPrivate: boolean,
+    status: OrderStatus,
+    startAt: StartAt,
+    // PublicKey.default means associated token account of seller
+    payoutWallet: PublicKey,
+    epoch: bigint,
+    // PublicKey.default if order is not gated
+    gateMint: PublicKey,
+    gateMinBalance: bigint,
+    isListed: boolean,
+    // order created before order counter was introduced. It should be migrated before any use
+    isLegacy: boolean,
     sellToken?: TokenMint,
     buyToken?: TokenMint,
 }
 
-const ORDER_ACCOUNT_SIZE = 169;
+const ORDER_ACCOUNT_SIZE = 260;
+const LEGACY_ORDER_ACCOUNT_SIZE = 169;
+
+function parseBool(value: number): boolean {
+    switch (value) {
+        case 0: return false;
+        case 1: return true;
+        default: throw "Account is not p2p order";
+    }
+}
 
 function parseOrderDescription(
     data: Buffer
 ): OrderDescriptionData {
-    if (data.length != ORDER_ACCOUNT_SIZE) {
+    if (data.length != ORDER_ACCOUNT_SIZE && data.length != LEGACY_ORDER_ACCOUNT_SIZE) {
         throw "Account is not p2p order";
     }
 
-    let view = new DataView(data.slice(0, ORDER_ACCOUNT_SIZE).buffer, 0);
-    let creationSlot = view.getBigUint64(0, true);
+    let view = new DataView(data.buffer, data.byteOffset, data.length);
+    let orderIndex = view.getBigUint64(0, true);
     let seller = new PublicKey(data.slice(8, 40));
     let sellAmount = view.getBigUint64(40, true);
     let orderWallet = new PublicKey(data.slice(48, 80));
@@ -54,10 +92,41 @@ function parseOrderDescription(
     let buyAmount = view.getBigUint64(144, true);
     let minSellAmount = view.getBigUint64(152, true);
     let remainsToFill = view.getBigUint64(160, true);
-    let isPrivate = view.getUint8(168) != 0;
+    let isPrivate = parseBool(view.getUint8(168));
+    let isLegacy = data.length == LEGACY_ORDER_ACCOUNT_SIZE;
+
+    // legacy orders get the same defaults as the program gives them on migration
+    let status = OrderStatus.Active;
+    let startAt: StartAt = { kind: StartAtKind.Immediately, value: 0n };
+    let payoutWallet = PublicKey.default;
+    let epoch = 0n;
+    let gateMint = PublicKey.default;
+    let gateMinBalance = 0n;
+    let isListed = false;
+    if (!isLegacy) {
+        status = view.getUint8(169);
+        if (!(status in OrderStatus)) {
+            throw "Account is not p2p order";
+        }
+        let startAtKind = view.getUint8(170);
+        if (!(startAtKind in StartAtKind)) {
+            throw "Account is not p2p order";
+        }
+        startAt = {
+            kind: startAtKind,
+            value: startAtKind == StartAtKind.UnixTimestamp
+                ? view.getBigInt64(171, true)
+                : view.getBigUint64(171, true),
+        };
+        payoutWallet = new PublicKey(data.slice(179, 211));
+        epoch = view.getBigUint64(211, true);
+        gateMint = new PublicKey(data.slice(219, 251));
+        gateMinBalance = view.getBigUint64(251, true);
+        isListed = parseBool(view.getUint8(259));
+    }
 
     return {
-        creationSlot: creationSlot,
+        orderIndex: orderIndex,
         seller: seller,
         sellAmount: sellAmount,
         orderWallet: orderWallet,
@@ -67,6 +136,14 @@ function parseOrderDescription(
         minSellAmount: minSellAmount,
         remainsToFill: remainsToFill,
         isPrivate: isPrivate,
+        status: status,
+        startAt: startAt,
+        payoutWallet: payoutWallet,
+        epoch: epoch,
+        gateMint: gateMint,
+        gateMinBalance: gateMinBalance,
+        isListed: isListed,
+        isLegacy: isLegacy,
     };
 }
 
@@ -136,17 +213,135 @@ function getOrderWalletAddress(sellTokenMint: PublicKey, authority: PublicKey):
 function getOrderAddress(
     programId: PublicKey,
     seller: PublicKey,
-    creationSlot: bigint
+    orderIndex: bigint
 ): [PublicKey, number] {
     let prefix = Buffer.from("OrderAccount", "utf-8");
     let sellerBytes = seller.toBytes();
-    let creationSlotBytes = Buffer.from(int64ToBytes(creationSlot));
+    let orderIndexBytes = Buffer.from(int64ToBytes(orderIndex));
+    return PublicKey.findProgramAddressSync(
+        [prefix, sellerBytes, orderIndexBytes],
+        programId
+    );
+}
+
+function getOrderCounterAddress(programId: PublicKey, seller: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("OrderCounter"), seller.toBytes()],
+        programId
+    );
+}
+
+function getMarketAddress(programId: PublicKey, tokenMint: PublicKey, priceMint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("Market"), tokenMint.toBytes(), priceMint.toBytes()],
+        programId
+    );
+}
+
+function getOrderBookAddress(programId: PublicKey, tokenMint: PublicKey, priceMint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("OrderBook"), tokenMint.toBytes(), priceMint.toBytes()],
+        programId
+    );
+}
+
+function getConfigAddress(programId: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync([Buffer.from("Config")], programId);
+}
+
+function getMintListEntryAddress(programId: PublicKey, mint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("MintListEntry"), mint.toBytes()],
+        programId
+    );
+}
+
+function getPayoutSplitAddress(programId: PublicKey, order: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("PayoutSplit"), order.toBytes()],
+        programId
+    );
+}
+
+function getReputationAddress(programId: PublicKey, wallet: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("Reputation"), wallet.toBytes()],
+        programId
+    );
+}
+
+function getTradeReceiptAddress(programId: PublicKey, order: PublicKey, buyer: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("TradeReceipt"), order.toBytes(), buyer.toBytes()],
+        programId
+    );
+}
+
+function getSellerEpochAddress(programId: PublicKey, seller: PublicKey): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
-        [prefix, sellerBytes, creationSlotBytes],
+        [Buffer.from("SellerEpoch"), seller.toBytes()],
         programId
     );
 }
 
+function getFlipAddress(programId: PublicKey, order: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("Flip"), order.toBytes()],
+        programId
+    );
+}
+
+// Program account exists only when it is owned by the program (lamports alone do not create it)
+async function getProgramAccountData(
+    connection: Connection,
+    programId: PublicKey,
+    address: PublicKey
+): Promise<Buffer|null> {
+    let account = await connection.getAccountInfo(address);
+    if (!account || !account.owner.equals(programId)) {
+        return null;
+    }
+
+    return account.data;
+}
+
+// Index of the next order of the seller. Counter is created along with the first order
+async function getNextOrderIndex(
+    connection: Connection,
+    programId: PublicKey,
+    seller: PublicKey
+): Promise<bigint> {
+    let [orderCounter] = getOrderCounterAddress(programId, seller);
+    let data = await getProgramAccountData(connection, programId, orderCounter);
+    if (!data) {
+        return 0n;
+    }
+
+    return new DataView(data.buffer, data.byteOffset, data.length).getBigUint64(32, true);
+}
+
+// Payout split recipient wallets in the order the program pays them
+async function getPayoutRecipientWallets(
+    connection: Connection,
+    programId: PublicKey,
+    order: PublicKey
+): Promise<PublicKey[]> {
+    let [payoutSplit] = getPayoutSplitAddress(programId, order);
+    let data = await getProgramAccountData(connection, programId, payoutSplit);
+    if (!data) {
+        return [];
+    }
+
+    let recipients: PublicKey[] = [];
+    let recipientsCount = data[32];
+    for (let i = 0; i < recipientsCount; i++) {
+        let offset = 33 + i * 34;
+        recipients.push(new PublicKey(data.slice(offset, offset + 32)));
+    }
+
+    return recipients;
+}
+
 async function checkOrder(
     programId: PublicKey,
     order: &OrderDescriptionData,
@@ -161,7 +356,7 @@ async function checkOrder(
 
 
     if (orderAddress) {
-        let [expectedOrderAddress, _] = getOrderAddress(programId, order.seller, order.creationSlot);
+        let [expectedOrderAddress, _] = getOrderAddress(programId, order.seller, order.orderIndex);
         if (!expectedOrderAddress.equals(orderAddress)) {
             return `Order address not match. Expected: ${expectedOrderAddress}`;
         }
@@ -258,6 +453,7 @@ enum P2PSwapInstructions {
     CreatePrivateOrder = 2,
     RevokeOrder = 3,
     FillOrder = 4,
+    MigrateOrder = 49,
 }
 
 interface CreateOrderProps {
@@ -265,7 +461,8 @@ interface CreateOrderProps {
     sellAmount: bigint,
     buyAmount: bigint,
     minSellAmount: bigint,
-    creationSlot: bigint,
+    // read from order counter of the signer when not set
+    orderIndex?: bigint,
     signer: PublicKey,
     sellToken: PublicKey,
     buyToken: PublicKey,
@@ -276,6 +473,10 @@ interface CreateOrderProps {
 }
 
 function createOrderInstruction(props: CreateOrderProps): TransactionInstruction {
+    if (props.orderIndex === undefined) {
+        throw "Order index is not set";
+    }
+
     let signerWallet = getAssociatedTokenAddressSync(props.sellToken, props.signer, false);
     let [orderWalletAuthority] =
         props.orderWalletAuthority
@@ -290,10 +491,17 @@ function createOrderInstruction(props: CreateOrderProps): TransactionInstruction
     let [orderAccount] =
         props.orderAddress
         ? [props.orderAddress]
-        : getOrderAddress(props.programId, props.signer, props.creationSlot);
+        : getOrderAddress(props.programId, props.signer, props.orderIndex);
+
+    let [orderCounter] = getOrderCounterAddress(props.programId, props.signer);
+    let [market] = getMarketAddress(props.programId, props.sellToken, props.buyToken);
+    let [orderBook] = getOrderBookAddress(props.programId, props.sellToken, props.buyToken);
+    let [config] = getConfigAddress(props.programId);
+    let [sellTokenListEntry] = getMintListEntryAddress(props.programId, props.sellToken);
+    let [buyTokenListEntry] = getMintListEntryAddress(props.programId, props.buyToken);
+    let [sellerEpoch] = getSellerEpochAddress(props.programId, props.signer);
 
     let keys = [
-        { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
         { pubkey: props.signer, isSigner: true, isWritable: true },
         { pubkey: signerWallet, isSigner: false, isWritable: true },
         { pubkey: props.sellToken, isSigner: false, isWritable: false },
@@ -303,8 +511,20 @@ function createOrderInstruction(props: CreateOrderProps): TransactionInstruction
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
         { pubkey: orderAccount, isSigner: false, isWritable: true },
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: orderCounter, isSigner: false, isWritable: true },
+        { pubkey: market, isSigner: false, isWritable: false },
+        { pubkey: orderBook, isSigner: false, isWritable: true },
+        { pubkey: config, isSigner: false, isWritable: false },
+        { pubkey: sellTokenListEntry, isSigner: false, isWritable: false },
+        { pubkey: buyTokenListEntry, isSigner: false, isWritable: false },
+        // payout wallet - system program means associated token account of seller
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: sellerEpoch, isSigner: false, isWritable: false },
+        // gate mint - system program means order is not gated
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
     ];
 
+    // order starts immediately and is not gated: start_at kind and value, gate_min_balance
     let data = Buffer.concat([
         Buffer.from(int8ToBytes(
             props.isPrivate
@@ -314,7 +534,10 @@ function createOrderInstruction(props: CreateOrderProps): TransactionInstruction
         Buffer.from(int64ToBytes(props.sellAmount)),
         Buffer.from(int64ToBytes(props.buyAmount)),
         Buffer.from(int64ToBytes(props.minSellAmount)),
-        Buffer.from(int64ToBytes(props.creationSlot)),
+        Buffer.from(int64ToBytes(props.orderIndex)),
+        Buffer.from(int8ToBytes(StartAtKind.Immediately)),
+        Buffer.from(int64ToBytes(0n)),
+        Buffer.from(int64ToBytes(0n)),
     ]);
 
     return new TransactionInstruction({
@@ -328,9 +551,13 @@ async function createOrderTransaction(
     connection: Connection,
     props: CreateOrderProps,
 ): Promise<[Transaction, PublicKey]> {
+    let orderIndex =
+        props.orderIndex !== undefined
+        ? props.orderIndex
+        : await getNextOrderIndex(connection, props.programId, props.signer);
     let [orderWalletAuthority] = getOrderWalletAuthority(props.programId, props.signer);
     let orderWallet = getOrderWalletAddress(props.sellToken, orderWalletAuthority);
-    let [orderAccount] = getOrderAddress(props.programId, props.signer, props.creationSlot);
+    let [orderAccount] = getOrderAddress(props.programId, props.signer, orderIndex);
     let createWalletInstruction: TransactionInstruction|null = null;
 
     if (!await connection.getAccountInfo(orderWallet)) {
@@ -359,11 +586,28 @@ async function createOrderTransaction(
             props.sellAmount
         ));
 
-    transaction.add(createOrderInstruction(props));
+    transaction.add(createOrderInstruction({ ...props, orderIndex: orderIndex }));
 
     return [transaction, orderAccount];
 }
 
+// Converts order in legacy layout to the current one. Payer tops up rent of the grown account
+function migrateOrderInstruction(
+    programId: PublicKey,
+    payer: PublicKey,
+    orderAddress: PublicKey
+): TransactionInstruction {
+    return new TransactionInstruction({
+        keys: [
+            { pubkey: payer, isSigner: true, isWritable: true },
+            { pubkey: orderAddress, isSigner: false, isWritable: true },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        programId: programId,
+        data: Buffer.from(int8ToBytes(P2PSwapInstructions.MigrateOrder)),
+    });
+}
+
 interface FillOrderProps {
     signer: PublicKey,
     programId: PublicKey,
@@ -379,7 +623,41 @@ interface FillOrderWallets {
     buyerSellTokenWallet: PublicKey,
 }
 
+// Flip account followed by reverse order, its wallet and order book. System program is passed in
+// place of reverse order accounts when the order is not a flip order
+async function getFlipAccounts(
+    connection: Connection,
+    programId: PublicKey,
+    orderAddress: PublicKey
+): Promise<AccountMeta[]> {
+    let [flip] = getFlipAddress(programId, orderAddress);
+    let flipData = await getProgramAccountData(connection, programId, flip);
+    let reverseOrderAddress = flipData ? new PublicKey(flipData.slice(32, 64)) : null;
+    let reverseOrderData = reverseOrderAddress
+        ? await getProgramAccountData(connection, programId, reverseOrderAddress)
+        : null;
+
+    if (!reverseOrderAddress || !reverseOrderData) {
+        return [
+            { pubkey: flip, isSigner: false, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ];
+    }
+
+    let reverseOrder = parseOrderDescription(reverseOrderData);
+    let [reverseOrderBook] = getOrderBookAddress(programId, reverseOrder.tokenMint, reverseOrder.priceMint);
+    return [
+        { pubkey: flip, isSigner: false, isWritable: false },
+        { pubkey: reverseOrderAddress, isSigner: false, isWritable: true },
+        { pubkey: reverseOrder.orderWallet, isSigner: false, isWritable: true },
+        { pubkey: reverseOrderBook, isSigner: false, isWritable: true },
+    ];
+}
+
 async function createFillInstruction(
+    connection: Connection,
     props: FillOrderProps,
     wallets: FillOrderWallets
 ): Promise<TransactionInstruction> {
@@ -402,18 +680,50 @@ async function createFillInstruction(
     }
 
     let [orderWalletAuthority] = getOrderWalletAuthority(props.programId, props.order.seller);
+    let tokenMint = props.order.sellToken.address;
+    let priceMint = props.order.buyToken.address;
+    let [market] = getMarketAddress(props.programId, tokenMint, priceMint);
+    let [orderBook] = getOrderBookAddress(props.programId, tokenMint, priceMint);
+    let [config] = getConfigAddress(props.programId);
+    let [payoutSplit] = getPayoutSplitAddress(props.programId, props.orderAddress);
+    let [sellerReputation] = getReputationAddress(props.programId, props.order.seller);
+    let [buyerReputation] = getReputationAddress(props.programId, props.signer);
+    let [tradeReceipt] = getTradeReceiptAddress(props.programId, props.orderAddress, props.signer);
+    let [sellerEpoch] = getSellerEpochAddress(props.programId, props.order.seller);
+    let buyerGateWallet = props.order.gateMint.equals(PublicKey.default)
+        ? SystemProgram.programId
+        : getAssociatedTokenAddressSync(props.order.gateMint, props.signer, false);
 
     keys.push(
       { pubkey: orderWalletAuthority, isSigner: false, isWritable: false },
-      { pubkey: props.order.sellToken.address, isSigner: false, isWritable: false },
+      { pubkey: tokenMint, isSigner: false, isWritable: false },
       { pubkey: props.order.orderWallet, isSigner: false, isWritable: true },
-      { pubkey: props.order.buyToken.address, isSigner: false, isWritable: false },
+      { pubkey: priceMint, isSigner: false, isWritable: false },
       { pubkey: wallets.buyerBuyTokenWallet, isSigner: false, isWritable: true },
       { pubkey: wallets.sellerBuyTokenWallet, isSigner: false, isWritable: true },
+      // buyer receives order tokens to its own wallet
       { pubkey: wallets.buyerSellTokenWallet, isSigner: false, isWritable: true },
+      { pubkey: props.signer, isSigner: false, isWritable: false },
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      { pubkey: market, isSigner: false, isWritable: false },
+      { pubkey: orderBook, isSigner: false, isWritable: true },
+      { pubkey: config, isSigner: false, isWritable: false },
+      { pubkey: payoutSplit, isSigner: false, isWritable: false },
+      ...await getFlipAccounts(connection, props.programId, props.orderAddress),
+      { pubkey: sellerReputation, isSigner: false, isWritable: true },
+      { pubkey: buyerReputation, isSigner: false, isWritable: true },
+      { pubkey: tradeReceipt, isSigner: false, isWritable: true },
+      { pubkey: sellerEpoch, isSigner: false, isWritable: false },
+      { pubkey: buyerGateWallet, isSigner: false, isWritable: false },
     )
 
+    let payoutRecipients = await getPayoutRecipientWallets(connection, props.programId, props.orderAddress);
+    for (let recipient of payoutRecipients) {
+        keys.push({ pubkey: recipient, isSigner: false, isWritable: true });
+    }
+
     let data: Buffer = Buffer.concat([
         Buffer.from(int8ToBytes(P2PSwapInstructions.FillOrder)),
         Buffer.from(int64ToBytes(props.sellTokenAmount)),
@@ -448,11 +758,18 @@ async function fillOrderTransaction(
             props.signer,
             false
         ),
-        sellerBuyTokenWallet: await getAssociatedTokenAddress(
-            props.order.buyToken.address,
-            props.order.seller,
-            false
-        )
+        sellerBuyTokenWallet: props.order.payoutWallet.equals(PublicKey.default)
+            ? await getAssociatedTokenAddress(
+                props.order.buyToken.address,
+                props.order.seller,
+                false
+            )
+            : props.order.payoutWallet
+    }
+
+    if (props.order.isLegacy) {
+        // buyer pays the rent difference of the grown order account
+        transaction.add(migrateOrderInstruction(props.programId, props.signer, props.orderAddress));
     }
 
     if (!await connection.getAccountInfo(wallets.sellerBuyTokenWallet)) {
@@ -475,7 +792,9 @@ async function fillOrderTransaction(
         ));
     }
 
-    let buyTokenAmount = (props.sellTokenAmount * props.order.buyAmount) / props.order.sellAmount;
+    // program rounds the payment up, so seller never gets less than the order price
+    let price = props.sellTokenAmount * props.order.buyAmount;
+    let buyTokenAmount = (price + props.order.sellAmount - 1n) / props.order.sellAmount;
 
     // Allow p2p-swap program to transfer funds from signer's token account
     transaction.add(
@@ -498,7 +817,85 @@ async function fillOrderTransaction(
         }));
     }
 
-    transaction.add(await createFillInstruction(props, wallets));
+    transaction.add(await createFillInstruction(connection, props, wallets));
+
+    return transaction;
+}
+
+interface RevokeOrderProps {
+    signer: PublicKey,
+    programId: PublicKey,
+    // 0 revokes all remaining tokens
+    revokeAmount: bigint,
+    orderAddress: PublicKey,
+    order: OrderDescriptionData,
+}
+
+// Seller revokes own order. Only the first 7 accounts are required, the rest are passed only when
+// the order uses them and trailing ones are omitted
+async function revokeOrderTransaction(
+    connection: Connection,
+    props: RevokeOrderProps,
+): Promise<Transaction> {
+    const { blockhash } = await connection.getLatestBlockhash();
+    let transaction = new Transaction({ recentBlockhash: blockhash, feePayer: props.signer });
+
+    if (props.order.isLegacy) {
+        transaction.add(migrateOrderInstruction(props.programId, props.signer, props.orderAddress));
+    }
+
+    let [orderWalletAuthority] = getOrderWalletAuthority(props.programId, props.order.seller);
+    let sellerWallet = getAssociatedTokenAddressSync(props.order.tokenMint, props.order.seller, false);
+    if (!await connection.getAccountInfo(sellerWallet)) {
+        transaction.add(createAssociatedTokenAccountInstruction(
+            props.signer,
+            sellerWallet,
+            props.order.seller,
+            props.order.tokenMint
+        ));
+    }
+
+    let keys: AccountMeta[] = [
+        { pubkey: props.signer, isSigner: true, isWritable: true },
+        { pubkey: props.order.seller, isSigner: true, isWritable: true },
+        { pubkey: props.orderAddress, isSigner: false, isWritable: true },
+        { pubkey: orderWalletAuthority, isSigner: false, isWritable: false },
+        { pubkey: props.order.orderWallet, isSigner: false, isWritable: true },
+        { pubkey: sellerWallet, isSigner: false, isWritable: true },
+        { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+    ];
+
+    // optional accounts 7..12, system program skips an account
+    let skipped: AccountMeta = { pubkey: SystemProgram.programId, isSigner: false, isWritable: false };
+    let [orderBook] = getOrderBookAddress(props.programId, props.order.tokenMint, props.order.priceMint);
+    let [sellerReputation] = getReputationAddress(props.programId, props.order.seller);
+    let [payoutSplit] = getPayoutSplitAddress(props.programId, props.orderAddress);
+    let [flip] = getFlipAddress(props.programId, props.orderAddress);
+    let hasReputation = await getProgramAccountData(connection, props.programId, sellerReputation) !== null;
+    let hasPayoutSplit = await getProgramAccountData(connection, props.programId, payoutSplit) !== null;
+    let hasFlip = await getProgramAccountData(connection, props.programId, flip) !== null;
+    let optional: AccountMeta[] = [
+        props.order.isListed ? { pubkey: orderBook, isSigner: false, isWritable: true } : skipped,
+        hasReputation ? { pubkey: sellerReputation, isSigner: false, isWritable: true } : skipped,
+        // caller delegate and seller epoch are needed only when operator or anyone else revokes
+        skipped,
+        skipped,
+        hasPayoutSplit ? { pubkey: payoutSplit, isSigner: false, isWritable: true } : skipped,
+        hasFlip ? { pubkey: flip, isSigner: false, isWritable: true } : skipped,
+    ];
+    while (optional.length > 0 && optional[optional.length - 1] === skipped) {
+        optional.pop();
+    }
+    keys.push(...optional);
+
+    transaction.add(new TransactionInstruction({
+        keys: keys,
+        programId: props.programId,
+        data: Buffer.concat([
+            Buffer.from(int8ToBytes(P2PSwapInstructions.RevokeOrder)),
+            Buffer.from(int64ToBytes(props.revokeAmount)),
+        ]),
+    }));
 
     return transaction;
 }
@@ -551,9 +948,14 @@ const P2P_SWAP_DEVNET = new PublicKey("AzVuKVf8qQjHBTyjEUZbr6zRvinZvjpuFZWMXPd76
 
 export {
     ORDER_ACCOUNT_SIZE,
+    LEGACY_ORDER_ACCOUNT_SIZE,
+    OrderStatus,
+    StartAtKind,
+    type StartAt,
     type OrderDescriptionData,
     type CreateOrderProps,
     type FillOrderProps,
+    type RevokeOrderProps,
     type WalletToken,
     parseOrderDescription,
     getOrderDescription,
@@ -567,6 +969,9 @@ export {
     createOrderInstruction,
     createOrderTransaction,
     fillOrderTransaction,
+    revokeOrderTransaction,
+    migrateOrderInstruction,
+    getNextOrderIndex,
     getTokens,
     P2P_SWAP_DEVNET,
 }
\ No newline at end of file